@@ -0,0 +1,37 @@
+use apex_framework::{core::Core, input::action::Action};
+
+use crate::client::{
+  client::{Client, GameState},
+  event::ClientEvent,
+};
+
+pub struct Autoplay;
+pub struct Cinema;
+
+impl Action<Client> for Autoplay {
+  fn execute(client: &mut Client, _core: &mut Core<Client>, repeat: bool) -> bool {
+    return watch_autoplay(client, repeat, false);
+  }
+}
+
+impl Action<Client> for Cinema {
+  fn execute(client: &mut Client, _core: &mut Core<Client>, repeat: bool) -> bool {
+    return watch_autoplay(client, repeat, true);
+  }
+}
+
+fn watch_autoplay(client: &mut Client, repeat: bool, cinema: bool) -> bool {
+  if repeat || client.game_state != GameState::Selection {
+    return false;
+  }
+
+  let selected_idx = client.selection_screen.beatmap_selector().selected();
+  let Some((beatmap_hash, _)) = client.beatmap_cache.get_index(selected_idx) else {
+    log::error!("Failed to select beatmap, no beatmap with cache idx `{}` found.", selected_idx);
+    return true;
+  };
+
+  client.event_bus.send(ClientEvent::WatchAutoplay { beatmap_hash, cinema });
+
+  return true;
+}
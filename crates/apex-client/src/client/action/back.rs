@@ -36,6 +36,16 @@ impl Action<Client> for Back {
         client.game_state = GameState::Playing;
       }
 
+      GameState::Failed => {
+        client.pause_screen.quit();
+      }
+
+      GameState::Replay => {
+        client.audio.apply_mods(client.selection_screen.mods());
+        client.audio.set_playing(true);
+        client.game_state = GameState::Selection;
+      }
+
       GameState::Results => {
         client.game_state = GameState::Selection;
       }
@@ -1,3 +1,4 @@
+use autoplay::{Autoplay, Cinema};
 use back::Back;
 use clear_query::ClearQuery;
 use debug::Debug;
@@ -15,6 +16,7 @@ use apex_framework::{actions, input::keybinds::KeyCombination, key_comb};
 
 use super::client::Client;
 
+pub mod autoplay;
 pub mod back;
 pub mod clear_query;
 pub mod debug;
@@ -48,6 +50,10 @@ actions! {
     Prev = key_comb!(ArrowUp),
     /// Pick selected element
     Select = key_comb!(Enter),
+    /// Watch autoplay on the selected beatmap
+    Autoplay = key_comb!(Ctrl + Enter),
+    /// Watch autoplay on the selected beatmap without the HUD
+    Cinema = key_comb!(Shift + Enter),
 
     /// Restart the beatmap
     Retry = key_comb!(Backquote),
@@ -14,7 +14,7 @@ impl Action<Client> for Next {
         return true;
       }
 
-      GameState::Paused => {
+      GameState::Paused | GameState::Failed => {
         client.pause_screen.select_down();
       }
 
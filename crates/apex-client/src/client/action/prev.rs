@@ -14,7 +14,7 @@ impl Action<Client> for Prev {
         return true;
       }
 
-      GameState::Paused => {
+      GameState::Paused | GameState::Failed => {
         client.pause_screen.select_up();
       }
 
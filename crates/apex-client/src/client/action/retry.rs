@@ -14,7 +14,14 @@ impl Action<Client> for Retry {
     }
 
     match client.game_state {
-      GameState::Playing => {
+      GameState::Playing | GameState::Replay => {
+        client.event_bus.send(ClientEvent::RetryBeatmap);
+
+        return true;
+      }
+
+      GameState::Failed => {
+        client.game_state = GameState::Playing;
         client.event_bus.send(ClientEvent::RetryBeatmap);
 
         return true;
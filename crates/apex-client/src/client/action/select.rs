@@ -22,7 +22,7 @@ impl Action<Client> for Select {
         return true;
       }
 
-      GameState::Paused => {
+      GameState::Paused | GameState::Failed => {
         client.pause_screen.click();
       }
 
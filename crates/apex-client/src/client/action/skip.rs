@@ -7,7 +7,7 @@ pub struct Skip;
 impl Action<Client> for Skip {
   fn execute(client: &mut Client, _core: &mut Core<Client>, _repeat: bool) -> bool {
     match client.game_state {
-      GameState::Playing => {
+      GameState::Playing | GameState::Replay => {
         client.gameplay_screen.skip_break(&mut client.audio, Time::from_seconds(1.0));
 
         return true;
@@ -1,14 +1,20 @@
-use std::{fs::File, path::Path};
+use std::{
+  fs::File,
+  io::{self, BufReader},
+  path::Path,
+};
 
 use apex_framework::{
   audio::{
     arc_buffer::ArcSamplesBuffer, audio_engine::AudioEngine, audio_mixer::AudioController,
-    frameless_source::FramelessSource, lead_in::lead_in,
+    frameless_source::FramelessSource, lead_in::lead_in, rate_source::rate_source,
   },
   time::{clock::AbstractClock, time::Time},
 };
 use rodio::{source::UniformSourceIterator, Decoder, Device, DeviceTrait as _, Source};
 
+use crate::client::gameplay::mods::Mods;
+
 /// Audio wrapper which allows for leading and trailing additional delays, or other gameplay-specific things.
 pub struct GameAudio {
   audio_engine: AudioEngine,
@@ -49,13 +55,20 @@ impl GameAudio {
     return self;
   }
 
+  /// Plays `source` as the music, which follows the speed of the clock. Sounds keep playing in realtime.
   pub fn set_source<S>(&mut self, source: S)
   where
     S: Source<Item = f32> + Send + Sync + 'static,
   {
-    self
-      .audio_controller
-      .play_audio(lead_in(source, std::time::Duration::from_millis(self.lead_in.to_ms() as u64)));
+    let source = lead_in(source, std::time::Duration::from_millis(self.lead_in.to_ms() as u64));
+    let playback_rate = self.audio_engine.playback_rate().clone();
+    self.audio_controller.play_audio(rate_source(source, playback_rate));
+  }
+
+  /// Plays the music at the rate of `mods`, with or without changing its pitch.
+  pub fn apply_mods(&mut self, mods: Mods) {
+    self.audio_engine.playback_rate().set_preserve_pitch(mods.preserves_pitch());
+    self.set_speed(mods.rate());
   }
 
   pub fn device(&self) -> &Device {
@@ -83,14 +96,20 @@ impl GameAudio {
   }
 
   pub fn load_sound(&self, path: impl AsRef<Path>) -> ArcSamplesBuffer<f32> {
+    return self.try_load_sound(path).unwrap();
+  }
+
+  /// Same as [`GameAudio::load_sound`], but missing or undecodable files are returned as errors.
+  pub fn try_load_sound(&self, path: impl AsRef<Path>) -> io::Result<ArcSamplesBuffer<f32>> {
     let channels = self.config.channels();
     let sample_rate = self.config.sample_rate();
-    let source = Decoder::new(File::open(path).unwrap()).unwrap();
+    let source =
+      Decoder::new(BufReader::new(File::open(path)?)).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
     // FramelessSource is needed for a audio desync workaround, see https://github.com/RustAudio/rodio/issues/316
     let source = UniformSourceIterator::new(FramelessSource::new(source), channels, sample_rate.0);
 
-    return ArcSamplesBuffer::<f32>::new(channels, sample_rate.0, source.collect::<Vec<_>>());
+    return Ok(ArcSamplesBuffer::<f32>::new(channels, sample_rate.0, source.collect::<Vec<_>>()));
   }
 }
 
@@ -110,7 +129,8 @@ impl AbstractClock for GameAudio {
   fn position(&mut self) -> Time {
     let pos = self.audio_engine.position();
 
-    return pos - self.lead_in + self.audio_offset;
+    // The offset makes up for latency, which passes in realtime
+    return pos - self.lead_in + self.audio_offset * self.speed();
   }
 
   fn set_position(&mut self, position: Time) {
@@ -124,6 +144,14 @@ impl AbstractClock for GameAudio {
   fn set_length(&mut self, value: Time) {
     self.audio_engine.set_length(value);
   }
+
+  fn speed(&self) -> f64 {
+    return self.audio_engine.speed();
+  }
+
+  fn set_speed(&mut self, value: f64) {
+    self.audio_engine.set_speed(value);
+  }
 }
 
 pub struct GameAudioController(AudioController);
@@ -0,0 +1,171 @@
+use std::path::{Path, PathBuf};
+
+use ahash::AHashMap;
+use apex_framework::audio::arc_buffer::ArcSamplesBuffer;
+use rodio::Source as _;
+
+use crate::client::gameplay::{
+  beatmap::{Beatmap, SampleSet},
+  taiko_hit_object::HitSamples,
+};
+
+use super::game_audio::{GameAudio, GameAudioController};
+
+/// Directory of the skin, its samples replace the default ones.
+const SKIN_DIR: &str = "./skin";
+
+/// Sample file extensions in the order they are looked up.
+const SAMPLE_EXTENSIONS: [&str; 3] = ["wav", "ogg", "mp3"];
+
+/// Sound of a sample set which is played when hitting an object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HitSound {
+  /// Played for don.
+  Normal,
+
+  /// Played for kat.
+  Clap,
+
+  /// Played on top of the others when hitting a big circle.
+  Finish,
+}
+
+impl HitSound {
+  pub const ALL: [HitSound; 3] = [HitSound::Normal, HitSound::Clap, HitSound::Finish];
+
+  fn name(self) -> &'static str {
+    return match self {
+      HitSound::Normal => "hitnormal",
+      HitSound::Clap => "hitclap",
+      HitSound::Finish => "hitfinish",
+    };
+  }
+
+  /// Sample which is played if neither the beatmap nor the skin have one.
+  fn default_path(self) -> Option<&'static str> {
+    return match self {
+      HitSound::Normal => Some("./assets/red.wav"),
+      HitSound::Clap => Some("./assets/blue.wav"),
+      HitSound::Finish => None,
+    };
+  }
+
+  /// Normal sounds use the sample set of the object, everything else is an addition.
+  fn sample_set(self, samples: &HitSamples) -> SampleSet {
+    return match self {
+      HitSound::Normal => samples.sample_set,
+      HitSound::Clap | HitSound::Finish => samples.addition_set,
+    };
+  }
+}
+
+fn sample_set_name(sample_set: SampleSet) -> &'static str {
+  return match sample_set {
+    SampleSet::Auto | SampleSet::Normal => "normal",
+    SampleSet::Soft => "soft",
+    SampleSet::Drum => "drum",
+  };
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct SampleKey {
+  sample_set: SampleSet,
+  index: u32,
+  sound: HitSound,
+}
+
+impl SampleKey {
+  fn new(samples: &HitSamples, sound: HitSound) -> Self {
+    return Self {
+      sample_set: sound.sample_set(samples),
+      index: samples.index,
+      sound,
+    };
+  }
+}
+
+/// Finds the file of a sample, looking in the beatmap's directory first, then in the skin and then in the defaults.
+///
+/// Samples with a custom index of 0 always come from the skin. An index of 1 uses the beatmap's sample without a
+/// number suffix, e.g. `taiko-soft-hitclap.wav`, higher ones use numbered files like `taiko-soft-hitclap2.wav`.
+pub fn resolve_sample(beatmap_dir: &Path, sample_set: SampleSet, index: u32, sound: HitSound) -> Option<PathBuf> {
+  let name = format!("taiko-{}-{}", sample_set_name(sample_set), sound.name());
+
+  let mut candidates = Vec::new();
+  match index {
+    0 => {}
+    1 => candidates.push(beatmap_dir.join(&name)),
+    index => candidates.push(beatmap_dir.join(format!("{}{}", name, index))),
+  }
+
+  candidates.push(Path::new(SKIN_DIR).join(&name));
+
+  for candidate in candidates {
+    for extension in SAMPLE_EXTENSIONS {
+      let path = candidate.with_extension(extension);
+
+      if path.is_file() {
+        return Some(path);
+      }
+    }
+  }
+
+  return sound.default_path().map(PathBuf::from);
+}
+
+/// Hitsound samples of a beatmap, loaded up front so playing them never has to touch the disk.
+#[derive(Default)]
+pub struct HitsoundBank {
+  /// Samples which resolved to a file that can't be decoded (e.g. empty files used to mute hitsounds) are `None`.
+  samples: AHashMap<SampleKey, Option<ArcSamplesBuffer<f32>>>,
+}
+
+impl HitsoundBank {
+  /// Resolves and loads every sample the hit objects of the beatmap can play.
+  pub fn load(beatmap: &Beatmap, audio: &GameAudio) -> Self {
+    let beatmap_dir = beatmap.file_path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+    let mut samples = AHashMap::<SampleKey, Option<ArcSamplesBuffer<f32>>>::new();
+    let mut buffers = AHashMap::<PathBuf, Option<ArcSamplesBuffer<f32>>>::new();
+
+    // Hits far away from any object use the default samples
+    let default_samples = HitSamples::default();
+    let object_samples = beatmap.hit_objects.iter().map(|x| x.samples());
+
+    for hit_samples in std::iter::once(&default_samples).chain(object_samples) {
+      for sound in HitSound::ALL {
+        let key = SampleKey::new(hit_samples, sound);
+        if samples.contains_key(&key) {
+          continue;
+        }
+
+        let buffer = resolve_sample(&beatmap_dir, key.sample_set, key.index, sound).and_then(|path| {
+          let buffer = buffers.entry(path).or_insert_with_key(|path| {
+            match audio.try_load_sound(path) {
+              Ok(buffer) => Some(buffer),
+              Err(e) => {
+                log::warn!("Failed to load hitsound {:?}: {}", path, e);
+                None
+              }
+            }
+          });
+
+          return buffer.clone();
+        });
+
+        samples.insert(key, buffer);
+      }
+    }
+
+    return Self { samples };
+  }
+
+  /// Plays a sound of the given samples at their volume, does nothing if it was not loaded.
+  pub fn play(&self, audio: &GameAudioController, samples: &HitSamples, sound: HitSound) {
+    let Some(Some(buffer)) = self.samples.get(&SampleKey::new(samples, sound)) else {
+      return;
+    };
+
+    audio.play_sound(buffer.clone().amplify(samples.volume as f32 / 100.0));
+  }
+}
@@ -2,6 +2,7 @@ use rodio::{cpal::traits::HostTrait, DeviceTrait};
 use serde::{Deserialize, Serialize};
 
 pub mod game_audio;
+pub mod hitsound_bank;
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct AudioOutput(String);
@@ -1,6 +1,6 @@
 use std::{
   fs::File,
-  io::BufReader,
+  io::{BufReader, BufWriter},
   num::NonZero,
   path::{Path, PathBuf},
   sync::atomic::AtomicBool,
@@ -48,18 +48,27 @@ use super::{
   audio::game_audio::GameAudio,
   event::ClientEvent,
   gameplay::{
-    beatmap::Beatmap,
-    beatmap_cache::{BeatmapCache, BeatmapInfo},
+    beatmap_cache::BeatmapCache,
+    beatmap_collections::BeatmapCollections,
+    beatmap_importer::{sanitize_file_name, BeatmapImporter},
+    beatmap_set::BeatmapSet,
+    mods::Mod,
   },
   graphics::{FrameLimiterOptions, RenderingBackend},
   score::score_cache::ScoreCache,
   screen::{
     debug_screen::debug_screen::DebugScreen, gameplay_screen::gameplay_screen::GameplayScreen,
-    pause_screen::pause_screen::PauseScreen, recording_screen::recording_screen::RecordingScreen,
-    result_screen::result_screen::ResultScreen, selection_screen::selection_screen::SelectionScreen,
-    settings_screen::settings_screen::SettingsScreen, volume_screen::VolumeScreen,
+    loading_screen::loading_screen::LoadingScreen, pause_screen::pause_screen::PauseScreen,
+    recording_screen::recording_screen::RecordingScreen, result_screen::result_screen::ResultScreen,
+    selection_screen::selection_screen::SelectionScreen, settings_screen::settings_screen::SettingsScreen,
+    volume_screen::VolumeScreen,
   },
   settings::{proxy::ClientSettingsProxy, Settings},
+  stable::{
+    osr::OsrReplay,
+    osr_error::OsrError,
+    stable_import::{self, StableImport},
+  },
 };
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -67,6 +76,8 @@ pub enum GameState {
   Selection,
   Playing,
   Paused,
+  Replay,
+  Failed,
   Results,
 }
 
@@ -81,11 +92,17 @@ pub struct Client {
   pub(crate) settings: Settings,
 
   pub(crate) beatmap_cache: BeatmapCache,
+  pub(crate) beatmap_importer: BeatmapImporter,
+  pub(crate) beatmap_collections: BeatmapCollections,
   pub(crate) score_cache: ScoreCache,
 
+  /// osu!stable import waiting for its beatmap sets to be loaded.
+  pub(crate) stable_import: Option<StableImport>,
+
+  /// Audio of the previously selected set, switching between its difficulties keeps the preview playing.
   pub(crate) prev_audio_path: PathBuf,
-  pub(crate) prev_beatmap_path: PathBuf,
 
+  pub(crate) loading_screen: LoadingScreen,
   pub(crate) selection_screen: SelectionScreen,
   pub(crate) gameplay_screen: GameplayScreen,
   pub(crate) result_screen: ResultScreen,
@@ -179,16 +196,35 @@ impl App for Client {
 
     self.debug_screen.prepare(core);
 
+    self.beatmap_cache.poll();
+
+    // Scores and collections are matched to beatmaps by the MD5 of their files, so the new sets have to be loaded first
+    if !self.beatmap_cache.is_scanning() {
+      if let Some(import) = self.stable_import.take() {
+        self.finish_stable_import(import);
+      }
+    }
+
+    let import_event_bus = self.event_bus.clone();
+    self.beatmap_importer.start(&self.beatmap_cache, move |archive, result| {
+      import_event_bus.send(ClientEvent::BeatmapImported { archive, result });
+    });
+
     match self.game_state {
       GameState::Selection => {
-        self.selection_screen.prepare(core, &self.beatmap_cache, &mut self.score_cache, &mut self.audio);
+        // There is nothing to select until the scan finds the first beatmaps
+        if !self.beatmap_cache.is_scanning() || !self.beatmap_cache.is_empty() {
+          self.selection_screen.prepare(core, &self.beatmap_cache, &mut self.score_cache, &mut self.audio);
+        }
+
+        self.loading_screen.prepare(core.egui.ctx(), &self.beatmap_cache);
       }
 
-      GameState::Playing => {
+      GameState::Playing | GameState::Replay => {
         self.gameplay_screen.prepare(core, &mut self.audio, &self.settings);
       }
 
-      GameState::Paused => {
+      GameState::Paused | GameState::Failed => {
         self.gameplay_screen.prepare(core, &mut self.audio, &self.settings);
         self.pause_screen.prepare(
           core,
@@ -205,6 +241,9 @@ impl App for Client {
       }
     }
 
+    // The selection screen is the only consumer of the cache's changes
+    self.beatmap_cache.acknowledge_changes(self.selection_screen.seen_changes());
+
     if self.settings.interface.gameplay.letterboxing() {
       self.backbuffer.prepare(&core.graphics.queue);
     }
@@ -353,10 +392,21 @@ impl App for Client {
   fn dispatch(&mut self, core: &mut Core<Self>, event: ClientEvent) {
     match event {
       ClientEvent::PickBeatmap { beatmap_hash } => {
-        let beatmap_info = self.beatmap_cache.get(beatmap_hash).unwrap();
-        let beatmap = Beatmap::from_path(&beatmap_info.file_path);
+        // The beatmap might have been removed since it was picked
+        let Some(beatmap_info) = self.beatmap_cache.get(beatmap_hash) else {
+          log::error!("Failed to load beatmap {}: no longer in the cache", beatmap_hash);
+          return;
+        };
+        let beatmap = match beatmap_info.load_beatmap() {
+          Ok(beatmap) => beatmap,
+          Err(e) => {
+            log::error!("Failed to load beatmap {:?}: {}", beatmap_info.file_path, e);
+            return;
+          }
+        };
 
-        self.gameplay_screen.play(beatmap, &core.graphics, &mut self.audio);
+        let mods = self.selection_screen.mods();
+        self.gameplay_screen.play(beatmap, mods, &core.graphics, &mut self.audio);
         self.game_state = GameState::Playing;
       }
 
@@ -373,40 +423,216 @@ impl App for Client {
       }
 
       ClientEvent::ShowResultScreen { beatmap_hash, score } => {
-        let beatmap_info = self.beatmap_cache.get(beatmap_hash).unwrap();
-        let beatmap = Beatmap::from_path(&beatmap_info.file_path);
         self.score_cache.insert(beatmap_hash, score.clone());
-        self.result_screen.set_score(beatmap, score);
         self.selection_screen.update_scores(&mut self.score_cache, beatmap_hash);
+
+        let Some(beatmap_info) = self.beatmap_cache.get(beatmap_hash) else {
+          log::error!("Failed to load beatmap {}: no longer in the cache", beatmap_hash);
+          return;
+        };
+        let beatmap = match beatmap_info.load_beatmap() {
+          Ok(beatmap) => beatmap,
+          Err(e) => {
+            log::error!("Failed to load beatmap {:?}: {}", beatmap_info.file_path, e);
+            return;
+          }
+        };
+
+        self.result_screen.set_score(beatmap_info.clone(), beatmap, score);
         self.game_state = GameState::Results;
       }
 
       ClientEvent::ViewScore { beatmap_hash, score_id } => {
-        let beatmap_info = self.beatmap_cache.get(beatmap_hash).unwrap();
-        let beatmap = Beatmap::from_path(&beatmap_info.file_path);
+        let Some(beatmap_info) = self.beatmap_cache.get(beatmap_hash) else {
+          log::error!("Failed to load beatmap {}: no longer in the cache", beatmap_hash);
+          return;
+        };
+        let beatmap = match beatmap_info.load_beatmap() {
+          Ok(beatmap) => beatmap,
+          Err(e) => {
+            log::error!("Failed to load beatmap {:?}: {}", beatmap_info.file_path, e);
+            return;
+          }
+        };
+
         let score = self.score_cache.score_details(score_id);
-        self.result_screen.set_score(beatmap, score.clone());
+        self.result_screen.set_score(beatmap_info.clone(), beatmap, score.clone());
+        self.game_state = GameState::Results;
+      }
+
+      ClientEvent::ExportReplay { beatmap_hash, score_id } => {
+        let Some(beatmap_info) = self.beatmap_cache.get(beatmap_hash) else {
+          log::error!("Failed to export replay of {}: beatmap no longer in the cache", beatmap_hash);
+          return;
+        };
+
+        let replay = OsrReplay {
+          beatmap_md5: beatmap_info.file_md5.clone(),
+          score: self.score_cache.score_details(score_id).clone(),
+        };
+
+        let name = format!(
+          "{} - {} - {} [{}] ({}) Taiko.osr",
+          replay.score.username(),
+          beatmap_info.artist,
+          beatmap_info.title,
+          beatmap_info.variant,
+          replay.score.date().strftime("%Y-%m-%d_%H-%M-%S"),
+        );
+        let path = Path::new("./replays").join(sanitize_file_name(&name));
+
+        let result = std::fs::create_dir_all("./replays")
+          .and_then(|_| File::create(&path))
+          .map_err(OsrError::from)
+          .and_then(|file| replay.write(BufWriter::new(file)));
+
+        match result {
+          Ok(()) => log::info!("Exported replay to {:?}", path),
+          Err(e) => log::error!("Failed to export replay to {:?}: {}", path, e),
+        }
+      }
+
+      ClientEvent::WatchReplay { beatmap_hash, score } => {
+        // Scores imported from osu!stable databases come without their replay
+        if score.hits().is_empty() {
+          log::error!("Failed to watch replay: score of {} has no recorded inputs", score.username());
+          return;
+        }
+
+        let Some(beatmap_info) = self.beatmap_cache.get(beatmap_hash) else {
+          log::error!("Failed to load beatmap {}: no longer in the cache", beatmap_hash);
+          return;
+        };
+        let beatmap = match beatmap_info.load_beatmap() {
+          Ok(beatmap) => beatmap,
+          Err(e) => {
+            log::error!("Failed to load beatmap {:?}: {}", beatmap_info.file_path, e);
+            return;
+          }
+        };
+
+        self.gameplay_screen.watch(beatmap, score, &core.graphics, &mut self.audio);
+        self.game_state = GameState::Replay;
+      }
+
+      ClientEvent::WatchAutoplay { beatmap_hash, cinema } => {
+        let Some(beatmap_info) = self.beatmap_cache.get(beatmap_hash) else {
+          log::error!("Failed to load beatmap {}: no longer in the cache", beatmap_hash);
+          return;
+        };
+        let beatmap = match beatmap_info.load_beatmap() {
+          Ok(beatmap) => beatmap,
+          Err(e) => {
+            log::error!("Failed to load beatmap {:?}: {}", beatmap_info.file_path, e);
+            return;
+          }
+        };
+
+        let mods = self.selection_screen.mods();
+        self.gameplay_screen.autoplay(beatmap, mods, cinema, &core.graphics, &mut self.audio);
+        self.game_state = GameState::Replay;
+      }
+
+      ClientEvent::ReplayEnded { beatmap_hash, score } => {
+        // Back to the rate of the picked mods, the replay might have been watched with others or at another speed
+        self.audio.apply_mods(self.selection_screen.mods());
+
+        let Some(beatmap_info) = self.beatmap_cache.get(beatmap_hash) else {
+          log::error!("Failed to load beatmap {}: no longer in the cache", beatmap_hash);
+          return;
+        };
+        let beatmap = match beatmap_info.load_beatmap() {
+          Ok(beatmap) => beatmap,
+          Err(e) => {
+            log::error!("Failed to load beatmap {:?}: {}", beatmap_info.file_path, e);
+            return;
+          }
+        };
+
+        self.result_screen.set_score(beatmap_info.clone(), beatmap, score);
         self.game_state = GameState::Results;
       }
 
+      ClientEvent::Failed { beatmap_hash, score } => {
+        if score.mods().contains(Mod::Perfect) {
+          self.pause_screen.offer_restart();
+        }
+
+        self.score_cache.insert(beatmap_hash, score);
+        self.selection_screen.update_scores(&mut self.score_cache, beatmap_hash);
+        self.game_state = GameState::Failed;
+      }
+
+      ClientEvent::ModsChanged(mods) => {
+        self.audio.apply_mods(mods);
+      }
+
       ClientEvent::ToggleRecordingWindow => {
         if !self.recording_screen.is_open() {
           self.recording_screen.toggle();
         }
       }
+
+      ClientEvent::BeatmapScanProgress(progress) => {
+        self.loading_screen.set_progress(progress);
+      }
+
+      ClientEvent::BeatmapImported { archive, result } => {
+        match result {
+          Ok(directory) => {
+            log::info!("Imported {:?} into {:?}", archive, directory);
+
+            // The watcher would pick the set up as well, but only after debouncing
+            self.beatmap_cache.load_difficulties(directory);
+          }
+
+          Err(e) => {
+            log::error!("Failed to import {:?}: {}", archive, e);
+            self.selection_screen.import_failed(archive, e);
+          }
+        }
+      }
+
+      ClientEvent::ImportStable { osu_dir, transfer } => {
+        log::info!("Importing osu!stable installation {:?}", osu_dir);
+
+        let event_bus = self.event_bus.clone();
+        std::thread::spawn(move || {
+          let result = stable_import::import(&osu_dir, Path::new("./beatmaps"), transfer);
+          event_bus.send(ClientEvent::StableImported(result));
+        });
+      }
+
+      ClientEvent::StableImported(result) => {
+        let import = match result {
+          Ok(import) => import,
+          Err(e) => {
+            log::error!("Failed to import osu!stable installation: {}", e);
+            return;
+          }
+        };
+
+        // A rescan parses the new sets in the background instead of waiting for the watcher to load them one by one
+        if !import.directories.is_empty() {
+          let scan_event_bus = self.event_bus.clone();
+          self.beatmap_cache.load_beatmaps("./beatmaps", move |progress| {
+            scan_event_bus.send(ClientEvent::BeatmapScanProgress(progress));
+          });
+        }
+
+        self.stable_import = Some(import);
+      }
     }
   }
 
   fn file_dropped(&mut self, _core: &mut Core<Self>, path: PathBuf, file: Vec<u8>) {
-    // TODO: this logic should be moved to the beatmap manager or whatever
-    // TODO: properly parse beatmapset id
-    let beatmapset_id = path.file_name().unwrap().to_str().unwrap().split_whitespace().next().unwrap();
-    zip::read::ZipArchive::new(std::io::Cursor::new(file))
-      .unwrap()
-      .extract(format!("./beatmaps/{}", beatmapset_id))
-      .unwrap();
-
-    self.beatmap_cache.load_difficulties(format!("./beatmaps/{}", beatmapset_id));
+    if path.extension().is_some_and(|x| x.eq_ignore_ascii_case("osr")) {
+      self.import_replay(&path, &file);
+      return;
+    }
+
+    // Every dropped file arrives separately, they are imported together on the next frame
+    self.beatmap_importer.queue(path, file);
   }
 }
 
@@ -461,24 +687,33 @@ impl Client {
 
     let game_state = GameState::Selection;
 
-    let beatmap_cache = BeatmapCache::new().tap_mut(|cache| {
-      cache.load_beatmaps("./beatmaps");
+    // Beatmaps are indexed in the same database as the scores
+    let scan_event_bus = event_bus.clone();
+    let beatmap_cache = BeatmapCache::new(Connection::open("./scores.db").unwrap()).tap_mut(|cache| {
+      cache.load_beatmaps("./beatmaps", move |progress| {
+        scan_event_bus.send(ClientEvent::BeatmapScanProgress(progress));
+      });
+
+      cache.watch("./beatmaps");
     });
 
+    let beatmap_importer = BeatmapImporter::new("./beatmaps");
+    let beatmap_collections = BeatmapCollections::new(Connection::open("./scores.db").unwrap());
+
     let conn = Connection::open("./scores.db").unwrap();
     let score_cache = ScoreCache::new(conn);
 
+    #[rustfmt::skip] let loading_screen = LoadingScreen::new();
     #[rustfmt::skip] let selection_screen = SelectionScreen::new(event_bus.clone(), &beatmap_cache, &mut audio, graphics, &settings);
-    #[rustfmt::skip] let result_screen = ResultScreen::new();
+    #[rustfmt::skip] let result_screen = ResultScreen::new(event_bus.clone());
     #[rustfmt::skip] let gameplay_screen = GameplayScreen::new(event_bus.clone(), graphics, &audio, &settings);
-    #[rustfmt::skip] let settings_screen = SettingsScreen::new();
+    #[rustfmt::skip] let settings_screen = SettingsScreen::new(event_bus.clone());
     #[rustfmt::skip] let volume_screen = VolumeScreen::new();
     #[rustfmt::skip] let recording_screen = RecordingScreen::new();
     #[rustfmt::skip] let pause_screen = PauseScreen::new(event_bus.clone());
     #[rustfmt::skip] let debug_screen = DebugScreen::new();
 
     let prev_audio_path = PathBuf::new();
-    let prev_beatmap_path = PathBuf::new();
 
     let physical_size = PhysicalSize::new(graphics.config.width, graphics.config.height);
     let backbuffer = Framebuffer::new(
@@ -506,10 +741,13 @@ impl Client {
       game_state,
       settings,
       prev_audio_path,
-      prev_beatmap_path,
       beatmap_cache,
+      beatmap_importer,
+      beatmap_collections,
       score_cache,
+      stable_import: None,
 
+      loading_screen,
       selection_screen,
       gameplay_screen,
       result_screen,
@@ -526,11 +764,11 @@ impl Client {
     match self.game_state {
       GameState::Selection => {}
 
-      GameState::Playing => {
+      GameState::Playing | GameState::Replay => {
         self.gameplay_screen.render(rpass);
       }
 
-      GameState::Paused => {
+      GameState::Paused | GameState::Failed => {
         self.gameplay_screen.render(rpass);
       }
 
@@ -538,29 +776,75 @@ impl Client {
     }
   }
 
+  /// Attaches a dropped `.osr` replay to the beatmap it was set on.
+  fn import_replay(&mut self, path: &Path, file: &[u8]) {
+    let replay = match OsrReplay::read(file) {
+      Ok(replay) => replay,
+      Err(e) => {
+        log::error!("Failed to import replay {:?}: {}", path, e);
+        return;
+      }
+    };
+
+    let Some((beatmap_hash, _)) = self.beatmap_cache.find_by_md5(&replay.beatmap_md5) else {
+      log::error!("Failed to import replay {:?}: beatmap {} is not installed", path, replay.beatmap_md5);
+      return;
+    };
+
+    if self.score_cache.import(beatmap_hash, replay.score).is_none() {
+      log::info!("Replay {:?} was imported already", path);
+      return;
+    }
+
+    log::info!("Imported replay {:?}", path);
+
+    let selected = self.selection_screen.beatmap_selector().selected();
+    if self.beatmap_cache.get_index(selected).is_some_and(|(hash, _)| hash == beatmap_hash) {
+      self.selection_screen.update_scores(&mut self.score_cache, beatmap_hash);
+    }
+  }
+
+  fn finish_stable_import(&mut self, import: StableImport) {
+    let directories = import.directories.len();
+    let import = import.resolve(&self.beatmap_cache);
+
+    let imported_scores = import
+      .scores
+      .into_iter()
+      .filter_map(|(beatmap_hash, score)| self.score_cache.import(beatmap_hash, score))
+      .count();
+
+    for (name, beatmaps) in &import.collections {
+      self.beatmap_collections.insert(name, beatmaps);
+    }
+
+    log::info!(
+      "Imported {} beatmap sets, {} scores and {} collections from osu!stable, {} entries had no beatmap",
+      directories,
+      imported_scores,
+      import.collections.len(),
+      import.unmatched,
+    );
+  }
+
   pub fn play_beatmap_audio(&mut self) {
     let selected = self.selection_screen.beatmap_selector().selected();
-    let Some((_, beatmap_info)) = self.beatmap_cache.get_index(selected) else {
+    let Some((_, beatmap_set)) = self.beatmap_cache.set_of(selected) else {
       return;
     };
 
-    if beatmap_info.audio_path != self.prev_audio_path
-      || beatmap_info.file_path.parent().unwrap() != self.prev_beatmap_path
-    {
-      self.prev_beatmap_path = beatmap_info.file_path.parent().unwrap().to_owned();
-      self.prev_audio_path = beatmap_info.audio_path.clone();
-    } else {
+    if beatmap_set.audio_path == self.prev_audio_path {
       return;
     }
 
-    Self::play_beatmap_audio_unchecked(&mut self.audio, &beatmap_info.file_path, beatmap_info);
+    self.prev_audio_path = beatmap_set.audio_path.clone();
+    Self::play_beatmap_audio_unchecked(&mut self.audio, beatmap_set);
   }
 
-  pub fn play_beatmap_audio_unchecked(audio: &mut GameAudio, path: &Path, beatmap_info: &BeatmapInfo) {
+  pub fn play_beatmap_audio_unchecked(audio: &mut GameAudio, beatmap_set: &BeatmapSet) {
     use std::time::Duration;
 
-    let audio_path = path.parent().unwrap().join(&beatmap_info.audio_path);
-    let file = BufReader::new(File::open(audio_path).unwrap());
+    let file = BufReader::new(File::open(&beatmap_set.audio_path).unwrap());
     let source = Decoder::new(file).unwrap();
 
     let config = audio.device().default_output_config().unwrap();
@@ -574,7 +858,7 @@ impl Client {
 
     audio.set_playing(false);
     audio.set_source(source);
-    audio.set_position(Time::from_ms(beatmap_info.preview_time as f64));
+    audio.set_position(Time::from_ms(beatmap_set.preview_time as f64));
     audio.set_playing(true);
   }
 }
@@ -1,6 +1,12 @@
+use std::path::PathBuf;
+
 use super::{
-  gameplay::beatmap::BeatmapHash,
+  gameplay::{beatmap::BeatmapHash, beatmap_cache::ScanProgress, beatmap_import_error::BeatmapImportError, mods::Mods},
   score::{score::Score, score_cache::ScoreId},
+  stable::{
+    stable_import::{SongsTransfer, StableImport},
+    stable_import_error::StableImportError,
+  },
 };
 
 #[derive(Debug)]
@@ -16,8 +22,39 @@ pub enum ClientEvent {
     beatmap_hash: BeatmapHash,
     score_id: ScoreId,
   },
+  ExportReplay {
+    beatmap_hash: BeatmapHash,
+    score_id: ScoreId,
+  },
+  WatchReplay {
+    beatmap_hash: BeatmapHash,
+    score: Score,
+  },
+  WatchAutoplay {
+    beatmap_hash: BeatmapHash,
+    cinema: bool,
+  },
+  ReplayEnded {
+    beatmap_hash: BeatmapHash,
+    score: Score,
+  },
+  Failed {
+    beatmap_hash: BeatmapHash,
+    score: Score,
+  },
   PickBeatmap {
     beatmap_hash: BeatmapHash,
   },
   SelectBeatmap,
+  ModsChanged(Mods),
+  BeatmapScanProgress(ScanProgress),
+  BeatmapImported {
+    archive: PathBuf,
+    result: Result<PathBuf, BeatmapImportError>,
+  },
+  ImportStable {
+    osu_dir: PathBuf,
+    transfer: SongsTransfer,
+  },
+  StableImported(Result<StableImport, StableImportError>),
 }
@@ -0,0 +1,61 @@
+use apex_framework::time::time::Time;
+
+use super::{
+  beatmap::Beatmap,
+  taiko_hit_object::{TaikoColor, TaikoHitObject},
+  taiko_player::TaikoInput,
+};
+
+/// Name shown for autoplay, whose scores are never stored.
+pub const AUTOPLAY_USERNAME: &str = "Autoplay";
+
+/// Generates perfect inputs for `beatmap`. Circles are hit exactly on time alternating between hands, big circles with
+/// both keys, and drumrolls and swells as often as they allow.
+pub fn autoplay_hits(beatmap: &Beatmap) -> Vec<(Time, TaikoInput)> {
+  let mut hits = Vec::new();
+  let mut left = true;
+
+  for hit_object in &beatmap.hit_objects {
+    match hit_object {
+      TaikoHitObject::Hit(hit) if hit.big => {
+        hits.push((hit.time, input(hit.color, true)));
+        hits.push((hit.time, input(hit.color, false)));
+      }
+
+      TaikoHitObject::Hit(hit) => {
+        hits.push((hit.time, input(hit.color, left)));
+        left = !left;
+      }
+
+      TaikoHitObject::Drumroll(drumroll) => {
+        for tick in 0 .. drumroll.tick_count {
+          hits.push((drumroll.tick_time(tick), input(TaikoColor::Don, left)));
+          left = !left;
+        }
+      }
+
+      TaikoHitObject::Swell(swell) => {
+        // Swells only count hits which alternate between don and kat
+        let spacing = swell.duration / swell.required_hits.max(1) as f64;
+        let mut color = TaikoColor::Don;
+
+        for idx in 0 .. swell.required_hits {
+          hits.push((swell.time + spacing * idx as f64, input(color, left)));
+          color.toggle();
+          left = !left;
+        }
+      }
+    }
+  }
+
+  return hits;
+}
+
+fn input(color: TaikoColor, left: bool) -> TaikoInput {
+  return match (color, left) {
+    (TaikoColor::Don, true) => TaikoInput::DonLeft,
+    (TaikoColor::Don, false) => TaikoInput::DonRight,
+    (TaikoColor::Kat, true) => TaikoInput::KatLeft,
+    (TaikoColor::Kat, false) => TaikoInput::KatRight,
+  };
+}
@@ -1,13 +1,22 @@
 use std::path::{Path, PathBuf};
 
-use ahash::AHashMap;
+use indexmap::IndexMap;
 use intbits::Bits;
 use log::warn;
 
 use apex_framework::time::time::Time;
 use smart_default::SmartDefault;
 
-use super::taiko_hit_object::{TaikoColor, TaikoHitObject};
+use super::{
+  beatmap_parse_error::{BeatmapParseError, ParseReporter},
+  taiko_hit_object::{HitSamples, TaikoColor, TaikoDrumroll, TaikoHit, TaikoHitObject, TaikoSwell},
+};
+
+/// Base distance of a slider in osu!pixels for a slider multiplier of 1.0, used by the taiko converter.
+pub(crate) const OSU_BASE_SCORING_DISTANCE: f64 = 100.0;
+
+/// osu!stable multiplies all slider velocities by this value when playing taiko.
+const LEGACY_TAIKO_VELOCITY_MULTIPLIER: f64 = 1.4;
 
 // Beatmap timing-related structs
 #[derive(SmartDefault, Debug, Clone)]
@@ -17,6 +26,13 @@ pub struct TimingPoint {
 
   #[default = 60.0]
   pub bpm: f64,
+
+  /// Beats per measure, can be fractional for TJA charts.
+  #[default = 4.0]
+  pub meter: f64,
+
+  /// Skips the barline at the start of this timing point.
+  pub omit_first_barline: bool,
 }
 
 #[derive(SmartDefault, Debug, Clone)]
@@ -28,6 +44,46 @@ pub struct VelocityPoint {
   pub velocity: f64,
 }
 
+/// Sample bank used for hitsounds, `Auto` falls back to the bank of the hit object or the beatmap.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum SampleSet {
+  #[default]
+  Auto = 0,
+  Normal = 1,
+  Soft = 2,
+  Drum = 3,
+}
+
+impl SampleSet {
+  pub fn from_id(id: u8) -> Self {
+    return match id {
+      1 => Self::Normal,
+      2 => Self::Soft,
+      3 => Self::Drum,
+      _ => Self::Auto,
+    };
+  }
+
+  pub fn id(self) -> u8 {
+    return self as u8;
+  }
+}
+
+/// Effects and hitsound settings of a section, every timing point line in a beatmap results in one.
+#[derive(SmartDefault, Debug, Clone)]
+pub struct EffectPoint {
+  #[default(Time::zero())]
+  pub time: Time,
+
+  pub kiai: bool,
+
+  pub sample_set: SampleSet,
+  pub sample_index: u32,
+
+  #[default = 100]
+  pub volume: u8,
+}
+
 #[derive(Debug, Clone)]
 pub struct BreakPoint {
   pub start: Time,
@@ -58,6 +114,14 @@ impl std::fmt::Display for BeatmapHash {
   }
 }
 
+impl std::str::FromStr for BeatmapHash {
+  type Err = blake3::HexError;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    return blake3::Hash::from_hex(s).map(Self);
+  }
+}
+
 impl Default for BeatmapHash {
   fn default() -> Self {
     let beatmap = Beatmap::default();
@@ -72,6 +136,7 @@ pub struct Beatmap {
   pub timing_points: Vec<TimingPoint>,
   pub velocity_points: Vec<VelocityPoint>,
   pub break_points: Vec<BreakPoint>,
+  pub effect_points: Vec<EffectPoint>,
 
   pub title: String,
   pub artist: String,
@@ -82,10 +147,18 @@ pub struct Beatmap {
   pub overall_difficulty: f32,
 
   pub velocity_multiplier: f32,
+  pub slider_tick_rate: f32,
 
   pub file_path: PathBuf,
   pub audio_path: PathBuf,
   pub bg_path: PathBuf,
+  /// Raw lines of the `[Events]` section other than the background and breaks (videos, storyboards), written back
+  /// unchanged.
+  pub events: Vec<String>,
+
+  /// Raw `key: value` properties of the sections that are not parsed line by line, in file order. Kept around so
+  /// writing the beatmap back to disk does not lose anything.
+  pub properties: IndexMap<String, IndexMap<String, String>>,
 
   pub hash: Option<BeatmapHash>,
 }
@@ -98,6 +171,7 @@ impl Default for Beatmap {
       timing_points: Vec::new(),
       velocity_points: Vec::new(),
       break_points: Vec::new(),
+      effect_points: Vec::new(),
 
       title: String::new(),
       artist: String::new(),
@@ -108,10 +182,14 @@ impl Default for Beatmap {
       overall_difficulty: 5.0,
 
       velocity_multiplier: 0.6,
+      slider_tick_rate: 1.0,
 
       file_path: PathBuf::new(),
       audio_path: PathBuf::new(),
       bg_path: PathBuf::new(),
+      events: Vec::new(),
+
+      properties: IndexMap::new(),
 
       hash: None,
     };
@@ -124,9 +202,27 @@ impl Beatmap {
       let mut hasher = blake3::Hasher::new();
 
       for hit_object in &self.hit_objects {
-        hasher.update(&hit_object.time.to_seconds().to_le_bytes());
-        hasher.update(&[hit_object.color.is_kat() as u8]);
-        hasher.update(&[hit_object.big as u8]);
+        match hit_object {
+          TaikoHitObject::Hit(hit) => {
+            hasher.update(&hit.time.to_seconds().to_le_bytes());
+            hasher.update(&[hit.color.is_kat() as u8]);
+            hasher.update(&[hit.big as u8]);
+          }
+
+          TaikoHitObject::Drumroll(drumroll) => {
+            hasher.update(&drumroll.time.to_seconds().to_le_bytes());
+            hasher.update(&drumroll.duration.to_seconds().to_le_bytes());
+            hasher.update(&drumroll.tick_spacing.to_seconds().to_le_bytes());
+            hasher.update(&(drumroll.tick_count as u64).to_le_bytes());
+            hasher.update(&[drumroll.big as u8]);
+          }
+
+          TaikoHitObject::Swell(swell) => {
+            hasher.update(&swell.time.to_seconds().to_le_bytes());
+            hasher.update(&swell.duration.to_seconds().to_le_bytes());
+            hasher.update(&(swell.required_hits as u64).to_le_bytes());
+          }
+        }
       }
 
       for timing_point in &self.timing_points {
@@ -150,6 +246,58 @@ impl Beatmap {
       return BeatmapHash(hasher.finalize());
     });
   }
+
+  /// Whether kiai time is active at the given time.
+  pub fn is_kiai(&self, time: Time) -> bool {
+    return active_point(&self.effect_points, time, |p| p.time).is_some_and(|p| p.kiai && p.time <= time);
+  }
+
+  /// Strength of the kiai flash at the given time, fades from 1.0 to 0.0 over every beat and is 0.0 outside of kiai.
+  pub fn kiai_flash(&self, time: Time) -> f32 {
+    if !self.is_kiai(time) {
+      return 0.0;
+    }
+
+    let Some(timing_point) = active_point(&self.timing_points, time, |p| p.time) else {
+      return 0.0;
+    };
+
+    let beat_length = 60.0 / timing_point.bpm;
+    let beat_progress = ((time - timing_point.time).to_seconds() / beat_length).rem_euclid(1.0);
+    return (1.0 - beat_progress) as f32;
+  }
+
+  /// Times of the barlines until the end of the last hit object.
+  ///
+  /// Every timing point starts a new measure which lasts `meter` beats, the barline at the timing point itself is
+  /// skipped if it's marked as omitted.
+  pub fn barlines(&self) -> Vec<Time> {
+    let end_time = self.hit_objects.iter().map(|x| x.end_time().to_seconds()).fold(0.0, f64::max);
+    let end_time = Time::from_seconds(end_time);
+    let mut barlines = Vec::new();
+
+    for (i, timing_point) in self.timing_points.iter().enumerate() {
+      let next_time = self.timing_points.get(i + 1).map(|x| x.time).unwrap_or(end_time + Time::from_ms(1.0));
+      let measure_length = 60.0 / timing_point.bpm * timing_point.meter;
+
+      // Broken timing points would result in an endless amount of barlines
+      if !measure_length.is_finite() || measure_length < 0.001 {
+        continue;
+      }
+
+      let skip = if timing_point.omit_first_barline { 1 } else { 0 };
+      for measure in skip .. {
+        let time = timing_point.time + Time::from_seconds(measure_length * measure as f64);
+        if time >= next_time {
+          break;
+        }
+
+        barlines.push(time);
+      }
+    }
+
+    return barlines;
+  }
 }
 
 pub fn calc_hit_window_150(od: f32) -> Time {
@@ -160,28 +308,208 @@ pub fn calc_hit_window_300(od: f32) -> Time {
   return Time::from_ms(50.0 - 3.0 * od);
 }
 
+/// Amount of alternating hits needed to complete a swell, same as osu!stable.
+pub fn calc_swell_required_hits(od: f32, duration: Time) -> usize {
+  let od = od as f64;
+  let rotation_ratio = if od > 5.0 { 5.0 + 2.5 * (od - 5.0) / 5.0 } else { 5.0 - 2.0 * (5.0 - od) / 5.0 };
+  let hit_multiplier = rotation_ratio * 1.65;
+
+  return ((duration.to_seconds() * hit_multiplier) as usize).max(1);
+}
+
+/// Returns the point which is active at the given time, points are expected to be sorted by time.
+pub(crate) fn active_point<T>(points: &[T], time: Time, point_time: impl Fn(&T) -> Time) -> Option<&T> {
+  let idx = points.partition_point(|p| point_time(p) <= time);
+  return points.get(idx.saturating_sub(1));
+}
+
+/// Slider data which is needed to convert it to a drumroll once all timing points are known.
+pub(crate) struct RawSlider {
+  pub time: Time,
+  pub length: f64,
+  pub slides: usize,
+  pub hitsounds: u8,
+
+  /// Hitsounds of the slider's head, repeats and tail, empty if they all use `hitsounds`.
+  pub node_hitsounds: Vec<u8>,
+
+  pub samples: HitSamples,
+}
+
+/// Whistles or claps become kat, finishes become big, everything else is a small don.
+fn hit_from_hitsounds(time: Time, hitsounds: u8, samples: HitSamples) -> TaikoHit {
+  return TaikoHit {
+    time,
+    color: if hitsounds.bit(1) || hitsounds.bit(3) { TaikoColor::Kat } else { TaikoColor::Don },
+    big: hitsounds.bit(2),
+    samples,
+  };
+}
+
+/// Parses the `normalSet:additionSet:index:volume:filename` hit sample of a hit object. Missing or broken values are
+/// left at zero, so they are inherited from the timing point with [`HitSamples::inherit`].
+fn parse_hit_samples(value: Option<&str>) -> HitSamples {
+  let mut parts = value.unwrap_or_default().split(':');
+  let mut next = || parts.next().and_then(|x| x.trim().parse::<u32>().ok()).unwrap_or(0);
+
+  return HitSamples {
+    sample_set: SampleSet::from_id(next().min(u8::MAX as u32) as u8),
+    addition_set: SampleSet::from_id(next().min(u8::MAX as u32) as u8),
+    index: next(),
+    volume: next().min(100) as u8,
+  };
+}
+
+/// Converts a slider to a drumroll the same way osu!stable does it.
+///
+/// The slider's pixel length is turned into a duration using the scroll velocity at that point in time, while ticks
+/// are always spaced relative to the beat length of the governing timing point.
+pub(crate) fn convert_slider(
+  slider: &RawSlider,
+  timing_point: &TimingPoint,
+  velocity_point: &VelocityPoint,
+  slider_multiplier: f64,
+  slider_tick_rate: f64,
+) -> TaikoDrumroll {
+  let beat_length = 60.0 * 1000.0 / timing_point.bpm;
+  let velocity = velocity_point.velocity.clamp(0.1, 10.0);
+
+  let distance = slider.length * slider.slides as f64 * LEGACY_TAIKO_VELOCITY_MULTIPLIER;
+  let taiko_velocity = OSU_BASE_SCORING_DISTANCE * slider_multiplier * LEGACY_TAIKO_VELOCITY_MULTIPLIER;
+  let duration = (distance / taiko_velocity * (beat_length / velocity)).trunc();
+
+  let tick_rate = if slider_tick_rate == 3.0 { 3.0 } else { 4.0 };
+  let tick_spacing = beat_length / tick_rate;
+  let tick_count = if tick_spacing > 0.0 {
+    ((duration + tick_spacing / 2.0) / tick_spacing).ceil() as usize
+  } else {
+    0
+  };
+
+  return TaikoDrumroll {
+    time: slider.time,
+    duration: Time::from_ms(duration),
+    tick_spacing: Time::from_ms(tick_spacing),
+    tick_count,
+    big: slider.hitsounds.bit(2),
+    samples: slider.samples.clone(),
+  };
+}
+
+/// Splits a slider of an osu!standard beatmap into hits the same way the osu!stable converter does, returns `None` if
+/// it should be converted to a drumroll instead.
+///
+/// Short and fast sliders become a stream of hits spaced by the slider ticks, which are coloured by cycling through
+/// the hitsounds of the slider's nodes.
+pub(crate) fn split_slider(
+  slider: &RawSlider,
+  timing_point: &TimingPoint,
+  velocity_point: &VelocityPoint,
+  slider_multiplier: f64,
+  slider_tick_rate: f64,
+  format_version: u32,
+) -> Option<Vec<TaikoHit>> {
+  let timing_beat_length = 60.0 * 1000.0 / timing_point.bpm;
+  let velocity = velocity_point.velocity.clamp(0.1, 10.0);
+  let beat_length = timing_beat_length / velocity;
+
+  let distance = slider.length * slider.slides as f64 * LEGACY_TAIKO_VELOCITY_MULTIPLIER;
+  let taiko_velocity = OSU_BASE_SCORING_DISTANCE * slider_multiplier * LEGACY_TAIKO_VELOCITY_MULTIPLIER;
+  let duration = (distance / taiko_velocity * beat_length).trunc();
+  let osu_velocity = taiko_velocity * (1000.0 / beat_length);
+
+  // osu!stable only used the velocity adjusted beat length for the tick spacing before v8
+  let beat_length = if format_version >= 8 { timing_beat_length } else { beat_length };
+  let tick_spacing = (beat_length / slider_tick_rate).min(duration / slider.slides as f64);
+
+  if !(tick_spacing > 0.0 && distance / osu_velocity * 1000.0 < 2.0 * beat_length) {
+    return None;
+  }
+
+  let start = slider.time.to_seconds() * 1000.0;
+  let mut hits = Vec::new();
+  let mut time = start;
+
+  while time <= start + duration + tick_spacing / 8.0 {
+    let hitsounds = match slider.node_hitsounds.len() {
+      0 => slider.hitsounds,
+      len => slider.node_hitsounds[hits.len() % len],
+    };
+
+    hits.push(hit_from_hitsounds(Time::from_ms(time), hitsounds, slider.samples.clone()));
+    time += tick_spacing;
+  }
+
+  return Some(hits);
+}
+
+/// Looks up a property from one of the `key: value` sections.
+fn property<'a>(
+  property_map: &IndexMap<&str, IndexMap<&str, &'a str>>,
+  section: &'static str,
+  key: &'static str,
+) -> Option<&'a str> {
+  return property_map.get(section).and_then(|x| x.get(key)).copied();
+}
+
+/// Same as [`property`], but the property has to be present.
+fn required_property<'a>(
+  property_map: &IndexMap<&str, IndexMap<&str, &'a str>>,
+  section: &'static str,
+  key: &'static str,
+) -> Result<&'a str, BeatmapParseError> {
+  return property(property_map, section, key).ok_or(BeatmapParseError::MissingProperty { section, key });
+}
+
 impl Beatmap {
-  pub fn from_path(path: impl AsRef<Path>) -> Self {
-    let data = std::fs::read_to_string(path.as_ref()).unwrap();
-    return Self::parse(data, path.as_ref().to_owned());
+  /// Reads and leniently parses a beatmap file, malformed lines are logged and skipped.
+  pub fn from_path(path: impl AsRef<Path>) -> Result<Self, BeatmapParseError> {
+    let path = path.as_ref();
+    let data = std::fs::read_to_string(path)?;
+
+    let mut warnings = Vec::new();
+    let beatmap = Self::parse_lenient(data, path.to_owned(), &mut warnings)?;
+
+    for warning in warnings {
+      warn!("{:?}: {}", path, warning);
+    }
+
+    return Ok(beatmap);
+  }
+
+  /// Parses a beatmap, failing on the first malformed line.
+  pub fn parse<T: AsRef<str>>(data: T, file_path: PathBuf) -> Result<Self, BeatmapParseError> {
+    return Self::parse_with(data.as_ref(), file_path, ParseReporter::strict());
   }
 
-  pub fn parse<T: AsRef<str>>(data: T, file_path: PathBuf) -> Self {
-    let data = data.as_ref();
+  /// Parses a beatmap, skipping malformed lines and collecting them into `warnings` instead.
+  pub fn parse_lenient<T: AsRef<str>>(
+    data: T,
+    file_path: PathBuf,
+    warnings: &mut Vec<BeatmapParseError>,
+  ) -> Result<Self, BeatmapParseError> {
+    return Self::parse_with(data.as_ref(), file_path, ParseReporter::lenient(warnings));
+  }
+
+  fn parse_with(data: &str, file_path: PathBuf, mut reporter: ParseReporter) -> Result<Self, BeatmapParseError> {
     let mut objects = Vec::<TaikoHitObject>::new();
+    let mut sliders = Vec::<RawSlider>::new();
+    let mut spinners = Vec::<(Time, Time, HitSamples)>::new();
 
     let mut timing_points = Vec::<TimingPoint>::new();
     let mut velocity_points = Vec::<VelocityPoint>::new();
     let mut break_points = Vec::<BreakPoint>::new();
+    let mut effect_points = Vec::<EffectPoint>::new();
 
     let mut bg_path = PathBuf::new();
-    let mut property_map = AHashMap::<&str, AHashMap<&str, &str>>::new();
+    let mut events = Vec::<String>::new();
+    let mut property_map = IndexMap::<&str, IndexMap<&str, &str>>::new();
     let mut current_category = None::<&str>;
 
     for (i, line) in data.lines().enumerate() {
       if let Some(char) = line.chars().nth(0) {
         if char == '[' {
-          current_category = Some(line);
+          current_category = Some(line.trim());
 
           continue;
         }
@@ -193,24 +521,39 @@ impl Beatmap {
             continue;
           }
 
-          let mut parts = line.split(','); // TODO: add proper error handling
+          let mut parts = line.split(',');
           let Some(time_ms) = parts.next().and_then(|x| x.parse::<f64>().ok()) else {
-            warn!("Failed to parse timing point time at line {}", i);
+            reporter.report(current_category, i, "Failed to parse timing point time")?;
             continue;
           };
           let Some(beat_length) = parts.next().and_then(|x| x.parse::<f64>().ok()) else {
-            warn!("Failed to parse timing point beat length at line {}", i);
-            continue;
-          };
-          let Some(uninherited) = parts.nth(4).map(|x| x == "1") else {
-            warn!("Failed to parse timing point uninherited flag at line {}", i);
+            reporter.report(current_category, i, "Failed to parse timing point beat length")?;
             continue;
           };
 
+          // Older beatmap versions don't have the remaining fields, missing or broken ones fall back to the defaults.
+          let meter = parts.next().and_then(|x| x.parse::<f64>().ok()).filter(|x| *x > 0.0).unwrap_or(4.0);
+          let sample_set = parts.next().and_then(|x| x.parse::<u8>().ok()).map(SampleSet::from_id).unwrap_or_default();
+          let sample_index = parts.next().and_then(|x| x.parse::<u32>().ok()).unwrap_or(0);
+          let volume = parts.next().and_then(|x| x.parse::<u8>().ok()).unwrap_or(100);
+          // All timing points of older beatmap versions are uninherited
+          let uninherited = parts.next().map(|x| x == "1").unwrap_or(true);
+          let effects = parts.next().and_then(|x| x.parse::<u8>().ok()).unwrap_or(0);
+
+          effect_points.push(EffectPoint {
+            time: Time::from_ms(time_ms),
+            kiai: effects.bit(0),
+            sample_set,
+            sample_index,
+            volume: volume.min(100),
+          });
+
           if uninherited {
             timing_points.push(TimingPoint {
               time: Time::from_ms(time_ms),
               bpm: (60.0 * 1000.0) / beat_length,
+              meter,
+              omit_first_barline: effects.bit(3),
             });
 
             velocity_points.push(VelocityPoint {
@@ -226,30 +569,34 @@ impl Beatmap {
         }
 
         Some("[Events]") => {
-          if line.contains(".jpg") || line.contains(".jpeg") || line.contains(".png") {
-            let Some(file_bg_path) = line.split("\"").nth(1) else {
-              warn!("Failed to bg path at line {}", i);
-              continue;
-            };
-
-            bg_path = file_bg_path.into();
+          if line.trim().is_empty() || line.starts_with("//") {
+            continue;
           }
 
           let mut parts = line.split(',');
           let Some(event_type) = parts.next() else {
-            warn!("Failed to parse event type at line {}", i);
+            reporter.report(current_category, i, "Failed to parse event type")?;
             continue;
           };
 
           match event_type {
+            "0" | "Background" if line.contains(".jpg") || line.contains(".jpeg") || line.contains(".png") => {
+              let Some(file_bg_path) = line.split("\"").nth(1) else {
+                reporter.report(current_category, i, "Failed to parse background path")?;
+                continue;
+              };
+
+              bg_path = file_bg_path.into();
+            }
+
             "2" | "Break" => {
               let Some(start) = parts.next().and_then(|x| x.parse::<f64>().ok()) else {
-                warn!("Failed to parse break start time at line {}", i);
+                reporter.report(current_category, i, "Failed to parse break start time")?;
                 continue;
               };
 
               let Some(end) = parts.next().and_then(|x| x.parse::<f64>().ok()) else {
-                warn!("Failed to parse break end time at line {}", i);
+                reporter.report(current_category, i, "Failed to parse break end time")?;
                 continue;
               };
 
@@ -259,53 +606,85 @@ impl Beatmap {
               });
             }
 
-            _ => {}
+            _ => events.push(line.to_owned()),
           }
         }
 
         Some("[HitObjects]") => {
+          if line.trim().is_empty() {
+            continue;
+          }
+
           let mut parts = line.split(',');
           let Some(time_in_ms) = parts.nth(2).and_then(|x| x.parse::<f64>().ok()) else {
-            warn!("Failed to parse hit object time at line {}", i);
+            reporter.report(current_category, i, "Failed to parse hit object time")?;
             continue;
           };
 
           let Some(object_type) = parts.next().and_then(|x| x.parse::<u8>().ok()) else {
-            warn!("Failed to parse hit object type at line {}", i);
+            reporter.report(current_category, i, "Failed to parse hit object type")?;
             continue;
           };
 
           let Some(hitsounds) = parts.next().and_then(|x| x.parse::<u8>().ok()) else {
-            warn!("Failed to parse hit object hitsouns at line {}", i);
+            reporter.report(current_category, i, "Failed to parse hit object hitsounds")?;
             continue;
           };
 
           // Circles
           if object_type.bit(0) {
-            // Whistles or claps become kat
-            if hitsounds.bit(1) || hitsounds.bit(3) {
-              objects.push(TaikoHitObject {
-                time: Time::from_ms(time_in_ms),
-                color: TaikoColor::Kat,
-                big: hitsounds.bit(2),
-              });
-            }
-            // Everything else becomes don
-            else {
-              objects.push(TaikoHitObject {
-                time: Time::from_ms(time_in_ms),
-                color: TaikoColor::Don,
-                big: hitsounds.bit(2),
-              });
-            }
+            let samples = parse_hit_samples(parts.next());
+            objects.push(TaikoHitObject::Hit(hit_from_hitsounds(Time::from_ms(time_in_ms), hitsounds, samples)));
           }
           // Sliders
           else if object_type.bit(1) {
-            /* sliders here */
+            let Some(slides) = parts.nth(1).and_then(|x| x.parse::<usize>().ok()) else {
+              reporter.report(current_category, i, "Failed to parse slider slides")?;
+              continue;
+            };
+
+            let Some(length) = parts.next().and_then(|x| x.parse::<f64>().ok()) else {
+              reporter.report(current_category, i, "Failed to parse slider length")?;
+              continue;
+            };
+
+            let node_hitsounds = match parts.next() {
+              Some(x) => {
+                match x.split('|').map(|x| x.parse::<u8>()).collect::<Result<Vec<_>, _>>() {
+                  Ok(node_hitsounds) => node_hitsounds,
+                  Err(_) => {
+                    reporter.report(current_category, i, "Failed to parse slider edge sounds")?;
+                    continue;
+                  }
+                }
+              }
+
+              None => Vec::new(),
+            };
+
+            // Edge sample sets are skipped, all hits of a split slider use the samples of the slider
+            let samples = parse_hit_samples(parts.nth(1));
+
+            // Drumrolls depend on timing points, so they are converted after everything else is parsed
+            sliders.push(RawSlider {
+              time: Time::from_ms(time_in_ms),
+              length,
+              slides,
+              hitsounds,
+              node_hitsounds,
+              samples,
+            });
           }
           // Spinners
           else if object_type.bit(3) {
-            /* spinners here */
+            let Some(end_time_in_ms) = parts.next().and_then(|x| x.parse::<f64>().ok()) else {
+              reporter.report(current_category, i, "Failed to parse spinner end time")?;
+              continue;
+            };
+
+            // Required hit count depends on the overall difficulty, so swells are converted later as well
+            let samples = parse_hit_samples(parts.next());
+            spinners.push((Time::from_ms(time_in_ms), Time::from_ms(end_time_in_ms), samples));
           }
         }
 
@@ -314,9 +693,9 @@ impl Beatmap {
             continue;
           }
 
-          let mut parts = line.split(':');
-          let Some(key) = parts.next() else { continue };
-          let Some(value) = parts.next() else { continue };
+          let Some((key, value)) = line.split_once(':') else {
+            continue;
+          };
 
           property_map.entry(category).or_default().insert(key.trim(), value.trim());
         }
@@ -329,7 +708,14 @@ impl Beatmap {
       if p.time != Time::zero() {
         // In osu!, if there are objects before the first timing point
         // they act as if affected by the first timing point after them
-        timing_points.insert(0, TimingPoint { time: Time::zero(), bpm: p.bpm });
+        timing_points.insert(
+          0,
+          TimingPoint {
+            time: Time::zero(),
+            bpm: p.bpm,
+            ..Default::default()
+          },
+        );
       }
     } else {
       timing_points.insert(0, TimingPoint::default());
@@ -344,29 +730,103 @@ impl Beatmap {
       velocity_points.insert(0, VelocityPoint::default());
     }
 
-    objects.sort_by(|a, b| a.time.to_seconds().total_cmp(&b.time.to_seconds()));
+    // Only osu!standard and osu!taiko beatmaps can be played as taiko.
+    let mode = property(&property_map, "[General]", "Mode").unwrap_or("0");
+    if mode != "0" && mode != "1" {
+      return Err(BeatmapParseError::UnsupportedMode(mode.to_owned()));
+    }
+
+    let converted = mode == "0";
+    let format_version = data
+      .lines()
+      .next()
+      .and_then(|x| x.trim_start_matches('\u{feff}').trim().strip_prefix("osu file format v"))
+      .and_then(|x| x.parse::<u32>().ok())
+      .unwrap_or(14);
+
+    let difficulty = |key| property(&property_map, "[Difficulty]", key);
+
+    let slider_multiplier = difficulty("SliderMultiplier").and_then(|x| x.parse::<f64>().ok()).unwrap_or(0.6);
+    let slider_tick_rate = difficulty("SliderTickRate").and_then(|x| x.parse::<f64>().ok()).unwrap_or(1.0);
+
+    for slider in &sliders {
+      let Some(timing_point) = active_point(&timing_points, slider.time, |p| p.time) else {
+        continue;
+      };
+
+      let Some(velocity_point) = active_point(&velocity_points, slider.time, |p| p.time) else {
+        continue;
+      };
+
+      if converted {
+        let hits =
+          split_slider(slider, timing_point, velocity_point, slider_multiplier, slider_tick_rate, format_version);
+
+        if let Some(hits) = hits {
+          objects.extend(hits.into_iter().map(TaikoHitObject::Hit));
+          continue;
+        }
+      }
+
+      let drumroll = convert_slider(slider, timing_point, velocity_point, slider_multiplier, slider_tick_rate);
+      objects.push(TaikoHitObject::Drumroll(drumroll));
+    }
+
+    let overall_difficulty = difficulty("OverallDifficulty").and_then(|x| x.parse::<f32>().ok()).unwrap_or(5.0);
+
+    for (start, end, samples) in spinners {
+      let duration = end - start;
+      objects.push(TaikoHitObject::Swell(TaikoSwell {
+        time: start,
+        duration,
+        required_hits: calc_swell_required_hits(overall_difficulty, duration),
+        samples,
+      }));
+    }
+
+    objects.sort_by(|a, b| a.time().to_seconds().total_cmp(&b.time().to_seconds()));
 
-    return Beatmap {
+    let default_effect_point = EffectPoint::default();
+    for object in &mut objects {
+      let effect_point = active_point(&effect_points, object.time(), |p| p.time).unwrap_or(&default_effect_point);
+      object.samples_mut().inherit(effect_point);
+    }
+
+    return Ok(Beatmap {
       hit_objects: objects,
       timing_points,
       velocity_points,
       break_points,
+      effect_points,
 
-      title: property_map["[Metadata]"]["Title"].to_owned(),
-      artist: property_map["[Metadata]"]["Artist"].to_owned(),
-      creator: property_map["[Metadata]"]["Creator"].to_owned(),
-      variant: property_map["[Metadata]"]["Version"].to_owned(),
+      title: required_property(&property_map, "[Metadata]", "Title")?.to_owned(),
+      artist: required_property(&property_map, "[Metadata]", "Artist")?.to_owned(),
+      creator: required_property(&property_map, "[Metadata]", "Creator")?.to_owned(),
+      variant: required_property(&property_map, "[Metadata]", "Version")?.to_owned(),
 
-      hp_drain_rate: property_map["[Difficulty]"]["HPDrainRate"].parse().unwrap_or(5.0),
-      overall_difficulty: property_map["[Difficulty]"]["OverallDifficulty"].parse().unwrap_or(5.0),
+      hp_drain_rate: difficulty("HPDrainRate").and_then(|x| x.parse().ok()).unwrap_or(5.0),
+      overall_difficulty,
 
-      velocity_multiplier: property_map["[Difficulty]"]["SliderMultiplier"].parse().unwrap_or(0.6),
+      velocity_multiplier: difficulty("SliderMultiplier").and_then(|x| x.parse().ok()).unwrap_or(0.6),
+      slider_tick_rate: slider_tick_rate as f32,
 
       file_path,
-      audio_path: PathBuf::from(property_map["[General]"]["AudioFilename"]),
+      audio_path: PathBuf::from(required_property(&property_map, "[General]", "AudioFilename")?),
       bg_path,
+      events,
+
+      properties: property_map
+        .iter()
+        .map(|(section, properties)| {
+          let properties = properties
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect::<IndexMap<_, _>>();
+          return (section.to_string(), properties);
+        })
+        .collect(),
 
       hash: None,
-    };
+    });
   }
 }
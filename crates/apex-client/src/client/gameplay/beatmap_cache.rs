@@ -1,15 +1,31 @@
 use std::{
+  borrow::Cow,
   path::{Path, PathBuf},
   str::FromStr,
+  sync::{
+    mpsc::{self, Receiver, TryRecvError},
+    Mutex,
+  },
+  time::{Duration, SystemTime},
 };
 
+use ahash::AHashMap;
 use indexmap::IndexMap;
 use instant::Instant;
 use log::warn;
+use notify::{RecommendedWatcher, RecursiveMode, Watcher as _};
+use rayon::iter::{IntoParallelIterator as _, ParallelIterator as _};
+use rosu_pp::model::mode::{ConvertStatus, GameMode};
+use rusqlite::{Connection, OptionalExtension as _};
 
 use apex_framework::time::time::Time;
 
-use super::beatmap::{Beatmap, BeatmapHash};
+use super::{
+  beatmap::{Beatmap, BeatmapHash},
+  beatmap_parse_error::BeatmapParseError,
+  beatmap_set::{BeatmapSet, BeatmapSetKey},
+  mods::Mods,
+};
 
 #[derive(Debug, Default, Clone)]
 pub struct BeatmapInfo {
@@ -19,6 +35,15 @@ pub struct BeatmapInfo {
   pub variant: String,
   pub preview_time: u64,
 
+  /// Online id of the beatmap set, missing for unsubmitted beatmaps and TJA charts.
+  pub beatmapset_id: Option<u32>,
+
+  /// Whether this is an osu!standard beatmap which is converted to taiko when played.
+  pub converted: bool,
+
+  /// Index of the course for TJA charts, which contain multiple difficulties in a single file.
+  pub course: Option<usize>,
+
   pub difficulty: f64,
   pub object_count: usize,
   pub length: Time,
@@ -30,15 +55,18 @@ pub struct BeatmapInfo {
   pub file_path: PathBuf,
   pub audio_path: PathBuf,
   pub bg_path: PathBuf,
+
+  /// Hex encoded MD5 of the beatmap file, which is how osu! refers to beatmaps, e.g. in replays.
+  pub file_md5: String,
 }
 
 impl BeatmapInfo {
-  pub fn from_path(path: impl AsRef<Path>) -> Self {
-    let data = std::fs::read_to_string(path.as_ref()).unwrap();
+  pub fn from_path(path: impl AsRef<Path>) -> Result<Self, BeatmapParseError> {
+    let data = std::fs::read_to_string(path.as_ref())?;
     return Self::parse(data, path.as_ref().to_owned());
   }
 
-  pub fn parse<T: AsRef<str>>(data: T, file_path: PathBuf) -> Self {
+  pub fn parse<T: AsRef<str>>(data: T, file_path: PathBuf) -> Result<Self, BeatmapParseError> {
     let data = data.as_ref();
     let mut beatmap_info = Self {
       title: String::new(),
@@ -47,6 +75,11 @@ impl BeatmapInfo {
       variant: String::new(),
       preview_time: 0,
 
+      beatmapset_id: None,
+
+      converted: false,
+      course: None,
+
       difficulty: 0.0,
       object_count: 0,
       length: Time::zero(),
@@ -58,9 +91,13 @@ impl BeatmapInfo {
       file_path,
       audio_path: PathBuf::new(),
       bg_path: PathBuf::new(),
+
+      file_md5: format!("{:x}", md5::compute(data)),
     };
 
-    let r_beatmap = rosu_pp::Beatmap::from_str(data).unwrap();
+    let r_beatmap = parse_taiko_difficulty(data)?;
+    beatmap_info.converted = r_beatmap.is_convert;
+
     let r_diff_attrs = rosu_pp::Difficulty::new().calculate(&r_beatmap);
     beatmap_info.difficulty = r_diff_attrs.stars();
     beatmap_info.bpm = r_beatmap.bpm();
@@ -117,10 +154,11 @@ impl BeatmapInfo {
 
           #[rustfmt::skip]
           match key {
-            "Title"   => value.trim().clone_into(&mut beatmap_info.title),
-            "Artist"  => value.trim().clone_into(&mut beatmap_info.artist),
-            "Creator" => value.trim().clone_into(&mut beatmap_info.creator),
-            "Version" => value.trim().clone_into(&mut beatmap_info.variant),
+            "Title"        => value.trim().clone_into(&mut beatmap_info.title),
+            "Artist"       => value.trim().clone_into(&mut beatmap_info.artist),
+            "Creator"      => value.trim().clone_into(&mut beatmap_info.creator),
+            "Version"      => value.trim().clone_into(&mut beatmap_info.variant),
+            "BeatmapSetID" => beatmap_info.beatmapset_id = value.trim().parse().ok().filter(|x| *x > 0),
 
             _ => {}
           };
@@ -130,34 +168,305 @@ impl BeatmapInfo {
       }
     }
 
-    return beatmap_info;
+    return Ok(beatmap_info);
+  }
+
+  /// Collects the info of an already parsed beatmap, e.g. one which was not read from an `.osu` file.
+  pub fn from_beatmap(beatmap: &Beatmap) -> Result<Self, BeatmapParseError> {
+    let mut data = Vec::new();
+    beatmap.write(&mut data)?;
+
+    return Self::parse(String::from_utf8_lossy(&data), beatmap.file_path.clone());
+  }
+
+  /// Star rating with `mods` applied, [`BeatmapInfo::difficulty`] is the one without any mods.
+  pub fn calc_difficulty(&self, mods: Mods) -> Result<f64, BeatmapParseError> {
+    let data = match self.course {
+      // Only `.osu` files can be parsed by `rosu_pp`, so TJA courses are written as one first
+      Some(_) => {
+        let mut data = Vec::new();
+        self.load_beatmap()?.write(&mut data)?;
+        String::from_utf8_lossy(&data).into_owned()
+      }
+
+      None => std::fs::read_to_string(&self.file_path)?,
+    };
+
+    let r_beatmap = parse_taiko_difficulty(&data)?;
+
+    // Difficulty settings are adjusted the same way as in gameplay and used as they are
+    let r_diff_attrs = rosu_pp::Difficulty::new()
+      .mods(mods.legacy_bits())
+      .od(mods.overall_difficulty(r_beatmap.od), true)
+      .hp(mods.hp_drain_rate(r_beatmap.hp), true)
+      .calculate(&r_beatmap);
+
+    return Ok(r_diff_attrs.stars());
+  }
+
+  /// Loads the beatmap this info was read from.
+  pub fn load_beatmap(&self) -> Result<Beatmap, BeatmapParseError> {
+    return match self.course {
+      Some(course) => Beatmap::from_tja_course(&self.file_path, course),
+      None => Beatmap::from_path(&self.file_path),
+    };
+  }
+}
+
+/// Parses a beatmap for difficulty calculation, osu!standard beatmaps are converted to taiko first.
+fn parse_taiko_difficulty(data: &str) -> Result<rosu_pp::Beatmap, BeatmapParseError> {
+  let mut r_beatmap = rosu_pp::Beatmap::from_str(data).map_err(|e| BeatmapParseError::Difficulty(e.to_string()))?;
+
+  if r_beatmap.mode == GameMode::Osu && r_beatmap.convert_in_place(GameMode::Taiko) == ConvertStatus::Incompatible {
+    return Err(BeatmapParseError::Difficulty("Failed to convert beatmap to taiko".to_owned()));
+  }
+
+  return Ok(r_beatmap);
+}
+
+/// Modification time and size of a beatmap file, used to tell whether its stored index entries are still current.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+  modified: i64,
+  size: i64,
+}
+
+impl FileStamp {
+  fn from_path(path: &Path) -> std::io::Result<Self> {
+    let metadata = std::fs::metadata(path)?;
+    let modified = metadata.modified()?.duration_since(SystemTime::UNIX_EPOCH).unwrap_or_default();
+
+    return Ok(Self {
+      modified: modified.as_nanos() as i64,
+      size: metadata.len() as i64,
+    });
+  }
+}
+
+/// Beatmaps of a single file as they were stored during the last scan.
+struct IndexedFile {
+  stamp: FileStamp,
+  beatmaps: Vec<(BeatmapHash, BeatmapInfo)>,
+
+  /// Why the file failed to parse, it has no beatmaps then.
+  error: Option<String>,
+}
+
+/// A beatmap file which was parsed because it is new or changed since the last scan.
+struct ScannedFile {
+  path: PathBuf,
+  stamp: FileStamp,
+  result: Result<Vec<(BeatmapHash, BeatmapInfo)>, BeatmapParseError>,
+}
+
+impl ScannedFile {
+  fn parse(path: PathBuf, stamp: FileStamp) -> Self {
+    let result = match path.extension().and_then(|x| x.to_str()) {
+      Some("tja") => BeatmapCache::load_tja(&path),
+      _ => BeatmapCache::load_beatmap(&path).map(|x| vec![x]),
+    };
+
+    return Self { path, stamp, result };
+  }
+}
+
+/// Progress of a background beatmap scan, reported after every parsed file.
+#[derive(Debug, Clone, Copy)]
+pub struct ScanProgress {
+  /// Files parsed so far, including the broken ones.
+  pub processed: usize,
+  pub failed: usize,
+
+  /// Files which have to be parsed, unchanged files come from the index and are not counted.
+  pub total: usize,
+  pub started: Instant,
+}
+
+impl ScanProgress {
+  pub fn is_done(&self) -> bool {
+    return self.processed >= self.total;
+  }
+
+  /// Estimated time until every file is parsed, unknown until the first one is done.
+  pub fn eta(&self) -> Option<Duration> {
+    if self.processed == 0 {
+      return None;
+    }
+
+    let remaining = self.total.saturating_sub(self.processed) as f64 / self.processed as f64;
+    return Some(self.started.elapsed().mul_f64(remaining));
   }
 }
 
+/// Beatmaps of a file which is currently in the cache.
+struct LoadedFile {
+  stamp: FileStamp,
+  hashes: Vec<BeatmapHash>,
+}
+
+/// A change to the cache. Consumers remember the [`BeatmapCache::revision`] they have seen to update incrementally
+/// instead of going through the whole library, and acknowledge it so the cache can drop the changes again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BeatmapChange {
+  /// The beatmap at this index was added or its info changed.
+  Inserted(usize),
+
+  /// The beatmap at this index was removed, the index is only reused once every consumer has seen this.
+  Removed(usize),
+}
+
+/// Version of the beatmaps table, bumped whenever its columns change.
+const INDEX_VERSION: i64 = 3;
+
+/// How long a path has to stay untouched before a change reported by the watcher is picked up, so files which are
+/// still being written are not parsed halfway through.
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
 pub struct BeatmapCache {
-  cache: IndexMap<BeatmapHash, BeatmapInfo, ahash::RandomState>,
-  last_update: Instant,
+  conn: Connection,
+
+  /// Removed beatmaps leave a `None` behind, so the indices of the others stay the same.
+  cache: Vec<(BeatmapHash, Option<BeatmapInfo>)>,
+  indices: AHashMap<BeatmapHash, usize>,
+
+  /// Indices of removed beatmaps which every consumer has seen, new beatmaps take them before growing the cache.
+  free: Vec<usize>,
+  files: AHashMap<PathBuf, LoadedFile>,
+
+  /// Sets are never removed either, they are just left without difficulties.
+  sets: IndexMap<BeatmapSetKey, BeatmapSet, ahash::RandomState>,
+
+  /// Set index of every beatmap index.
+  beatmap_sets: Vec<usize>,
+
+  broken: Vec<(PathBuf, BeatmapParseError)>,
+
+  /// Changes which were not acknowledged yet, the ones before are dropped.
+  changes: Vec<BeatmapChange>,
+  acknowledged: usize,
+  len: usize,
+
+  /// Results of the running background scans, a rescan can start before the previous scan is done.
+  scans: Vec<Receiver<ScannedFile>>,
+
+  watcher: Option<(RecommendedWatcher, Receiver<notify::Result<notify::Event>>)>,
+
+  /// Paths the watcher reported, along with when they were last touched.
+  pending: AHashMap<PathBuf, Instant>,
 }
 
 impl BeatmapCache {
-  pub fn new() -> Self {
+  pub fn new(connection: Connection) -> Self {
+    connection
+      .execute("create table if not exists beatmaps_version (version integer not null)", ())
+      .unwrap();
+
+    // The index only caches what is on disk, so an outdated one is simply rebuilt
+    let version = connection
+      .query_row("select version from beatmaps_version", (), |row| row.get::<_, i64>(0))
+      .optional()
+      .unwrap();
+
+    if version != Some(INDEX_VERSION) {
+      connection
+        .execute_batch(&format!(
+          "drop table if exists beatmaps;
+           drop table if exists broken_beatmaps;
+           delete from beatmaps_version;
+           insert into beatmaps_version (version) values ({});",
+          INDEX_VERSION
+        ))
+        .unwrap();
+    }
+
+    connection
+      .execute(
+        "create table if not exists beatmaps (
+           id integer primary key,
+           file_path text not null,
+           course integer,
+           file_modified integer not null,
+           file_size integer not null,
+           hash text not null,
+           title text not null,
+           artist text not null,
+           creator text not null,
+           variant text not null,
+           preview_time integer not null,
+           beatmapset_id integer,
+           converted integer not null,
+           difficulty real not null,
+           object_count integer not null,
+           length real not null,
+           bpm real not null,
+           hp_drain real not null,
+           overall_difficulty real not null,
+           audio_path text not null,
+           bg_path text not null,
+           file_md5 text not null
+       )",
+        (),
+      )
+      .unwrap();
+
+    connection
+      .execute("create index if not exists beatmaps_file_path on beatmaps (file_path)", ())
+      .unwrap();
+
+    connection
+      .execute(
+        "create table if not exists broken_beatmaps (
+           file_path text primary key,
+           file_modified integer not null,
+           file_size integer not null,
+           error text not null
+       )",
+        (),
+      )
+      .unwrap();
+
     return Self {
-      cache: IndexMap::default(),
-      last_update: Instant::now(),
+      conn: connection,
+      cache: Vec::new(),
+      indices: AHashMap::new(),
+      free: Vec::new(),
+      files: AHashMap::new(),
+      sets: IndexMap::default(),
+      beatmap_sets: Vec::new(),
+      broken: Vec::new(),
+      changes: Vec::new(),
+      acknowledged: 0,
+      len: 0,
+
+      scans: Vec::new(),
+
+      watcher: None,
+      pending: AHashMap::new(),
     };
   }
 
-  pub fn load_beatmaps(&mut self, path: impl AsRef<Path>) {
+  /// Scans every beatmapset directory in `path`. Beatmaps from the index are available right away, new or changed
+  /// files are parsed on a worker pool and show up through [`BeatmapCache::poll`].
+  pub fn load_beatmaps(&mut self, path: impl AsRef<Path>, on_progress: impl Fn(ScanProgress) + Send + 'static) {
     let path = path.as_ref();
+    let started = Instant::now();
+
     let Ok(iter) = std::fs::read_dir(path) else {
       warn!("Failed to read beatmap directory: {:?}, creating new one...", path);
       if let Err(e) = std::fs::create_dir_all(path) {
         warn!("Failed to create beatmap directory: {:?}", e);
       }
 
+      on_progress(ScanProgress {
+        processed: 0,
+        failed: 0,
+        total: 0,
+        started,
+      });
       return;
     };
 
+    let mut dirs = Vec::new();
     for entry in iter {
       let Ok(entry) = entry else {
         warn!("Failed to read beatmap directory: {:?}", entry);
@@ -167,68 +476,699 @@ impl BeatmapCache {
       let path = entry.path();
 
       if path.is_dir() {
-        self.load_dir_beatmaps(&path);
+        dirs.push(path);
       }
     }
 
-    self.last_update = Instant::now();
+    let stale = self.load_indexed(&[path.to_owned()], &dirs);
+
+    let progress = ScanProgress {
+      processed: 0,
+      failed: 0,
+      total: stale.len(),
+      started,
+    };
+
+    on_progress(progress);
+
+    let (tx, rx) = mpsc::channel();
+    self.scans.push(rx);
+
+    std::thread::spawn(move || {
+      // Progress is reported while holding the lock, so the reports always arrive in order
+      let state = Mutex::new((progress, on_progress));
+
+      stale.into_par_iter().for_each_with(tx, |tx, (path, stamp)| {
+        let scanned = ScannedFile::parse(path, stamp);
+        let is_broken = scanned.result.is_err();
+
+        // The receiver is gone once the cache is dropped, there is nothing left to do then
+        let _ = tx.send(scanned);
+
+        let mut state = state.lock().unwrap();
+        let (progress, on_progress) = &mut *state;
+
+        progress.processed += 1;
+        if is_broken {
+          progress.failed += 1;
+        }
+
+        on_progress(*progress);
+      });
+    });
   }
 
+  /// Loads the beatmaps of a single directory, blocking until every file is parsed.
   pub fn load_difficulties(&mut self, path: impl AsRef<Path>) {
-    self.load_dir_beatmaps(path);
+    self.load_directories(&[path.as_ref().to_owned()]);
+  }
+
+  fn load_directories(&mut self, dirs: &[PathBuf]) {
+    let stale = self.load_indexed(dirs, dirs);
 
-    self.last_update = Instant::now();
+    let scanned = stale.into_iter().map(|(path, stamp)| ScannedFile::parse(path, stamp)).collect::<Vec<_>>();
+    self.store_scanned(scanned);
   }
 
-  fn load_dir_beatmaps(&mut self, path: impl AsRef<Path>) {
-    let path = path.as_ref();
-    let Ok(iter) = std::fs::read_dir(path) else {
-      warn!("Failed to read directory: {:?}", path);
-      return;
+  /// Starts watching `path` for beatmap files being added, changed or removed while the game is running.
+  pub fn watch(&mut self, path: impl AsRef<Path>) {
+    let (tx, rx) = mpsc::channel();
+    let watcher = notify::recommended_watcher(tx).and_then(|mut watcher| {
+      watcher.watch(path.as_ref(), RecursiveMode::Recursive)?;
+      return Ok(watcher);
+    });
+
+    match watcher {
+      Ok(watcher) => self.watcher = Some((watcher, rx)),
+      Err(e) => warn!("Failed to watch beatmap directory {:?}: {}", path.as_ref(), e),
+    }
+  }
+
+  /// Moves results of the background scan and changes reported by the watcher into the cache, should be called every
+  /// frame.
+  pub fn poll(&mut self) {
+    let mut scanned = Vec::new();
+    self.scans.retain(|scan| {
+      loop {
+        match scan.try_recv() {
+          Ok(file) => scanned.push(file),
+          Err(TryRecvError::Empty) => break true,
+          Err(TryRecvError::Disconnected) => break false,
+        }
+      }
+    });
+
+    self.store_scanned(scanned);
+
+    if let Some((_, events)) = &self.watcher {
+      for event in events.try_iter() {
+        match event {
+          Ok(event) if !event.kind.is_access() => {
+            for path in event.paths {
+              self.pending.insert(path, Instant::now());
+            }
+          }
+
+          Ok(_) => {}
+          Err(e) => warn!("Failed to watch beatmap directory: {}", e),
+        }
+      }
+    }
+
+    // New directories wait for running scans, which most likely cover them already
+    let is_scanning = self.is_scanning();
+    let settled = self
+      .pending
+      .iter()
+      .filter(|(path, time)| time.elapsed() >= WATCH_DEBOUNCE && !(is_scanning && path.is_dir()))
+      .map(|(path, _)| path.clone())
+      .collect::<Vec<_>>();
+
+    for path in &settled {
+      self.pending.remove(path);
+    }
+
+    // Directories are loaded together, so a burst of new sets only reads the index once
+    let (dirs, paths) = settled.into_iter().partition::<Vec<_>, _>(|path| path.is_dir());
+    if !dirs.is_empty() {
+      self.load_directories(&dirs);
+    }
+
+    for path in paths {
+      self.reload_path(&path);
+    }
+  }
+
+  /// Blocks until the background scan is done and all of its results are in the cache.
+  pub fn wait_for_scan(&mut self) {
+    for scan in std::mem::take(&mut self.scans) {
+      self.store_scanned(scan.iter().collect());
+    }
+  }
+
+  pub fn is_scanning(&self) -> bool {
+    return !self.scans.is_empty();
+  }
+
+  /// Adds the beatmaps of unchanged files in `dirs` from the index and returns the files which have to be parsed.
+  /// Index entries of files below `roots` which no longer exist are removed.
+  fn load_indexed(&mut self, roots: &[PathBuf], dirs: &[PathBuf]) -> Vec<(PathBuf, FileStamp)> {
+    let is_below_roots = |path: &Path| roots.iter().any(|root| path.starts_with(root));
+
+    let mut indexed = match Self::indexed_files(&self.conn) {
+      Ok(indexed) => indexed,
+      Err(e) => {
+        warn!("Failed to read beatmap index: {}", e);
+        AHashMap::new()
+      }
     };
 
-    for entry in iter {
-      let Ok(entry) = entry else {
-        warn!("Failed to read beatmap file: {:?}", entry);
+    let mut stale = Vec::new();
+
+    for dir in dirs {
+      let Ok(iter) = std::fs::read_dir(dir) else {
+        warn!("Failed to read directory: {:?}", dir);
         continue;
       };
 
-      let entry_path = entry.path();
+      for entry in iter {
+        let Ok(entry) = entry else {
+          warn!("Failed to read beatmap file: {:?}", entry);
+          continue;
+        };
+
+        let entry_path = entry.path();
+
+        if !entry_path.is_file() || !is_beatmap_file(&entry_path) {
+          continue;
+        }
+
+        let stamp = match FileStamp::from_path(&entry_path) {
+          Ok(stamp) => stamp,
+          Err(e) => {
+            warn!("Skipping broken beatmap {:?}: {}", entry_path, e);
+            self.broken.push((entry_path, e.into()));
+            continue;
+          }
+        };
+
+        // Already loaded, e.g. when a directory is rescanned
+        if self.files.get(&entry_path).is_some_and(|x| x.stamp == stamp) {
+          continue;
+        }
+
+        if let Some(file) = indexed.remove(&entry_path) {
+          if file.stamp == stamp {
+            match file.error {
+              Some(error) => {
+                warn!("Skipping broken beatmap {:?}: {}", entry_path, error);
+                self.forget_file(&entry_path);
+                self.broken.push((entry_path, BeatmapParseError::Indexed(error)));
+              }
+
+              None => self.insert_file(entry_path, stamp, file.beatmaps),
+            }
 
-      if entry_path.is_file() {
-        if let Some(extension) = entry_path.extension() {
-          if extension == "osu" {
-            let data = std::fs::read_to_string(&entry_path).unwrap();
-            let beatmap = Beatmap::parse(&data, path.to_owned());
-            let beatmap_info = BeatmapInfo::parse(&data, entry_path.to_owned());
-            self.cache.insert(beatmap.hash(), beatmap_info);
+            continue;
           }
         }
+
+        stale.push((entry_path, stamp));
       }
     }
+
+    // Loaded files which are gone now, only happens when a directory is rescanned
+    let deleted = self.files.keys().filter(|x| is_below_roots(x) && !x.is_file()).cloned().collect::<Vec<_>>();
+    for path in deleted {
+      self.forget_file(&path);
+    }
+
+    // Whatever is left in the index below the root was deleted since the last scan
+    let result = self.conn.transaction().and_then(|tx| {
+      for path in indexed.into_keys().filter(|x| is_below_roots(x)) {
+        Self::remove_indexed(&tx, &path)?;
+      }
+
+      return tx.commit();
+    });
+
+    if let Err(e) = result {
+      warn!("Failed to remove deleted beatmaps from index: {}", e);
+    }
+
+    return stale;
+  }
+
+  /// Stores parsed files in the index and adds their beatmaps to the cache.
+  fn store_scanned(&mut self, scanned: Vec<ScannedFile>) {
+    if scanned.is_empty() {
+      return;
+    }
+
+    let result = self.conn.transaction().and_then(|tx| {
+      for file in &scanned {
+        Self::store_file(&tx, &file.path, file.stamp, &file.result)?;
+      }
+
+      return tx.commit();
+    });
+
+    if let Err(e) = result {
+      warn!("Failed to update beatmap index: {}", e);
+    }
+
+    for file in scanned {
+      match file.result {
+        Ok(beatmaps) => {
+          self.insert_file(file.path, file.stamp, beatmaps);
+        }
+
+        Err(e) => {
+          warn!("Skipping broken beatmap {:?}: {}", file.path, e);
+          self.forget_file(&file.path);
+          self.broken.push((file.path, e));
+        }
+      }
+    }
+  }
+
+  /// Brings the cache in line with a path reported by the watcher, which can be a beatmap file or a whole directory.
+  fn reload_path(&mut self, path: &Path) {
+    if path.is_dir() {
+      self.load_directories(&[path.to_owned()]);
+      return;
+    }
+
+    if path.is_file() {
+      if !is_beatmap_file(path) {
+        return;
+      }
+
+      match FileStamp::from_path(path) {
+        Ok(stamp) if self.files.get(path).is_some_and(|x| x.stamp == stamp) => {}
+        Ok(stamp) => self.store_scanned(vec![ScannedFile::parse(path.to_owned(), stamp)]),
+        Err(e) => warn!("Failed to read beatmap {:?}: {}", path, e),
+      }
+
+      return;
+    }
+
+    // The path is gone, which removes a single file or everything in a directory
+    let removed = (self.files.keys())
+      .chain(self.broken.iter().map(|(x, _)| x))
+      .filter(|x| x.starts_with(path))
+      .cloned()
+      .collect::<Vec<_>>();
+
+    let result = self.conn.transaction().and_then(|tx| {
+      for path in &removed {
+        Self::remove_indexed(&tx, path)?;
+      }
+
+      return tx.commit();
+    });
+
+    if let Err(e) = result {
+      warn!("Failed to remove deleted beatmaps from index: {}", e);
+    }
+
+    for path in removed {
+      self.forget_file(&path);
+    }
+  }
+
+  /// Adds the beatmaps of a file, replacing whatever the file contained before.
+  fn insert_file(&mut self, path: PathBuf, stamp: FileStamp, beatmaps: Vec<(BeatmapHash, BeatmapInfo)>) {
+    self.forget_file(&path);
+
+    let mut hashes = Vec::with_capacity(beatmaps.len());
+    for (hash, info) in beatmaps {
+      let set_entry = self.sets.entry(BeatmapSetKey::new(&info));
+      let set_idx = set_entry.index();
+      set_entry.or_insert_with(|| BeatmapSet::new(&info));
+
+      let idx = self.index_for(hash);
+      match self.cache[idx].1.replace(info) {
+        // Replaces an existing beatmap, which might have been in another set
+        Some(_) => self.remove_from_set(idx),
+        None => self.len += 1,
+      }
+
+      if self.beatmap_sets.len() <= idx {
+        self.beatmap_sets.resize(idx + 1, 0);
+      }
+
+      self.beatmap_sets[idx] = set_idx;
+
+      let cache = &self.cache;
+      let difficulties = &mut self.sets[set_idx].difficulties;
+      difficulties.push(idx);
+      difficulties.sort_by(|a, b| {
+        let difficulty = |idx: &usize| cache[*idx].1.as_ref().map(|x| x.difficulty).unwrap_or(0.0);
+        return difficulty(a).total_cmp(&difficulty(b));
+      });
+
+      self.update_set(set_idx);
+
+      hashes.push(hash);
+      self.changes.push(BeatmapChange::Inserted(idx));
+    }
+
+    self.files.insert(path, LoadedFile { stamp, hashes });
+  }
+
+  /// Index of a beatmap which is about to be inserted. Beatmaps which were removed before get their old index back if
+  /// it was not taken yet.
+  fn index_for(&mut self, hash: BeatmapHash) -> usize {
+    if let Some(idx) = self.indices.get(&hash).copied() {
+      if let Some(pos) = self.free.iter().position(|x| *x == idx) {
+        self.free.swap_remove(pos);
+      }
+
+      return idx;
+    }
+
+    let idx = match self.free.pop() {
+      Some(idx) => {
+        let (prev_hash, _) = std::mem::replace(&mut self.cache[idx], (hash, None));
+        self.indices.remove(&prev_hash);
+        idx
+      }
+
+      None => {
+        self.cache.push((hash, None));
+        self.cache.len() - 1
+      }
+    };
+
+    self.indices.insert(hash, idx);
+    return idx;
+  }
+
+  /// Removes the beatmaps of a file, their indices stay taken so nothing else moves around.
+  fn forget_file(&mut self, path: &Path) {
+    self.broken.retain(|(x, _)| x != path);
+
+    let Some(file) = self.files.remove(path) else {
+      return;
+    };
+
+    for hash in file.hashes {
+      let Some(idx) = self.indices.get(&hash).copied() else {
+        continue;
+      };
+
+      // Another file containing the same beatmap may have taken over the entry
+      let entry = &mut self.cache[idx].1;
+      if entry.as_ref().is_some_and(|x| x.file_path == path) {
+        *entry = None;
+        self.len -= 1;
+        self.remove_from_set(idx);
+        self.changes.push(BeatmapChange::Removed(idx));
+      }
+    }
+  }
+
+  fn remove_from_set(&mut self, idx: usize) {
+    let Some(set_idx) = self.beatmap_sets.get(idx).copied() else {
+      return;
+    };
+
+    if let Some((_, set)) = self.sets.get_index_mut(set_idx) {
+      set.difficulties.retain(|x| *x != idx);
+      self.update_set(set_idx);
+    }
+  }
+
+  /// Takes the set's background, audio and metadata from its easiest difficulty, so they don't belong to a
+  /// difficulty which was removed or changed.
+  fn update_set(&mut self, set_idx: usize) {
+    let cache = &self.cache;
+    let Some((_, set)) = self.sets.get_index_mut(set_idx) else {
+      return;
+    };
+
+    if let Some(info) = set.difficulties.first().and_then(|idx| cache[*idx].1.as_ref()) {
+      set.update(info);
+    }
+  }
+
+  /// Reads every entry of the index, grouped by file.
+  fn indexed_files(conn: &Connection) -> rusqlite::Result<AHashMap<PathBuf, IndexedFile>> {
+    let mut stmt = conn.prepare(
+      "select file_path, course, file_modified, file_size, hash, title, artist, creator, variant, preview_time,
+         converted, difficulty, object_count, length, bpm, hp_drain, overall_difficulty, audio_path, bg_path,
+         beatmapset_id, file_md5
+       from beatmaps
+       order by id",
+    )?;
+
+    let rows = stmt.query_map((), |row| {
+      let file_path = PathBuf::from(row.get::<_, String>(0)?);
+      let stamp = FileStamp { modified: row.get(2)?, size: row.get(3)? };
+
+      let hash = row.get::<_, String>(4)?.parse::<BeatmapHash>().ok();
+
+      let beatmap_info = BeatmapInfo {
+        title: row.get(5)?,
+        artist: row.get(6)?,
+        creator: row.get(7)?,
+        variant: row.get(8)?,
+        preview_time: row.get::<_, i64>(9)? as u64,
+
+        beatmapset_id: row.get::<_, Option<i64>>(19)?.map(|x| x as u32),
+
+        converted: row.get(10)?,
+        course: row.get::<_, Option<i64>>(1)?.map(|x| x as usize),
+
+        difficulty: row.get(11)?,
+        object_count: row.get::<_, i64>(12)? as usize,
+        length: Time::from_seconds(row.get::<_, f64>(13)?),
+        bpm: row.get(14)?,
+
+        hp_drain: row.get(15)?,
+        overall_difficulty: row.get(16)?,
+
+        file_path: file_path.clone(),
+        audio_path: PathBuf::from(row.get::<_, String>(17)?),
+        bg_path: PathBuf::from(row.get::<_, String>(18)?),
+
+        file_md5: row.get(20)?,
+      };
+
+      return Ok((file_path, stamp, hash, beatmap_info));
+    })?;
+
+    let mut files = AHashMap::<PathBuf, IndexedFile>::new();
+
+    for row in rows {
+      let (file_path, stamp, hash, beatmap_info) = row?;
+      let file = files.entry(file_path).or_insert_with(|| IndexedFile { stamp, beatmaps: Vec::new(), error: None });
+
+      match hash {
+        Some(hash) => file.beatmaps.push((hash, beatmap_info)),
+
+        // Forces the file to be parsed again
+        None => file.stamp.size = -1,
+      }
+    }
+
+    let mut stmt = conn.prepare("select file_path, file_modified, file_size, error from broken_beatmaps")?;
+    let rows = stmt.query_map((), |row| {
+      let file_path = PathBuf::from(row.get::<_, String>(0)?);
+      let stamp = FileStamp { modified: row.get(1)?, size: row.get(2)? };
+
+      return Ok((file_path, stamp, row.get::<_, String>(3)?));
+    })?;
+
+    for row in rows {
+      let (file_path, stamp, error) = row?;
+      let file = IndexedFile {
+        stamp,
+        beatmaps: Vec::new(),
+        error: Some(error),
+      };
+
+      files.insert(file_path, file);
+    }
+
+    return Ok(files);
+  }
+
+  /// Replaces the index entries of a file. Broken files keep their stamp and error, so they are only parsed again once
+  /// they change. Files which could not be read at all are tried again next time.
+  fn store_file(
+    conn: &Connection,
+    path: &Path,
+    stamp: FileStamp,
+    result: &Result<Vec<(BeatmapHash, BeatmapInfo)>, BeatmapParseError>,
+  ) -> rusqlite::Result<()> {
+    Self::remove_indexed(conn, path)?;
+
+    let file_path = path.to_string_lossy();
+    let beatmaps = match result {
+      Ok(beatmaps) => beatmaps,
+      Err(BeatmapParseError::Io(_)) => return Ok(()),
+      Err(e) => {
+        conn.execute(
+          "insert into broken_beatmaps (file_path, file_modified, file_size, error) values (?1, ?2, ?3, ?4)",
+          (&file_path, stamp.modified, stamp.size, e.to_string()),
+        )?;
+
+        return Ok(());
+      }
+    };
+
+    let mut stmt = conn.prepare_cached(
+      "insert into beatmaps (file_path, course, file_modified, file_size, hash, title, artist, creator, variant,
+         preview_time, converted, difficulty, object_count, length, bpm, hp_drain, overall_difficulty, audio_path,
+         bg_path, beatmapset_id, file_md5)
+       values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21)",
+    )?;
+
+    for (hash, info) in beatmaps {
+      stmt.execute(rusqlite::params![
+        file_path,
+        info.course.map(|x| x as i64),
+        stamp.modified,
+        stamp.size,
+        hash.to_string(),
+        info.title,
+        info.artist,
+        info.creator,
+        info.variant,
+        info.preview_time as i64,
+        info.converted,
+        info.difficulty,
+        info.object_count as i64,
+        info.length.to_seconds(),
+        info.bpm,
+        info.hp_drain,
+        info.overall_difficulty,
+        info.audio_path.to_string_lossy(),
+        info.bg_path.to_string_lossy(),
+        info.beatmapset_id.map(|x| x as i64),
+        info.file_md5,
+      ])?;
+    }
+
+    return Ok(());
+  }
+
+  fn remove_indexed(conn: &Connection, path: &Path) -> rusqlite::Result<()> {
+    let file_path = path.to_string_lossy();
+    conn.execute("delete from beatmaps where file_path = ?1", (&file_path,))?;
+    conn.execute("delete from broken_beatmaps where file_path = ?1", (&file_path,))?;
+
+    return Ok(());
+  }
+
+  fn load_beatmap(path: &Path) -> Result<(BeatmapHash, BeatmapInfo), BeatmapParseError> {
+    let data = std::fs::read_to_string(path)?;
+
+    let mut warnings = Vec::new();
+    let beatmap = Beatmap::parse_lenient(&data, path.to_owned(), &mut warnings)?;
+    let beatmap_info = BeatmapInfo::parse(&data, path.to_owned())?;
+
+    for warning in warnings {
+      warn!("{:?}: {}", path, warning);
+    }
+
+    return Ok((beatmap.hash(), beatmap_info));
+  }
+
+  /// Every course of a TJA chart becomes a separate difficulty.
+  fn load_tja(path: &Path) -> Result<Vec<(BeatmapHash, BeatmapInfo)>, BeatmapParseError> {
+    let mut difficulties = Vec::new();
+    let file_md5 = format!("{:x}", md5::compute(std::fs::read(path)?));
+
+    for (course, beatmap) in Beatmap::from_tja_path(path)?.into_iter().enumerate() {
+      let mut beatmap_info = BeatmapInfo::from_beatmap(&beatmap)?;
+      beatmap_info.course = Some(course);
+      beatmap_info.file_md5.clone_from(&file_md5);
+
+      difficulties.push((beatmap.hash(), beatmap_info));
+    }
+
+    return Ok(difficulties);
+  }
+
+  /// Beatmap files which failed to load, along with the reason why.
+  pub fn broken(&self) -> &[(PathBuf, BeatmapParseError)] {
+    return &self.broken;
   }
 
   pub fn get(&self, hash: BeatmapHash) -> Option<&BeatmapInfo> {
-    return self.cache.get(&hash);
+    return self.indices.get(&hash).and_then(|idx| self.cache[*idx].1.as_ref());
+  }
+
+  /// Finds a beatmap by the MD5 of its file, all courses of a TJA chart share it so the first one is returned.
+  pub fn find_by_md5(&self, md5: &str) -> Option<(BeatmapHash, &BeatmapInfo)> {
+    return self.iter().find(|(_, info)| info.file_md5.eq_ignore_ascii_case(md5));
   }
 
+  /// Beatmap at an index, `None` if it was removed.
   pub fn get_index(&self, idx: usize) -> Option<(BeatmapHash, &BeatmapInfo)> {
-    return self.cache.get_index(idx).map(|(hash, beatmap_info)| (*hash, beatmap_info));
+    let (hash, beatmap_info) = self.cache.get(idx)?;
+    return beatmap_info.as_ref().map(|beatmap_info| (*hash, beatmap_info));
   }
 
   pub fn iter(&self) -> impl Iterator<Item = (BeatmapHash, &BeatmapInfo)> {
-    return self.cache.iter().map(|(hash, beatmap_info)| (*hash, beatmap_info));
+    return self.cache.iter().filter_map(|(hash, beatmap_info)| beatmap_info.as_ref().map(|x| (*hash, x)));
+  }
+
+  /// Set of the beatmap at an index along with the set's index.
+  pub fn set_of(&self, idx: usize) -> Option<(usize, &BeatmapSet)> {
+    self.get_index(idx)?;
+
+    let set_idx = *self.beatmap_sets.get(idx)?;
+    return self.sets.get_index(set_idx).map(|(_, set)| (set_idx, set));
+  }
+
+  pub fn get_set(&self, set_idx: usize) -> Option<&BeatmapSet> {
+    return self.sets.get_index(set_idx).map(|(_, set)| set).filter(|x| !x.is_empty());
+  }
+
+  /// Sets which have at least one difficulty, along with their indices.
+  pub fn sets(&self) -> impl Iterator<Item = (usize, &BeatmapSet)> {
+    return self.sets.values().enumerate().filter(|(_, set)| !set.is_empty());
+  }
+
+  /// How many changes were made to the cache so far.
+  pub fn revision(&self) -> usize {
+    return self.acknowledged + self.changes.len();
   }
 
-  pub fn last_update(&self) -> Instant {
-    return self.last_update;
+  /// Changes made after `revision` in order. A revision older than the last acknowledged one, e.g. of a consumer which
+  /// was created after it, gets a full resync instead which inserts or removes every index.
+  pub fn changes_since(&self, revision: usize) -> Cow<'_, [BeatmapChange]> {
+    let Some(unseen) = revision.checked_sub(self.acknowledged) else {
+      let resync = self.cache.iter().enumerate().map(|(idx, (_, beatmap_info))| {
+        match beatmap_info {
+          Some(_) => BeatmapChange::Inserted(idx),
+          None => BeatmapChange::Removed(idx),
+        }
+      });
+
+      return Cow::Owned(resync.collect());
+    };
+
+    return Cow::Borrowed(&self.changes[unseen.min(self.changes.len()) ..]);
+  }
+
+  /// Drops the changes up to `revision`, which every consumer has to have seen already. Indices of beatmaps removed by
+  /// them can be reused from now on.
+  pub fn acknowledge_changes(&mut self, revision: usize) {
+    let seen = revision.saturating_sub(self.acknowledged).min(self.changes.len());
+    let index_of = |change: &BeatmapChange| {
+      let (BeatmapChange::Inserted(idx) | BeatmapChange::Removed(idx)) = *change;
+      return idx;
+    };
+
+    for (i, change) in self.changes[.. seen].iter().enumerate() {
+      let BeatmapChange::Removed(idx) = *change else {
+        continue;
+      };
+
+      // The index is inserted or removed again later, which frees it once that change is seen
+      if !self.changes[i + 1 ..].iter().any(|x| index_of(x) == idx) {
+        self.free.push(idx);
+      }
+    }
+
+    self.changes.drain(.. seen);
+    self.acknowledged += seen;
   }
 
   pub fn len(&self) -> usize {
-    return self.cache.len();
+    return self.len;
   }
 
   pub fn is_empty(&self) -> bool {
-    return self.cache.is_empty();
+    return self.len == 0;
   }
 }
+
+fn is_beatmap_file(path: &Path) -> bool {
+  return matches!(path.extension().and_then(|x| x.to_str()), Some("osu" | "tja"));
+}
@@ -0,0 +1,79 @@
+use indexmap::IndexMap;
+use log::warn;
+use rusqlite::Connection;
+
+use super::beatmap::BeatmapHash;
+
+/// Named lists of beatmaps, e.g. the collections imported from osu!stable.
+pub struct BeatmapCollections {
+  conn: Connection,
+  collections: IndexMap<String, Vec<BeatmapHash>>,
+}
+
+impl BeatmapCollections {
+  pub fn new(connection: Connection) -> Self {
+    connection
+      .execute(
+        "create table if not exists collections (
+           name text not null,
+           hash text not null,
+           primary key (name, hash)
+         )",
+        (),
+      )
+      .unwrap();
+
+    let collections = Self::load(&connection).unwrap_or_else(|e| {
+      warn!("Failed to load collections: {}", e);
+      IndexMap::new()
+    });
+
+    return Self { conn: connection, collections };
+  }
+
+  fn load(conn: &Connection) -> rusqlite::Result<IndexMap<String, Vec<BeatmapHash>>> {
+    let mut stmt = conn.prepare("select name, hash from collections order by rowid")?;
+    let rows = stmt.query_map((), |row| return Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))?;
+
+    let mut collections = IndexMap::<_, Vec<_>>::new();
+    for row in rows {
+      let (name, hash) = row?;
+
+      if let Ok(hash) = hash.parse::<BeatmapHash>() {
+        collections.entry(name).or_default().push(hash);
+      }
+    }
+
+    return Ok(collections);
+  }
+
+  /// Adds beatmaps to a collection, creating it if needed. Beatmaps which are in it already are skipped.
+  pub fn insert(&mut self, name: &str, beatmaps: &[BeatmapHash]) {
+    let collection = self.collections.entry(name.to_owned()).or_default();
+
+    let result = self.conn.transaction().and_then(|tx| {
+      for hash in beatmaps {
+        if collection.contains(hash) {
+          continue;
+        }
+
+        tx.execute("insert or ignore into collections (name, hash) values (?1, ?2)", (name, hash.to_string()))?;
+        collection.push(*hash);
+      }
+
+      return tx.commit();
+    });
+
+    if let Err(e) = result {
+      warn!("Failed to store collection {:?}: {}", name, e);
+    }
+  }
+
+  pub fn get(&self, name: &str) -> Option<&[BeatmapHash]> {
+    return self.collections.get(name).map(Vec::as_slice);
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = (&str, &[BeatmapHash])> {
+    return self.collections.iter().map(|(name, beatmaps)| (name.as_str(), beatmaps.as_slice()));
+  }
+}
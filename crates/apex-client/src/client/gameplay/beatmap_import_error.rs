@@ -0,0 +1,26 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+use super::beatmap_parse_error::BeatmapParseError;
+
+#[derive(Debug, Error)]
+pub enum BeatmapImportError {
+  #[error("Failed to read archive: {0}")]
+  Archive(#[from] zip::result::ZipError),
+
+  #[error("Failed to extract archive: {0}")]
+  Io(#[from] std::io::Error),
+
+  #[error("Archive entry {0:?} points outside of the beatmap directory")]
+  UnsafePath(String),
+
+  #[error("Archive does not contain any beatmaps")]
+  NoBeatmaps,
+
+  #[error("{path:?}: {source}")]
+  InvalidBeatmap { path: PathBuf, source: BeatmapParseError },
+
+  #[error("Beatmap set is already imported in {0:?}")]
+  AlreadyImported(PathBuf),
+}
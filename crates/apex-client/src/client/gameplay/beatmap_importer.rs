@@ -0,0 +1,244 @@
+use std::{
+  fs::File,
+  io::{Cursor, Read as _},
+  path::{Path, PathBuf},
+  thread::JoinHandle,
+};
+
+use ahash::AHashMap;
+use zip::ZipArchive;
+
+use super::{
+  beatmap::{Beatmap, BeatmapHash},
+  beatmap_cache::{BeatmapCache, BeatmapInfo},
+  beatmap_import_error::BeatmapImportError,
+  beatmap_parse_error::BeatmapParseError,
+};
+
+/// Characters which are not allowed in file names on at least one platform.
+const RESERVED_CHARS: &[char] = &['<', '>', ':', '"', '/', '\\', '|', '?', '*'];
+
+/// Imports `.osz` archives into the beatmaps directory.
+///
+/// Archives are queued as they are dropped and imported in batches on a separate thread, one batch at a time, so
+/// duplicates between archives dropped together are detected as well.
+pub struct BeatmapImporter {
+  directory: PathBuf,
+  queued: Vec<(PathBuf, Vec<u8>)>,
+
+  worker: Option<JoinHandle<AHashMap<BeatmapHash, PathBuf>>>,
+
+  /// Beatmaps imported by previous batches, they might not be in the cache yet.
+  imported: AHashMap<BeatmapHash, PathBuf>,
+}
+
+impl BeatmapImporter {
+  pub fn new(directory: impl Into<PathBuf>) -> Self {
+    return Self {
+      directory: directory.into(),
+      queued: Vec::new(),
+      worker: None,
+      imported: AHashMap::new(),
+    };
+  }
+
+  pub fn queue(&mut self, archive: PathBuf, file: Vec<u8>) {
+    self.queued.push((archive, file));
+  }
+
+  /// Starts importing the queued archives unless a batch is still being imported, `on_imported` is called with the
+  /// directory of every imported set.
+  pub fn start(
+    &mut self,
+    beatmap_cache: &BeatmapCache,
+    on_imported: impl Fn(PathBuf, Result<PathBuf, BeatmapImportError>) + Send + 'static,
+  ) {
+    if self.queued.is_empty() || self.worker.as_ref().is_some_and(|worker| !worker.is_finished()) {
+      return;
+    }
+
+    if let Some(worker) = self.worker.take() {
+      match worker.join() {
+        Ok(imported) => self.imported.extend(imported),
+        Err(_) => log::error!("Beatmap import thread panicked"),
+      }
+    }
+
+    let queued = std::mem::take(&mut self.queued);
+    let directory = self.directory.clone();
+
+    let mut known = self.imported.clone();
+    known.extend(beatmap_cache.iter().map(|(hash, info)| (hash, set_directory(info))));
+
+    self.worker = Some(std::thread::spawn(move || {
+      let mut imported = AHashMap::new();
+
+      for (archive, file) in queued {
+        let result = import_archive(&file, &directory, &mut known);
+
+        // `known` holds the beatmaps of the cache too, only the new ones need to be remembered for later batches
+        if let Ok(target) = &result {
+          imported.extend(known.iter().filter(|(_, dir)| *dir == target).map(|(hash, dir)| (*hash, dir.clone())));
+        }
+
+        on_imported(archive, result);
+      }
+
+      return imported;
+    }));
+  }
+}
+
+/// Validates a `.osz` archive and extracts it into a new directory inside `directory`.
+///
+/// `known` maps beatmap hashes to the directory they are in, archives without any new beatmaps are rejected and the
+/// imported ones are added to it. Archives which share beatmaps with a known set are extracted into its directory
+/// instead, without touching the files which are already there.
+pub fn import_archive(
+  file: &[u8],
+  directory: &Path,
+  known: &mut AHashMap<BeatmapHash, PathBuf>,
+) -> Result<PathBuf, BeatmapImportError> {
+  let mut archive = ZipArchive::new(Cursor::new(file))?;
+
+  let mut entries = Vec::with_capacity(archive.len());
+  let mut beatmaps = Vec::new();
+  let mut first_error = None;
+
+  for idx in 0 .. archive.len() {
+    let mut entry = archive.by_index(idx)?;
+
+    // Absolute paths and `..` components would escape the set directory
+    let Some(path) = entry.enclosed_name() else {
+      return Err(BeatmapImportError::UnsafePath(entry.name().to_owned()));
+    };
+
+    if entry.is_file() && path.extension().is_some_and(|x| x.eq_ignore_ascii_case("osu")) {
+      let mut data = String::new();
+      let beatmap = entry.read_to_string(&mut data).map_err(BeatmapParseError::from);
+
+      match beatmap.and_then(|_| parse_beatmap(&data, path.clone())) {
+        Ok(beatmap) => beatmaps.push((path.clone(), beatmap)),
+        Err(source) => {
+          first_error.get_or_insert(BeatmapImportError::InvalidBeatmap { path: path.clone(), source });
+        }
+      }
+    }
+
+    entries.push((idx, path));
+  }
+
+  let Some((_, (_, info))) = beatmaps.first() else {
+    return Err(first_error.unwrap_or(BeatmapImportError::NoBeatmaps));
+  };
+
+  if let Some(existing) = beatmaps.iter().map(|(_, (hash, _))| known.get(hash)).collect::<Option<Vec<_>>>() {
+    return Err(BeatmapImportError::AlreadyImported(existing[0].clone()));
+  }
+
+  let existing = beatmaps.iter().find_map(|(_, (hash, _))| known.get(hash)).cloned();
+  let target = match &existing {
+    Some(existing) => existing.clone(),
+    None => unique_directory(directory, &directory_name(info)),
+  };
+
+  // Known difficulties are already in the set, possibly under another name
+  let duplicates = beatmaps.iter().filter(|(_, (hash, _))| known.contains_key(hash)).map(|(path, _)| path);
+  let duplicates = duplicates.collect::<Vec<_>>();
+  entries.retain(|(_, path)| !duplicates.contains(&path) && !target.join(path).exists());
+
+  let mut created = Vec::new();
+  if let Err(e) = extract(&mut archive, &entries, &target, &mut created) {
+    // Leaving a partially extracted set behind would get it picked up by the beatmap scan
+    for path in created {
+      let _ = std::fs::remove_file(path);
+    }
+
+    if existing.is_none() {
+      let _ = std::fs::remove_dir_all(&target);
+    }
+
+    return Err(e);
+  }
+
+  for (_, (hash, _)) in beatmaps {
+    known.insert(hash, target.clone());
+  }
+
+  return Ok(target);
+}
+
+fn parse_beatmap(data: &str, path: PathBuf) -> Result<(BeatmapHash, BeatmapInfo), BeatmapParseError> {
+  let beatmap = Beatmap::parse_lenient(data, path.clone(), &mut Vec::new())?;
+  let beatmap_info = BeatmapInfo::parse(data, path)?;
+
+  return Ok((beatmap.hash(), beatmap_info));
+}
+
+/// Extracts the given entries, `created` collects the files written so far.
+fn extract(
+  archive: &mut ZipArchive<Cursor<&[u8]>>,
+  entries: &[(usize, PathBuf)],
+  target: &Path,
+  created: &mut Vec<PathBuf>,
+) -> Result<(), BeatmapImportError> {
+  std::fs::create_dir_all(target)?;
+
+  for (idx, path) in entries {
+    let mut entry = archive.by_index(*idx)?;
+    let path = target.join(path);
+
+    if entry.is_dir() {
+      std::fs::create_dir_all(&path)?;
+      continue;
+    }
+
+    if let Some(parent) = path.parent() {
+      std::fs::create_dir_all(parent)?;
+    }
+
+    let mut file = File::create(&path)?;
+    created.push(path);
+    std::io::copy(&mut entry, &mut file)?;
+  }
+
+  return Ok(());
+}
+
+fn set_directory(info: &BeatmapInfo) -> PathBuf {
+  return info.file_path.parent().map(PathBuf::from).unwrap_or_default();
+}
+
+/// Follows the `<id> <artist> - <title>` naming of osu!stable.
+fn directory_name(info: &BeatmapInfo) -> String {
+  let name = match info.beatmapset_id {
+    Some(id) => format!("{} {} - {}", id, info.artist, info.title),
+    None => format!("{} - {}", info.artist, info.title),
+  };
+
+  return sanitize_file_name(&name);
+}
+
+/// Replaces characters which aren't allowed in file names on any platform.
+pub(crate) fn sanitize_file_name(name: &str) -> String {
+  let name = name
+    .chars()
+    .map(|c| if RESERVED_CHARS.contains(&c) || c.is_control() { '_' } else { c })
+    .collect::<String>();
+
+  // Windows does not allow trailing dots or spaces either
+  return name.trim_end_matches(['.', ' ']).to_owned();
+}
+
+/// Never extracts over an existing directory, a numbered suffix is added instead.
+fn unique_directory(directory: &Path, name: &str) -> PathBuf {
+  let mut path = directory.join(name);
+  let mut suffix = 2;
+
+  while path.exists() {
+    path = directory.join(format!("{} ({})", name, suffix));
+    suffix += 1;
+  }
+
+  return path;
+}
@@ -0,0 +1,66 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BeatmapParseError {
+  #[error("Failed to read beatmap file: {0}")]
+  Io(#[from] std::io::Error),
+
+  #[error("{section} line {line}: {reason}")]
+  InvalidLine {
+    section: String,
+    line: usize,
+    reason: String,
+  },
+
+  #[error("{section} is missing the required `{key}` property")]
+  MissingProperty { section: &'static str, key: &'static str },
+
+  #[error("Unsupported game mode: {0}")]
+  UnsupportedMode(String),
+
+  #[error("Failed to calculate difficulty: {0}")]
+  Difficulty(String),
+
+  #[error("File does not contain any charts")]
+  NoCharts,
+
+  /// Error of a file which failed to parse during an earlier scan, as it was stored in the beatmap index.
+  #[error("{0}")]
+  Indexed(String),
+}
+
+/// Decides what happens to recoverable errors (i.e. a single malformed line) while parsing.
+///
+/// In strict mode the first one is returned as an error, in lenient mode they are collected as warnings and the
+/// offending line is skipped.
+pub(crate) struct ParseReporter<'a> {
+  warnings: Option<&'a mut Vec<BeatmapParseError>>,
+}
+
+impl<'a> ParseReporter<'a> {
+  pub fn strict() -> Self {
+    return Self { warnings: None };
+  }
+
+  pub fn lenient(warnings: &'a mut Vec<BeatmapParseError>) -> Self {
+    return Self { warnings: Some(warnings) };
+  }
+
+  /// `line_idx` is zero-based, as returned by `enumerate`.
+  pub fn report(&mut self, section: Option<&str>, line_idx: usize, reason: &str) -> Result<(), BeatmapParseError> {
+    let error = BeatmapParseError::InvalidLine {
+      section: section.unwrap_or_default().to_owned(),
+      line: line_idx + 1,
+      reason: reason.to_owned(),
+    };
+
+    match &mut self.warnings {
+      Some(warnings) => {
+        warnings.push(error);
+        return Ok(());
+      }
+
+      None => return Err(error),
+    }
+  }
+}
@@ -1,21 +1,32 @@
 use std::sync::Arc;
 
-use instant::Instant;
+use indexmap::IndexMap;
 use log::debug;
 use nucleo::{
   pattern::{CaseMatching, Normalization},
   Nucleo,
 };
 
-use crate::client::gameplay::beatmap_cache::BeatmapCache;
+use crate::client::gameplay::beatmap_cache::{BeatmapCache, BeatmapChange};
+
+/// Item of the fuzzy matcher: the beatmap's cache index, the revision it was pushed with and its search string.
+type SelectorItem = (usize, usize, String);
 
 pub struct BeatmapSelector {
-  matcher: Nucleo<(usize, String)>,
+  matcher: Nucleo<SelectorItem>,
+
+  /// Revision of the current item of every cache index, `None` if the beatmap was removed. The matcher can't remove
+  /// items, so outdated ones are filtered out by comparing against this.
+  revisions: Vec<Option<usize>>,
+
+  /// How many of the cache's changes were already applied.
+  seen_changes: usize,
+
+  /// Matches grouped by set, rebuilt only when the matches or the library change.
+  matched_sets: Vec<(usize, Vec<usize>)>,
 
   selected_idx: usize,
   search_query: String,
-
-  last_update: Instant,
 }
 
 impl BeatmapSelector {
@@ -27,38 +38,84 @@ impl BeatmapSelector {
       1,
     );
 
-    for (i, (_, info)) in beatmap_cache.iter().enumerate() {
-      let q_str = format!("{}{}{}{}", &info.title, &info.artist, &info.variant, &info.creator);
-      matcher.injector().push((i, q_str), |(_, q_str), cols| {
-        cols[0] = q_str.clone().into();
-      });
-    }
-
-    return Self {
+    let mut selector = Self {
       matcher,
+      revisions: Vec::new(),
+      seen_changes: 0,
+      matched_sets: Vec::new(),
       selected_idx: 0,
       search_query: String::new(),
-      last_update: Instant::now(),
     };
+
+    selector.apply_changes(beatmap_cache);
+    selector.group_matches(beatmap_cache);
+
+    return selector;
   }
 
   pub fn tick(&mut self, beatmap_cache: &BeatmapCache) {
-    self.matcher.tick(10);
+    let status = self.matcher.tick(10);
+    if self.apply_changes(beatmap_cache) || status.changed {
+      self.group_matches(beatmap_cache);
+    }
 
-    // TODO: this is going to be very slow on a large number of beatmaps, probably go with event based approach
-    if beatmap_cache.last_update() > self.last_update {
-      debug!("Updating beatmap list");
+    // Don't leave the selection on a beatmap that no longer exists
+    if beatmap_cache.get_index(self.selected_idx).is_none() {
+      let first = self.listed().next();
+      if let Some(idx) = first {
+        self.selected_idx = idx;
+      }
+    }
+  }
 
-      self.last_update = Instant::now();
-      self.matcher.restart(true);
+  /// Returns whether there were any changes to apply.
+  fn apply_changes(&mut self, beatmap_cache: &BeatmapCache) -> bool {
+    let changes = beatmap_cache.changes_since(self.seen_changes);
+    if changes.is_empty() {
+      return false;
+    }
 
-      for (i, (_, info)) in beatmap_cache.iter().enumerate() {
-        let q_str = format!("{}{}{}{}", &info.title, &info.artist, &info.variant, &info.creator);
-        self.matcher.injector().push((i, q_str), |(_, q_str), cols| {
-          cols[0] = q_str.clone().into();
-        });
+    debug!("Updating beatmap list with {} changes", changes.len());
+
+    for (revision, change) in (self.seen_changes ..).zip(changes.iter()) {
+      match *change {
+        BeatmapChange::Inserted(idx) => {
+          // Removed again by a later change
+          let Some((_, info)) = beatmap_cache.get_index(idx) else {
+            continue;
+          };
+
+          if self.revisions.len() <= idx {
+            self.revisions.resize(idx + 1, None);
+          }
+
+          self.revisions[idx] = Some(revision);
+
+          let q_str = format!("{}{}{}{}", &info.title, &info.artist, &info.variant, &info.creator);
+          self.matcher.injector().push((idx, revision, q_str), |(_, _, q_str), cols| {
+            cols[0] = q_str.clone().into();
+          });
+        }
+
+        BeatmapChange::Removed(idx) => {
+          if let Some(revision) = self.revisions.get_mut(idx) {
+            *revision = None;
+          }
+        }
       }
     }
+
+    self.seen_changes = beatmap_cache.revision();
+    return true;
+  }
+
+  /// Revision of the cache up to which changes were applied.
+  pub fn seen_changes(&self) -> usize {
+    return self.seen_changes;
+  }
+
+  fn is_current(&self, (idx, revision, _): &SelectorItem) -> bool {
+    return self.revisions.get(*idx) == Some(&Some(*revision));
   }
 
   pub fn selected(&self) -> usize {
@@ -70,7 +127,7 @@ impl BeatmapSelector {
   }
 
   pub fn matched(&self) -> impl Iterator<Item = usize> + '_ {
-    return self.matcher.snapshot().matched_items(..).map(|x| x.data.0);
+    return self.matcher.snapshot().matched_items(..).filter(|x| self.is_current(x.data)).map(|x| x.data.0);
   }
 
   pub fn query(&self) -> &str {
@@ -106,29 +163,58 @@ impl BeatmapSelector {
     return !self.search_query.is_empty();
   }
 
+  /// Matched beatmaps grouped by set. Sets are in the order their best match was found, their difficulties are sorted
+  /// by star rating.
+  pub fn matched_sets(&self) -> &[(usize, Vec<usize>)] {
+    return &self.matched_sets;
+  }
+
+  fn group_matches(&mut self, beatmap_cache: &BeatmapCache) {
+    let mut sets = IndexMap::<usize, Vec<usize>, ahash::RandomState>::default();
+    for idx in self.matched() {
+      if let Some((set_idx, _)) = beatmap_cache.set_of(idx) {
+        sets.entry(set_idx).or_default().push(idx);
+      }
+    }
+
+    self.matched_sets = sets
+      .into_iter()
+      .map(|(set_idx, mut matched)| {
+        if let Some(set) = beatmap_cache.get_set(set_idx) {
+          matched.sort_by_key(|idx| set.difficulties().iter().position(|x| x == idx));
+        }
+
+        return (set_idx, matched);
+      })
+      .collect();
+  }
+
+  /// Matched beatmaps in the order they are listed.
+  fn listed(&self) -> impl Iterator<Item = usize> + '_ {
+    return self.matched_sets.iter().flat_map(|(_, matched)| matched.iter().copied());
+  }
+
   pub fn select_next(&mut self) {
-    let snapshot = self.matcher.snapshot();
-    let mut iter = snapshot.matched_items(..);
-    let Some(idx) = iter.position(|x| x.data.0 == self.selected_idx) else {
-      self.selected_idx = snapshot.matched_items(..).next().map(|x| x.data.0).unwrap_or(0);
+    let listed = self.listed().collect::<Vec<_>>();
+    let Some(pos) = listed.iter().position(|x| *x == self.selected_idx) else {
+      self.selected_idx = listed.first().copied().unwrap_or(0);
       return;
     };
 
-    if let Some(info) = snapshot.get_matched_item(idx as u32 + 1) {
-      self.selected_idx = info.data.0;
+    if let Some(idx) = listed.get(pos + 1) {
+      self.selected_idx = *idx;
     }
   }
 
   pub fn select_prev(&mut self) {
-    let snapshot = self.matcher.snapshot();
-    let mut iter = snapshot.matched_items(..);
-    let Some(idx) = iter.position(|x| x.data.0 == self.selected_idx) else {
-      self.selected_idx = snapshot.matched_items(..).next().map(|x| x.data.0).unwrap_or(0);
+    let listed = self.listed().collect::<Vec<_>>();
+    let Some(pos) = listed.iter().position(|x| *x == self.selected_idx) else {
+      self.selected_idx = listed.first().copied().unwrap_or(0);
       return;
     };
 
-    if let Some(info) = snapshot.get_matched_item(idx as u32 - 1) {
-      self.selected_idx = info.data.0;
+    if let Some(idx) = pos.checked_sub(1).and_then(|pos| listed.get(pos)) {
+      self.selected_idx = *idx;
     }
   }
 }
@@ -0,0 +1,94 @@
+use std::path::PathBuf;
+
+use super::beatmap_cache::BeatmapInfo;
+
+/// What makes difficulties belong to the same set, besides sharing a directory.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum SetIdentity {
+  Id(u32),
+
+  /// Unsubmitted beatmaps and TJA charts have no set id, so they are grouped by song.
+  Metadata {
+    artist: String,
+    title: String,
+  },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) struct BeatmapSetKey {
+  directory: PathBuf,
+  identity: SetIdentity,
+}
+
+impl BeatmapSetKey {
+  pub(crate) fn new(info: &BeatmapInfo) -> Self {
+    let directory = info.file_path.parent().map(PathBuf::from).unwrap_or_default();
+    let identity = match info.beatmapset_id {
+      Some(id) => SetIdentity::Id(id),
+      None => {
+        SetIdentity::Metadata {
+          artist: info.artist.clone(),
+          title: info.title.clone(),
+        }
+      }
+    };
+
+    return Self { directory, identity };
+  }
+}
+
+/// Difficulties of the same song in the same directory, they share the background and the audio preview.
+#[derive(Debug, Clone)]
+pub struct BeatmapSet {
+  pub directory: PathBuf,
+  pub beatmapset_id: Option<u32>,
+
+  pub title: String,
+  pub artist: String,
+  pub creator: String,
+
+  /// Paths are relative to the working directory, unlike the ones in [`BeatmapInfo`].
+  pub audio_path: PathBuf,
+  pub bg_path: PathBuf,
+  pub preview_time: u64,
+
+  /// Cache indices of the difficulties, sorted by star rating.
+  pub(crate) difficulties: Vec<usize>,
+}
+
+impl BeatmapSet {
+  /// Creates an empty set, taking its metadata from the first difficulty.
+  pub(crate) fn new(info: &BeatmapInfo) -> Self {
+    let directory = info.file_path.parent().map(PathBuf::from).unwrap_or_default();
+
+    return Self {
+      audio_path: directory.join(&info.audio_path),
+      bg_path: directory.join(&info.bg_path),
+      preview_time: info.preview_time,
+
+      directory,
+      beatmapset_id: info.beatmapset_id,
+
+      title: info.title.clone(),
+      artist: info.artist.clone(),
+      creator: info.creator.clone(),
+
+      difficulties: Vec::new(),
+    };
+  }
+
+  /// Takes the shared files and metadata from another difficulty, as they may differ between difficulties.
+  pub(crate) fn update(&mut self, info: &BeatmapInfo) {
+    let difficulties = std::mem::take(&mut self.difficulties);
+    *self = Self { difficulties, ..Self::new(info) };
+  }
+
+  /// Cache indices of the difficulties, from the easiest to the hardest.
+  pub fn difficulties(&self) -> &[usize] {
+    return &self.difficulties;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return self.difficulties.is_empty();
+  }
+}
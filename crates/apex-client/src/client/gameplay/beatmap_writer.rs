@@ -0,0 +1,269 @@
+use std::{
+  io::{self, Write},
+  path::Path,
+};
+
+use apex_framework::time::time::Time;
+use indexmap::IndexMap;
+
+use super::{
+  beatmap::{active_point, Beatmap, EffectPoint, TimingPoint, VelocityPoint, OSU_BASE_SCORING_DISTANCE},
+  taiko_hit_object::{HitSamples, TaikoDrumroll, TaikoHitObject},
+};
+
+type Properties = IndexMap<String, IndexMap<String, String>>;
+
+/// Returns the value closest to `guess` which satisfies `check`, or `guess` itself if there is none nearby.
+///
+/// Most values are stored after a conversion (e.g. milliseconds to seconds, beat length to bpm) and simply reversing it
+/// can be off by a few ulps, which would change the beatmap when it's parsed again.
+fn nudge(guess: f64, check: impl Fn(f64) -> bool) -> f64 {
+  for step in 0 ..= 16 {
+    for bits in [guess.to_bits().wrapping_add(step), guess.to_bits().wrapping_sub(step)] {
+      let candidate = f64::from_bits(bits);
+
+      if check(candidate) {
+        return candidate;
+      }
+    }
+  }
+
+  return guess;
+}
+
+/// Milliseconds which parse back to exactly `time`.
+fn time_ms(time: Time) -> f64 {
+  return nudge(time.to_seconds() * 1000.0, |ms| Time::from_ms(ms) == time);
+}
+
+/// Hit sample column of a hit object, the values are already inherited so nothing is left to the timing points.
+fn hit_samples(samples: &HitSamples) -> String {
+  let (sample_set, addition_set) = (samples.sample_set.id(), samples.addition_set.id());
+  return format!("{}:{}:{}:{}:", sample_set, addition_set, samples.index, samples.volume);
+}
+
+fn set_property(properties: &mut Properties, section: &str, key: &str, value: String) {
+  properties.entry(section.to_owned()).or_default().insert(key.to_owned(), value);
+}
+
+/// Same as [`set_property`], but keeps the original formatting if it still parses to the same value.
+fn set_number_property(properties: &mut Properties, section: &str, key: &str, value: f32) {
+  let current = properties.get(section).and_then(|x| x.get(key)).and_then(|x| x.parse::<f32>().ok());
+
+  if current != Some(value) {
+    set_property(properties, section, key, value.to_string());
+  }
+}
+
+impl Beatmap {
+  /// Writes the beatmap to a file in the `.osu` format, see [`Beatmap::write`].
+  pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+    let mut writer = io::BufWriter::new(std::fs::File::create(path)?);
+    self.write(&mut writer)?;
+
+    return writer.flush();
+  }
+
+  /// Writes the beatmap in the `.osu` format.
+  ///
+  /// Parsing the output again results in the same beatmap with the same hash. Properties of the other sections are
+  /// written back the way they were read, as are events other than the background and breaks.
+  pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+    let properties = self.written_properties();
+
+    // Drumrolls are converted from sliders using whatever the parser is going to read
+    let difficulty =
+      |key: &str| properties.get("[Difficulty]").and_then(|x| x.get(key)).and_then(|x| x.parse::<f64>().ok());
+    let slider_multiplier = difficulty("SliderMultiplier").unwrap_or(0.6);
+
+    writeln!(writer, "osu file format v14")?;
+
+    for (section, properties) in &properties {
+      let separator = if section == "[General]" || section == "[Editor]" { ": " } else { ":" };
+
+      writeln!(writer)?;
+      writeln!(writer, "{}", section)?;
+
+      for (key, value) in properties {
+        writeln!(writer, "{}{}{}", key, separator, value)?;
+      }
+    }
+
+    writeln!(writer)?;
+    writeln!(writer, "[Events]")?;
+
+    if !self.bg_path.as_os_str().is_empty() {
+      writeln!(writer, "0,0,\"{}\",0,0", self.bg_path.display())?;
+    }
+
+    for break_point in &self.break_points {
+      writeln!(writer, "2,{},{}", time_ms(break_point.start), time_ms(break_point.end))?;
+    }
+
+    for event in &self.events {
+      writeln!(writer, "{}", event)?;
+    }
+
+    writeln!(writer)?;
+    writeln!(writer, "[TimingPoints]")?;
+    self.write_timing_points(&mut writer)?;
+
+    writeln!(writer)?;
+    writeln!(writer, "[HitObjects]")?;
+
+    for hit_object in &self.hit_objects {
+      match hit_object {
+        TaikoHitObject::Hit(hit) => {
+          // Whistles become kat, finishes become big
+          let hitsounds = ((hit.color.is_kat() as u8) << 1) | ((hit.big as u8) << 2);
+          writeln!(writer, "256,192,{},1,{},{}", time_ms(hit.time), hitsounds, hit_samples(&hit.samples))?;
+        }
+
+        TaikoHitObject::Drumroll(drumroll) => {
+          let hitsounds = (drumroll.big as u8) << 2;
+          let length = self.slider_length(drumroll, slider_multiplier);
+          let samples = hit_samples(&drumroll.samples);
+          let time = time_ms(drumroll.time);
+          writeln!(writer, "256,192,{},2,{},L|356:192,1,{},0|0,0:0|0:0,{}", time, hitsounds, length, samples)?;
+        }
+
+        TaikoHitObject::Swell(swell) => {
+          let end_ms =
+            nudge(swell.end_time().to_seconds() * 1000.0, |ms| Time::from_ms(ms) - swell.time == swell.duration);
+          let samples = hit_samples(&swell.samples);
+          writeln!(writer, "256,192,{},12,0,{},{}", time_ms(swell.time), end_ms, samples)?;
+        }
+      }
+    }
+
+    return Ok(());
+  }
+
+  /// Stored properties updated with the beatmap's fields.
+  fn written_properties(&self) -> Properties {
+    let mut properties = Properties::new();
+
+    // Known sections always come first and in the usual order
+    for section in ["[General]", "[Metadata]", "[Difficulty]"] {
+      properties.insert(section.to_owned(), IndexMap::new());
+    }
+
+    for (section, values) in &self.properties {
+      properties.entry(section.clone()).or_default().extend(values.clone());
+    }
+
+    let audio_path = self.audio_path.to_string_lossy().into_owned();
+    set_property(&mut properties, "[General]", "AudioFilename", audio_path);
+
+    // osu!standard objects are converted while parsing, so the written beatmap is always a taiko one
+    set_property(&mut properties, "[General]", "Mode", "1".to_owned());
+
+    set_property(&mut properties, "[Metadata]", "Title", self.title.clone());
+    set_property(&mut properties, "[Metadata]", "Artist", self.artist.clone());
+    set_property(&mut properties, "[Metadata]", "Creator", self.creator.clone());
+    set_property(&mut properties, "[Metadata]", "Version", self.variant.clone());
+
+    set_number_property(&mut properties, "[Difficulty]", "HPDrainRate", self.hp_drain_rate);
+    set_number_property(&mut properties, "[Difficulty]", "OverallDifficulty", self.overall_difficulty);
+    set_number_property(&mut properties, "[Difficulty]", "SliderMultiplier", self.velocity_multiplier);
+    set_number_property(&mut properties, "[Difficulty]", "SliderTickRate", self.slider_tick_rate);
+
+    return properties;
+  }
+
+  /// Writes timing and velocity points back as the timing point lines they were parsed from.
+  ///
+  /// Effect points without a velocity point at the same time (e.g. gogo time of TJA charts) get an inherited timing
+  /// point of their own which keeps the active velocity, parsing it adds a velocity point there.
+  fn write_timing_points(&self, writer: &mut impl Write) -> io::Result<()> {
+    let mut timing_points = self.timing_points.iter().collect::<Vec<_>>();
+    let mut effect_changes = self
+      .effect_points
+      .iter()
+      .filter(|effect_point| !self.velocity_points.iter().any(|p| p.time == effect_point.time))
+      .peekable();
+
+    for velocity_point in &self.velocity_points {
+      while let Some(effect_point) = effect_changes.next_if(|p| p.time < velocity_point.time) {
+        self.write_inherited_point(writer, effect_point.time)?;
+      }
+
+      // Every uninherited timing point comes with a velocity point of 1.0 at the same time. Timing points left over
+      // at the end were inserted by the parser and will be inserted again.
+      let uninherited = if velocity_point.velocity == 1.0 {
+        timing_points.iter().position(|p| p.time == velocity_point.time)
+      } else {
+        None
+      };
+
+      let Some(idx) = uninherited else {
+        self.write_inherited_point(writer, velocity_point.time)?;
+        continue;
+      };
+
+      let effect_point = self.effect_point_at(velocity_point.time);
+      let time = time_ms(velocity_point.time);
+      let sample_set = effect_point.sample_set.id();
+      let (sample_index, volume) = (effect_point.sample_index, effect_point.volume);
+
+      let timing_point = timing_points.remove(idx);
+      let bpm = timing_point.bpm;
+      let beat_length = nudge(60.0 * 1000.0 / bpm, |x| (60.0 * 1000.0) / x == bpm);
+      let effects = (effect_point.kiai as u8) | ((timing_point.omit_first_barline as u8) << 3);
+
+      // osu! only supports whole beats, fractional TJA measures are rounded
+      let meter = timing_point.meter.round().max(1.0);
+      writeln!(
+        writer,
+        "{},{},{},{},{},{},1,{}",
+        time, beat_length, meter, sample_set, sample_index, volume, effects
+      )?;
+    }
+
+    for effect_point in effect_changes {
+      self.write_inherited_point(writer, effect_point.time)?;
+    }
+
+    return Ok(());
+  }
+
+  /// Writes an inherited timing point with the velocity and effects active at `time`.
+  fn write_inherited_point(&self, writer: &mut impl Write, time: Time) -> io::Result<()> {
+    let default_velocity_point = VelocityPoint::default();
+    let velocity_point = active_point(&self.velocity_points, time, |p| p.time).unwrap_or(&default_velocity_point);
+    let effect_point = self.effect_point_at(time);
+
+    let time = time_ms(time);
+    let sample_set = effect_point.sample_set.id();
+    let (sample_index, volume) = (effect_point.sample_index, effect_point.volume);
+
+    let velocity = velocity_point.velocity;
+    let beat_length = nudge(-100.0 / velocity, |x| -100.0 / x == velocity);
+    let effects = effect_point.kiai as u8;
+    return writeln!(writer, "{},{},4,{},{},{},0,{}", time, beat_length, sample_set, sample_index, volume, effects);
+  }
+
+  /// Effect point active at `time`. Samples of the first effect point also apply before it, kiai does not.
+  fn effect_point_at(&self, time: Time) -> EffectPoint {
+    let mut effect_point = active_point(&self.effect_points, time, |p| p.time).cloned().unwrap_or_default();
+    effect_point.kiai &= effect_point.time <= time;
+    return effect_point;
+  }
+
+  /// Pixel length of a slider which converts back to the given drumroll.
+  fn slider_length(&self, drumroll: &TaikoDrumroll, slider_multiplier: f64) -> f64 {
+    let default_timing_point = TimingPoint::default();
+    let default_velocity_point = VelocityPoint::default();
+
+    let timing_point = active_point(&self.timing_points, drumroll.time, |p| p.time).unwrap_or(&default_timing_point);
+    let velocity_point =
+      active_point(&self.velocity_points, drumroll.time, |p| p.time).unwrap_or(&default_velocity_point);
+
+    let beat_length = 60.0 * 1000.0 / timing_point.bpm;
+    let velocity = velocity_point.velocity.clamp(0.1, 10.0);
+
+    // The converted duration is truncated, so aim for the middle of the millisecond
+    let duration = drumroll.duration.to_seconds() * 1000.0 + 0.5;
+    return duration * OSU_BASE_SCORING_DISTANCE * slider_multiplier * velocity / beat_length;
+  }
+}
@@ -1,5 +1,16 @@
+pub mod autoplay;
 pub mod beatmap;
 pub mod beatmap_cache;
+pub mod beatmap_collections;
+pub mod beatmap_import_error;
+pub mod beatmap_importer;
+pub mod beatmap_parse_error;
 pub mod beatmap_selector;
+pub mod beatmap_set;
+pub mod beatmap_writer;
+pub mod mods;
+pub mod replay_player;
+pub mod simulator;
 pub mod taiko_hit_object;
 pub mod taiko_player;
+pub mod tja;
@@ -0,0 +1,445 @@
+use std::fmt::{self, Display, Formatter};
+
+use apex_framework::graphics::color::Color;
+
+use super::{beatmap::Beatmap, taiko_hit_object::TaikoHitObject};
+
+/// Groups of mods, in the order they are listed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModKind {
+  DifficultyReduction,
+  DifficultyIncrease,
+  Conversion,
+}
+
+impl ModKind {
+  pub const ALL: [ModKind; 3] = [
+    ModKind::DifficultyReduction,
+    ModKind::DifficultyIncrease,
+    ModKind::Conversion,
+  ];
+
+  pub fn name(&self) -> &'static str {
+    return match self {
+      ModKind::DifficultyReduction => "Difficulty Reduction",
+      ModKind::DifficultyIncrease => "Difficulty Increase",
+      ModKind::Conversion => "Conversion",
+    };
+  }
+
+  pub fn color(&self) -> Color {
+    #[rustfmt::skip] return match self {
+      ModKind::DifficultyReduction => Color::new(0.400, 0.800, 0.300, 1.0),
+      ModKind::DifficultyIncrease  => Color::new(0.900, 0.300, 0.300, 1.0),
+      ModKind::Conversion          => Color::new(0.550, 0.400, 0.850, 1.0),
+    };
+  }
+}
+
+/// A single gameplay mod, the value is its bit in [`Mods`].
+///
+/// Mods which exist in osu!stable use the same bits, see [`Mods::legacy_bits`] for how they are stored in replays.
+#[repr(u64)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mod {
+  NoFail = 1 << 0,
+  Easy = 1 << 1,
+  Hidden = 1 << 3,
+  HardRock = 1 << 4,
+  SuddenDeath = 1 << 5,
+  DoubleTime = 1 << 6,
+  HalfTime = 1 << 8,
+  Nightcore = 1 << 9,
+  Flashlight = 1 << 10,
+  Perfect = 1 << 14,
+
+  /// Recolours every note, the pattern is decided by [`Mods::seed`]. osu!stable has no seed, so its replays with Random
+  /// can't be reproduced.
+  Random = 1 << 21,
+
+  /// Half Time with lowered pitch, osu!stable doesn't have it and stores it as Half Time.
+  Daycore = 1 << 31,
+
+  /// Custom difficulty settings, see [`DifficultyAdjust`]. osu!stable doesn't have it either.
+  DifficultyAdjust = 1 << 32,
+
+  /// Turns every don into a kat and the other way around. osu!stable doesn't have it.
+  Swap = 1 << 33,
+}
+
+impl Mod {
+  pub const ALL: [Mod; 14] = [
+    Mod::Easy,
+    Mod::NoFail,
+    Mod::HardRock,
+    Mod::SuddenDeath,
+    Mod::Perfect,
+    Mod::DoubleTime,
+    Mod::Nightcore,
+    Mod::HalfTime,
+    Mod::Daycore,
+    Mod::Hidden,
+    Mod::Flashlight,
+    Mod::DifficultyAdjust,
+    Mod::Random,
+    Mod::Swap,
+  ];
+
+  pub fn bit(&self) -> u64 {
+    return *self as u64;
+  }
+
+  pub fn acronym(&self) -> &'static str {
+    return match self {
+      Mod::DoubleTime => "DT",
+      Mod::HalfTime => "HT",
+      Mod::Nightcore => "NC",
+      Mod::Daycore => "DC",
+      Mod::Hidden => "HD",
+      Mod::Flashlight => "FL",
+      Mod::Easy => "EZ",
+      Mod::HardRock => "HR",
+      Mod::DifficultyAdjust => "DA",
+      Mod::NoFail => "NF",
+      Mod::SuddenDeath => "SD",
+      Mod::Perfect => "PF",
+      Mod::Random => "RD",
+      Mod::Swap => "SW",
+    };
+  }
+
+  pub fn kind(&self) -> ModKind {
+    return match self {
+      Mod::Easy | Mod::NoFail | Mod::HalfTime | Mod::Daycore => ModKind::DifficultyReduction,
+      Mod::HardRock
+      | Mod::SuddenDeath
+      | Mod::Perfect
+      | Mod::DoubleTime
+      | Mod::Nightcore
+      | Mod::Hidden
+      | Mod::Flashlight => ModKind::DifficultyIncrease,
+
+      Mod::DifficultyAdjust | Mod::Random | Mod::Swap => ModKind::Conversion,
+    };
+  }
+
+  pub fn name(&self) -> &'static str {
+    return match self {
+      Mod::DoubleTime => "Double Time",
+      Mod::HalfTime => "Half Time",
+      Mod::Nightcore => "Nightcore",
+      Mod::Daycore => "Daycore",
+      Mod::Hidden => "Hidden",
+      Mod::Flashlight => "Flashlight",
+      Mod::Easy => "Easy",
+      Mod::HardRock => "Hard Rock",
+      Mod::DifficultyAdjust => "Difficulty Adjust",
+      Mod::NoFail => "No Fail",
+      Mod::SuddenDeath => "Sudden Death",
+      Mod::Perfect => "Perfect",
+      Mod::Random => "Random",
+      Mod::Swap => "Swap",
+    };
+  }
+
+  pub fn description(&self) -> &'static str {
+    return match self {
+      Mod::DoubleTime => "Plays 1.5x as fast.",
+      Mod::HalfTime => "Plays at 0.75x speed.",
+      Mod::Nightcore => "Double Time with a higher pitch.",
+      Mod::Daycore => "Half Time with a lower pitch.",
+      Mod::Hidden => "Notes fade out before reaching the hit position.",
+      Mod::Flashlight => "Only notes close to the hit position are visible, the area shrinks with combo.",
+      Mod::Easy => "Larger timing windows, less health drain, slower notes and two extra lives.",
+      Mod::HardRock => "Tighter timing windows, more health drain and faster notes.",
+      Mod::DifficultyAdjust => "Pick your own overall difficulty, health drain and scroll speed.",
+      Mod::NoFail => "You can't fail, no matter how much health drains.",
+      Mod::SuddenDeath => "Miss a note and fail.",
+      Mod::Perfect => "Anything less than a great hit fails.",
+      Mod::Random => "Notes are coloured randomly, a new pattern every play.",
+      Mod::Swap => "Dons become kats and kats become dons.",
+    };
+  }
+
+  /// Mods which can't be active along with this one.
+  pub fn incompatible(&self) -> &'static [Mod] {
+    const RATE: &[Mod] = &[Mod::DoubleTime, Mod::HalfTime, Mod::Nightcore, Mod::Daycore];
+    const DIFFICULTY: &[Mod] = &[Mod::Easy, Mod::HardRock, Mod::DifficultyAdjust];
+    const FAIL: &[Mod] = &[Mod::NoFail, Mod::SuddenDeath, Mod::Perfect];
+    const PATTERN: &[Mod] = &[Mod::Random, Mod::Swap];
+
+    return match self {
+      Mod::DoubleTime | Mod::HalfTime | Mod::Nightcore | Mod::Daycore => RATE,
+      Mod::Easy | Mod::HardRock | Mod::DifficultyAdjust => DIFFICULTY,
+      Mod::NoFail | Mod::SuddenDeath | Mod::Perfect => FAIL,
+      Mod::Random | Mod::Swap => PATTERN,
+      Mod::Hidden | Mod::Flashlight => &[],
+    };
+  }
+}
+
+/// Settings of the Difficulty Adjust mod, values which are `None` are taken from the beatmap.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DifficultyAdjust {
+  pub overall_difficulty: Option<f32>,
+  pub hp_drain_rate: Option<f32>,
+
+  /// Multiplier of the scroll speed of the beatmap.
+  pub scroll_speed: f32,
+}
+
+impl Default for DifficultyAdjust {
+  fn default() -> Self {
+    return Self {
+      overall_difficulty: None,
+      hp_drain_rate: None,
+      scroll_speed: 1.0,
+    };
+  }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Mods {
+  bits: u64,
+
+  /// Only applies while [`Mod::DifficultyAdjust`] is active, but is kept around so the settings aren't lost when it is
+  /// switched off and on again.
+  difficulty_adjust: DifficultyAdjust,
+
+  /// Decides the colours of [`Mod::Random`], stored with the score so its replay is played on the same pattern.
+  seed: u32,
+}
+
+impl Mods {
+  pub fn from_bits(bits: u64) -> Self {
+    let bits = Mod::ALL.iter().map(Mod::bit).filter(|x| bits & x != 0).fold(0, |acc, x| acc | x);
+    return Self {
+      bits,
+      difficulty_adjust: DifficultyAdjust::default(),
+      seed: 0,
+    };
+  }
+
+  pub fn bits(&self) -> u64 {
+    return self.bits;
+  }
+
+  /// Reads mods of an osu!stable score, which stores Nightcore along with Double Time and Perfect along with Sudden
+  /// Death.
+  pub fn from_legacy_bits(bits: u32) -> Self {
+    let mut mods = Self::from_bits(bits as u64 & !Mod::Daycore.bit());
+
+    if mods.contains(Mod::Nightcore) {
+      mods.remove(Mod::DoubleTime);
+    }
+
+    if mods.contains(Mod::Perfect) {
+      mods.remove(Mod::SuddenDeath);
+    }
+
+    return mods;
+  }
+
+  /// Bits as osu!stable stores them, for exported replays and `rosu_pp`.
+  pub fn legacy_bits(&self) -> u32 {
+    let mut bits = (self.bits & !(Mod::Daycore.bit() | Mod::DifficultyAdjust.bit() | Mod::Swap.bit())) as u32;
+
+    if self.contains(Mod::Nightcore) {
+      bits |= Mod::DoubleTime.bit() as u32;
+    }
+
+    if self.contains(Mod::Perfect) {
+      bits |= Mod::SuddenDeath.bit() as u32;
+    }
+
+    if self.contains(Mod::Daycore) {
+      bits |= Mod::HalfTime.bit() as u32;
+    }
+
+    return bits;
+  }
+
+  pub fn contains(&self, value: Mod) -> bool {
+    return self.bits & value.bit() != 0;
+  }
+
+  pub fn is_empty(&self) -> bool {
+    return self.bits == 0;
+  }
+
+  pub fn insert(&mut self, value: Mod) {
+    for other in value.incompatible() {
+      self.remove(*other);
+    }
+
+    self.bits |= value.bit();
+  }
+
+  pub fn remove(&mut self, value: Mod) {
+    self.bits &= !value.bit();
+  }
+
+  /// Switches `value` on or off, switching it on turns off every incompatible mod.
+  pub fn toggle(&mut self, value: Mod) {
+    if self.contains(value) {
+      self.remove(value);
+    } else {
+      self.insert(value);
+    }
+  }
+
+  pub fn iter(&self) -> impl DoubleEndedIterator<Item = Mod> + '_ {
+    return Mod::ALL.into_iter().filter(|x| self.contains(*x));
+  }
+
+  pub fn seed(&self) -> u32 {
+    return self.seed;
+  }
+
+  pub fn set_seed(&mut self, seed: u32) {
+    self.seed = seed;
+  }
+
+  pub fn difficulty_adjust(&self) -> &DifficultyAdjust {
+    return &self.difficulty_adjust;
+  }
+
+  pub fn difficulty_adjust_mut(&mut self) -> &mut DifficultyAdjust {
+    return &mut self.difficulty_adjust;
+  }
+
+  /// Playback rate of the beatmap, `1.0` is realtime.
+  pub fn rate(&self) -> f64 {
+    if self.contains(Mod::DoubleTime) || self.contains(Mod::Nightcore) {
+      return 1.5;
+    }
+
+    if self.contains(Mod::HalfTime) || self.contains(Mod::Daycore) {
+      return 0.75;
+    }
+
+    return 1.0;
+  }
+
+  /// Whether the audio is time-stretched at [`Mods::rate`], Nightcore and Daycore change the pitch along with it.
+  pub fn preserves_pitch(&self) -> bool {
+    return !self.contains(Mod::Nightcore) && !self.contains(Mod::Daycore);
+  }
+
+  /// Overall difficulty of a beatmap with `value` as its own, which decides the hit windows.
+  pub fn overall_difficulty(&self, value: f32) -> f32 {
+    return self.adjust_difficulty(value, self.difficulty_adjust.overall_difficulty);
+  }
+
+  /// HP drain rate of a beatmap with `value` as its own.
+  pub fn hp_drain_rate(&self, value: f32) -> f32 {
+    return self.adjust_difficulty(value, self.difficulty_adjust.hp_drain_rate);
+  }
+
+  /// Multiplier of the scroll speed.
+  pub fn scroll_speed(&self) -> f32 {
+    if self.contains(Mod::HardRock) {
+      return 1.4;
+    }
+
+    if self.contains(Mod::Easy) {
+      return 0.8;
+    }
+
+    if self.contains(Mod::DifficultyAdjust) {
+      return self.difficulty_adjust.scroll_speed;
+    }
+
+    return 1.0;
+  }
+
+  /// How many times the health bar is refilled instead of failing.
+  pub fn extra_lives(&self) -> usize {
+    return if self.contains(Mod::Easy) { 2 } else { 0 };
+  }
+
+  /// Changes the difficulty settings and note colours of `beatmap` to the ones it is played with. Scores are still
+  /// stored against the unmodified beatmap, so its hash is kept as it was.
+  pub fn apply(&self, beatmap: &mut Beatmap) {
+    beatmap.hash = Some(beatmap.hash());
+
+    beatmap.overall_difficulty = self.overall_difficulty(beatmap.overall_difficulty);
+    beatmap.hp_drain_rate = self.hp_drain_rate(beatmap.hp_drain_rate);
+    beatmap.velocity_multiplier *= self.scroll_speed();
+
+    let hits = beatmap.hit_objects.iter_mut().filter_map(|x| {
+      match x {
+        TaikoHitObject::Hit(hit) => Some(hit),
+        _ => None,
+      }
+    });
+
+    if self.contains(Mod::Random) {
+      let mut rng = SplitMix64(self.seed as u64);
+      for hit in hits {
+        if rng.next() & 1 == 1 {
+          hit.color.toggle();
+        }
+      }
+    } else if self.contains(Mod::Swap) {
+      for hit in hits {
+        hit.color.toggle();
+      }
+    }
+  }
+
+  fn adjust_difficulty(&self, value: f32, custom: Option<f32>) -> f32 {
+    if self.contains(Mod::HardRock) {
+      return (value * 1.4).min(10.0);
+    }
+
+    if self.contains(Mod::Easy) {
+      return value * 0.5;
+    }
+
+    if self.contains(Mod::DifficultyAdjust) {
+      return custom.unwrap_or(value);
+    }
+
+    return value;
+  }
+}
+
+/// Tiny generator for [`Mod::Random`], the patterns have to stay the same across versions and platforms for replays.
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+  fn next(&mut self) -> u64 {
+    self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+    let mut z = self.0;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    return z ^ (z >> 31);
+  }
+}
+
+impl FromIterator<Mod> for Mods {
+  fn from_iter<T: IntoIterator<Item = Mod>>(iter: T) -> Self {
+    let mut mods = Mods::default();
+    for value in iter {
+      mods.insert(value);
+    }
+
+    return mods;
+  }
+}
+
+impl Display for Mods {
+  fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+    if self.is_empty() {
+      return write!(f, "NM");
+    }
+
+    for value in self.iter() {
+      write!(f, "{}", value.acronym())?;
+    }
+
+    return Ok(());
+  }
+}
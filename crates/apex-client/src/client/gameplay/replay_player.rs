@@ -0,0 +1,52 @@
+use apex_framework::time::time::Time;
+
+use crate::client::score::score::Score;
+
+use super::{autoplay::autoplay_hits, beatmap::Beatmap, taiko_player::TaikoInput};
+
+/// Feeds recorded or generated inputs into gameplay, in the order they were made.
+pub struct ReplayPlayer {
+  hits: Vec<(Time, TaikoInput)>,
+  next_hit: usize,
+
+  /// Score the inputs were recorded for, missing for autoplay.
+  score: Option<Score>,
+}
+
+impl ReplayPlayer {
+  pub fn new(score: Score) -> Self {
+    return Self {
+      hits: score.hits.clone(),
+      next_hit: 0,
+      score: Some(score),
+    };
+  }
+
+  pub fn autoplay(beatmap: &Beatmap) -> Self {
+    return Self {
+      hits: autoplay_hits(beatmap),
+      next_hit: 0,
+      score: None,
+    };
+  }
+
+  pub fn score(&self) -> Option<&Score> {
+    return self.score.as_ref();
+  }
+
+  /// Returns the next input made at or before `time`, call this in a loop to catch up with `time`.
+  pub fn next_hit(&mut self, time: Time) -> Option<(Time, TaikoInput)> {
+    let hit = self.hits.get(self.next_hit).copied()?;
+    if hit.0 > time {
+      return None;
+    }
+
+    self.next_hit += 1;
+    return Some(hit);
+  }
+
+  /// Starts over from the first input, the judgements have to be rebuilt from there when seeking.
+  pub fn rewind(&mut self) {
+    self.next_hit = 0;
+  }
+}
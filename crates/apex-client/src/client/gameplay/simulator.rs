@@ -0,0 +1,128 @@
+use apex_framework::time::time::Time;
+use jiff::Timestamp;
+
+use crate::client::score::{judgement_processor::Judgement, score::Score, score_processor::ScoreProcessor};
+
+use super::{
+  beatmap::Beatmap,
+  mods::Mods,
+  taiko_player::{HitTarget, TaikoInput, TaikoPlayer},
+};
+
+/// Ruleset settings a play is simulated with.
+#[derive(Debug, Clone, Default)]
+pub struct SimulatorConfig {
+  /// Overrides the overall difficulty of the beatmap, which decides the hit windows.
+  pub overall_difficulty: Option<f32>,
+
+  /// Mods the play is judged with, and which are recorded on the resulting score.
+  pub mods: Mods,
+}
+
+/// Judgement of a circle, drumroll tick or swell hit.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObjectJudgement {
+  /// Index of the hit object in the beatmap.
+  pub object: usize,
+  pub judgement: Judgement,
+  pub time: Time,
+
+  /// Offset of the input from the object, missing for circles which were never hit.
+  pub hit_delta: Option<Time>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+  pub score: Score,
+  pub judgements: Vec<ObjectJudgement>,
+}
+
+/// Judges a play from the beatmap and timed inputs alone, without any graphics or audio.
+pub struct Simulator {
+  taiko_player: TaikoPlayer,
+  score_processor: ScoreProcessor,
+  judgements: Vec<ObjectJudgement>,
+}
+
+impl Simulator {
+  pub fn new(mut beatmap: Beatmap, config: &SimulatorConfig) -> Self {
+    if let Some(overall_difficulty) = config.overall_difficulty {
+      beatmap.overall_difficulty = overall_difficulty;
+    }
+
+    config.mods.apply(&mut beatmap);
+
+    let mut taiko_player = TaikoPlayer::new();
+    taiko_player.play(beatmap);
+
+    return Self {
+      taiko_player,
+      score_processor: ScoreProcessor::new(config.mods),
+      judgements: Vec::new(),
+    };
+  }
+
+  /// Judges an input made at `time`, after counting every circle which can't be hit anymore by then as a miss. Inputs
+  /// have to be fed in chronological order.
+  pub fn hit(&mut self, time: Time, input: TaikoInput) -> Option<ObjectJudgement> {
+    self.process_misses(time);
+
+    let (result, target) = self.taiko_player.hit(time, input)?;
+    self.score_processor.feed(time, Some(input), result.judgement);
+
+    let object = match target {
+      HitTarget::Object(object) | HitTarget::DrumrollTick { object, .. } | HitTarget::Swell { object, .. } => object,
+    };
+
+    let judgement = ObjectJudgement {
+      object,
+      judgement: result.judgement,
+      time,
+      hit_delta: Some(result.hit_delta),
+    };
+
+    self.judgements.push(judgement);
+    return Some(judgement);
+  }
+
+  /// Counts every circle which can't be hit anymore at `time` as a miss.
+  pub fn process_misses(&mut self, time: Time) {
+    while let Some(object) = self.taiko_player.process_miss(time) {
+      // Judged when the hit window closed, no matter how late this was called
+      let time = self.taiko_player.beatmap().hit_objects[object].time() + self.taiko_player.hit_window_150();
+      self.score_processor.feed(time, None, Judgement::Miss);
+
+      self.judgements.push(ObjectJudgement {
+        object,
+        judgement: Judgement::Miss,
+        time,
+        hit_delta: None,
+      });
+    }
+  }
+
+  /// Judgements made so far, in the order they were made.
+  pub fn judgements(&self) -> &[ObjectJudgement] {
+    return &self.judgements;
+  }
+
+  /// Counts everything which is left as a miss and returns the final score of the play.
+  pub fn finish(mut self, date: Timestamp, username: String) -> SimulationResult {
+    self.process_misses(Time::from_seconds(f64::INFINITY));
+
+    return SimulationResult {
+      score: self.score_processor.export(date, username),
+      judgements: self.judgements,
+    };
+  }
+}
+
+/// Simulates a whole play of the chronologically ordered `inputs`, set now by the default player.
+pub fn simulate(beatmap: Beatmap, config: &SimulatorConfig, inputs: &[(Time, TaikoInput)]) -> SimulationResult {
+  let mut simulator = Simulator::new(beatmap, config);
+  for (time, input) in inputs.iter().copied() {
+    simulator.hit(time, input);
+  }
+
+  return simulator.finish(Timestamp::now(), String::from(Score::DEFAULT_USERNAME));
+}
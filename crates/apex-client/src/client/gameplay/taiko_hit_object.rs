@@ -1,11 +1,167 @@
 use apex_framework::time::time::Time;
 
+use super::beatmap::{EffectPoint, SampleSet};
+
+#[derive(Clone, Debug)]
+pub enum TaikoHitObject {
+  /// Regular don or kat circle.
+  Hit(TaikoHit),
+
+  /// Yellow roll which can be hit any amount of times for bonus score.
+  Drumroll(TaikoDrumroll),
+
+  /// Big circle which stays at the hit position until it's hit enough times by alternating don and kat.
+  Swell(TaikoSwell),
+}
+
+impl Default for TaikoHitObject {
+  fn default() -> Self {
+    return TaikoHitObject::Hit(TaikoHit::default());
+  }
+}
+
+impl TaikoHitObject {
+  pub fn time(&self) -> Time {
+    return match self {
+      TaikoHitObject::Hit(hit) => hit.time,
+      TaikoHitObject::Drumroll(drumroll) => drumroll.time,
+      TaikoHitObject::Swell(swell) => swell.time,
+    };
+  }
+
+  pub fn end_time(&self) -> Time {
+    return match self {
+      TaikoHitObject::Hit(hit) => hit.time,
+      TaikoHitObject::Drumroll(drumroll) => drumroll.end_time(),
+      TaikoHitObject::Swell(swell) => swell.end_time(),
+    };
+  }
+
+  pub fn samples(&self) -> &HitSamples {
+    return match self {
+      TaikoHitObject::Hit(hit) => &hit.samples,
+      TaikoHitObject::Drumroll(drumroll) => &drumroll.samples,
+      TaikoHitObject::Swell(swell) => &swell.samples,
+    };
+  }
+
+  pub fn samples_mut(&mut self) -> &mut HitSamples {
+    return match self {
+      TaikoHitObject::Hit(hit) => &mut hit.samples,
+      TaikoHitObject::Drumroll(drumroll) => &mut drumroll.samples,
+      TaikoHitObject::Swell(swell) => &mut swell.samples,
+    };
+  }
+
+  pub fn is_big(&self) -> bool {
+    return match self {
+      TaikoHitObject::Hit(hit) => hit.big,
+      TaikoHitObject::Drumroll(drumroll) => drumroll.big,
+      TaikoHitObject::Swell(_) => true,
+    };
+  }
+}
+
+#[rustfmt::skip]
+#[derive(Clone, Debug, Default)]
+pub struct TaikoHit {
+  pub time    : Time,
+  pub color   : TaikoColor,
+  pub big     : bool,
+  pub samples : HitSamples,
+}
+
 #[rustfmt::skip]
-#[derive(Clone, Default)]
-pub struct TaikoHitObject {
-  pub time  : Time,
-  pub color : TaikoColor,
-  pub big   : bool,
+#[derive(Clone, Debug, Default)]
+pub struct TaikoDrumroll {
+  pub time         : Time,
+  pub duration     : Time,
+  pub tick_spacing : Time,
+  pub tick_count   : usize,
+  pub big          : bool,
+  pub samples      : HitSamples,
+}
+
+impl TaikoDrumroll {
+  pub fn end_time(&self) -> Time {
+    return self.time + self.duration;
+  }
+
+  pub fn tick_time(&self, idx: usize) -> Time {
+    return self.time + self.tick_spacing * idx as f64;
+  }
+
+  /// Ticks can be hit half a tick spacing before or after their time.
+  pub fn tick_window(&self) -> Time {
+    return self.tick_spacing / 2.0;
+  }
+}
+
+#[rustfmt::skip]
+#[derive(Clone, Debug, Default)]
+pub struct TaikoSwell {
+  pub time          : Time,
+  pub duration      : Time,
+  pub required_hits : usize,
+  pub samples       : HitSamples,
+}
+
+impl TaikoSwell {
+  pub fn end_time(&self) -> Time {
+    return self.time + self.duration;
+  }
+}
+
+/// Sample sets, custom sample index and volume of the sounds played when hitting an object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HitSamples {
+  /// Sample set of the normal sound, played for don.
+  pub sample_set: SampleSet,
+
+  /// Sample set of the additions, played for kat and finishers.
+  pub addition_set: SampleSet,
+
+  /// Custom sample index, 0 uses the samples of the skin instead of the beatmap's.
+  pub index: u32,
+
+  /// Volume in percent.
+  pub volume: u8,
+}
+
+impl Default for HitSamples {
+  fn default() -> Self {
+    return Self {
+      sample_set: SampleSet::Normal,
+      addition_set: SampleSet::Normal,
+      index: 0,
+      volume: 100,
+    };
+  }
+}
+
+impl HitSamples {
+  /// Fills in everything the hit object left to the timing point, `Auto` sample sets and zeroes mean inherited.
+  pub fn inherit(&mut self, effect_point: &EffectPoint) {
+    if self.sample_set == SampleSet::Auto {
+      self.sample_set = effect_point.sample_set;
+    }
+
+    if self.sample_set == SampleSet::Auto {
+      self.sample_set = SampleSet::Normal;
+    }
+
+    if self.addition_set == SampleSet::Auto {
+      self.addition_set = self.sample_set;
+    }
+
+    if self.index == 0 {
+      self.index = effect_point.sample_index;
+    }
+
+    if self.volume == 0 {
+      self.volume = effect_point.volume;
+    }
+  }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
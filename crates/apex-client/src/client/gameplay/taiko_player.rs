@@ -2,10 +2,13 @@ use apex_framework::time::{clock::AbstractClock as _, time::Time};
 
 use crate::client::{
   audio::game_audio::GameAudio,
-  score::judgement_processor::{check_hit, HitResult},
+  score::judgement_processor::{check_hit, HitResult, Judgement},
 };
 
-use super::beatmap::{calc_hit_window_150, calc_hit_window_300, Beatmap, BreakPoint};
+use super::{
+  beatmap::{calc_hit_window_150, calc_hit_window_300, Beatmap, BreakPoint},
+  taiko_hit_object::{TaikoColor, TaikoHitObject, TaikoSwell},
+};
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum TaikoInput {
@@ -15,6 +18,15 @@ pub enum TaikoInput {
   KatRight = 3,
 }
 
+impl TaikoInput {
+  pub fn color(&self) -> TaikoColor {
+    return match self {
+      TaikoInput::DonRight | TaikoInput::DonLeft => TaikoColor::Don,
+      TaikoInput::KatLeft | TaikoInput::KatRight => TaikoColor::Kat,
+    };
+  }
+}
+
 impl TryFrom<u8> for TaikoInput {
   type Error = ();
 
@@ -41,6 +53,32 @@ pub enum BreakState {
   Intro(BreakPoint),
 }
 
+/// What a successful hit has landed on.
+#[derive(Debug, Clone, Copy)]
+pub enum HitTarget {
+  /// A regular circle, identified by its index in the beatmap.
+  Object(usize),
+
+  /// A single tick of the drumroll at the given index.
+  DrumrollTick { object: usize, tick: usize },
+
+  /// The swell at the given index, completed once no hits remain.
+  Swell { object: usize, remaining: usize },
+}
+
+/// Time after which a bonus object can't be hit anymore, `None` for regular circles.
+fn bonus_end_time(hit_object: &TaikoHitObject) -> Option<Time> {
+  return match hit_object {
+    TaikoHitObject::Hit(_) => None,
+    TaikoHitObject::Drumroll(drumroll) => Some(drumroll.end_time() + drumroll.tick_window()),
+    TaikoHitObject::Swell(swell) => Some(swell.end_time()),
+  };
+}
+
+/// Time after hitting a big circle in which the other key of the same color still belongs to it, so hitting big circles
+/// with both keys doesn't hit the next object as well.
+const STRONG_HIT_WINDOW_MS: f64 = 30.0;
+
 /// Logcial actions that a player can perform while playing taiko.
 pub struct TaikoPlayer {
   beatmap: Beatmap,
@@ -51,6 +89,13 @@ pub struct TaikoPlayer {
 
   current_circle: usize,
   current_break_point: usize,
+
+  /// Drumroll tick or swell hit count of the current hit object.
+  current_tick: usize,
+  last_swell_color: Option<TaikoColor>,
+
+  /// Time and color of the big circle hit last, while its second key can still follow.
+  last_big_hit: Option<(Time, TaikoColor)>,
 }
 
 impl TaikoPlayer {
@@ -61,6 +106,9 @@ impl TaikoPlayer {
       hit_window_300: Time::zero(),
       current_circle: 0,
       current_break_point: 0,
+      current_tick: 0,
+      last_swell_color: None,
+      last_big_hit: None,
     };
   }
 
@@ -75,6 +123,9 @@ impl TaikoPlayer {
   pub fn reset(&mut self) {
     self.current_circle = 0;
     self.current_break_point = 0;
+    self.current_tick = 0;
+    self.last_swell_color = None;
+    self.last_big_hit = None;
   }
 
   pub fn beatmap(&self) -> &Beatmap {
@@ -85,39 +136,162 @@ impl TaikoPlayer {
     return time >= audio.length() + audio.lead_out;
   }
 
-  /// You should call this method in a loop until it returns `false`. Returns `true` if a miss has occured.
-  pub fn process_miss(&mut self, time: Time) -> bool {
+  /// You should call this method in a loop until it returns `None`. Returns the index of the circle which was missed.
+  pub fn process_miss(&mut self, time: Time) -> Option<usize> {
     // Skip unhit (if any) until we find the next hit object that can be hit.
-    if let Some(hit_object) = self.beatmap.hit_objects.get(self.current_circle) {
-      let hit_window_end_time = hit_object.time + self.hit_window_150;
+    while let Some(hit_object) = self.beatmap.hit_objects.get(self.current_circle) {
+      match hit_object {
+        TaikoHitObject::Hit(hit) => {
+          let hit_window_end_time = hit.time + self.hit_window_150;
 
-      if hit_window_end_time >= time {
-        return false;
-      }
+          if hit_window_end_time >= time {
+            return None;
+          }
 
-      // Unhit hit object which can not be hit anymore counts as a miss.
-      self.current_circle += 1;
+          // Unhit hit object which can not be hit anymore counts as a miss.
+          let missed = self.current_circle;
+          self.next_object();
 
-      return true;
+          return Some(missed);
+        }
+
+        TaikoHitObject::Drumroll(_) | TaikoHitObject::Swell(_) => {
+          if bonus_end_time(hit_object).is_some_and(|end_time| end_time >= time) {
+            return None;
+          }
+
+          // Drumrolls and swells are purely bonus, so they never count as a miss.
+          self.next_object();
+        }
+      }
     }
 
-    return false;
+    return None;
   }
 
-  pub fn hit(&mut self, time: Time, input: TaikoInput) -> Option<(HitResult, usize)> {
-    if let Some(obj) = self.beatmap.hit_objects.get(self.current_circle) {
-      if let Some(result) = check_hit(time, obj, input, self.hit_window_150, self.hit_window_300) {
-        let hit_idx = self.current_circle;
+  pub fn hit(&mut self, time: Time, input: TaikoInput) -> Option<(HitResult, HitTarget)> {
+    if let Some((big_time, color)) = self.last_big_hit.take() {
+      if input.color() == color && time - big_time <= Time::from_ms(STRONG_HIT_WINDOW_MS) {
+        return None;
+      }
+    }
+
+    // Drumrolls and swells which are over can't be hit anymore, move on to the next object.
+    while let Some(end_time) = self.beatmap.hit_objects.get(self.current_circle).and_then(bonus_end_time) {
+      if end_time >= time {
+        break;
+      }
+
+      self.next_object();
+    }
+
+    match self.beatmap.hit_objects.get(self.current_circle)? {
+      TaikoHitObject::Hit(obj) => {
+        if let Some(result) = check_hit(time, obj, input, self.hit_window_150, self.hit_window_300) {
+          let hit_idx = self.current_circle;
+
+          if obj.big && result.judgement != Judgement::Miss {
+            self.last_big_hit = Some((time, obj.color));
+          }
 
-        self.current_circle += 1;
+          self.next_object();
 
-        return Some((result, hit_idx));
+          return Some((result, HitTarget::Object(hit_idx)));
+        }
+      }
+
+      TaikoHitObject::Drumroll(drumroll) => {
+        let tick_window = drumroll.tick_window();
+
+        // Skip ticks which are too far behind to be hit.
+        while self.current_tick < drumroll.tick_count && drumroll.tick_time(self.current_tick) + tick_window < time {
+          self.current_tick += 1;
+        }
+
+        if self.current_tick < drumroll.tick_count {
+          let hit_delta = time - drumroll.tick_time(self.current_tick);
+
+          if hit_delta.abs() <= tick_window {
+            let target = HitTarget::DrumrollTick {
+              object: self.current_circle,
+              tick: self.current_tick,
+            };
+            self.current_tick += 1;
+
+            return Some((
+              HitResult {
+                judgement: Judgement::DrumrollTick,
+                hit_delta,
+              },
+              target,
+            ));
+          }
+        }
+
+        // Past its end the drumroll only holds on to its last ticks, everything else belongs to the next object.
+        if time > drumroll.end_time() {
+          self.next_object();
+
+          return self.hit(time, input);
+        }
+      }
+
+      TaikoHitObject::Swell(swell) => {
+        if time < swell.time {
+          return None;
+        }
+
+        // Swells have to be hit by alternating between don and kat.
+        let color = input.color();
+        if self.last_swell_color == Some(color) {
+          return None;
+        }
+
+        self.last_swell_color = Some(color);
+        self.current_tick += 1;
+
+        let hit_delta = time - swell.time;
+        let object = self.current_circle;
+        let remaining = swell.required_hits.saturating_sub(self.current_tick);
+
+        if remaining == 0 {
+          self.next_object();
+
+          return Some((HitResult { judgement: Judgement::Swell, hit_delta }, HitTarget::Swell { object, remaining }));
+        }
+
+        return Some((
+          HitResult {
+            judgement: Judgement::SwellTick,
+            hit_delta,
+          },
+          HitTarget::Swell { object, remaining },
+        ));
       }
     }
 
     return None;
   }
 
+  /// Returns the swell which is currently being hit along with the amount of hits left to complete it.
+  pub fn active_swell(&self, time: Time) -> Option<(&TaikoSwell, usize)> {
+    let Some(TaikoHitObject::Swell(swell)) = self.beatmap.hit_objects.get(self.current_circle) else {
+      return None;
+    };
+
+    if time < swell.time || time > swell.end_time() {
+      return None;
+    }
+
+    return Some((swell, swell.required_hits.saturating_sub(self.current_tick)));
+  }
+
+  fn next_object(&mut self) {
+    self.current_circle += 1;
+    self.current_tick = 0;
+    self.last_swell_color = None;
+  }
+
   pub fn skip_break(&mut self, audio: &mut GameAudio, break_leniency_end: Time) {
     let time = audio.position();
     match self.is_break(time, break_leniency_end) {
@@ -142,8 +316,8 @@ impl TaikoPlayer {
       return BreakState::None;
     };
 
-    if time < obj.time - break_leniency_end && obj.time > Time::from_seconds(10.0) {
-      return BreakState::Intro(BreakPoint { start: Time::zero(), end: obj.time });
+    if time < obj.time() - break_leniency_end && obj.time() > Time::from_seconds(10.0) {
+      return BreakState::Intro(BreakPoint { start: Time::zero(), end: obj.time() });
     } else {
       for break_point in &self.beatmap.break_points[self.current_break_point ..] {
         if time >= break_point.end {
@@ -0,0 +1,543 @@
+use std::path::{Path, PathBuf};
+
+use apex_framework::time::time::Time;
+use indexmap::IndexMap;
+use log::warn;
+
+use super::{
+  beatmap::{calc_swell_required_hits, Beatmap, EffectPoint, TimingPoint, VelocityPoint},
+  beatmap_parse_error::{BeatmapParseError, ParseReporter},
+  taiko_hit_object::{HitSamples, TaikoColor, TaikoDrumroll, TaikoHit, TaikoHitObject, TaikoSwell},
+};
+
+/// Slider multiplier which gives TJA charts roughly the same scroll speed as in the arcade games.
+const TJA_VELOCITY_MULTIPLIER: f32 = 1.4;
+
+/// Header values which apply to every course following them.
+#[derive(Default, Clone)]
+struct TjaHeader {
+  title: Option<String>,
+  subtitle: String,
+  maker: String,
+  wave: Option<String>,
+  bg: String,
+  bpm: f64,
+  offset: f64,
+  demo_start: f64,
+
+  course: String,
+  balloons: Vec<usize>,
+}
+
+/// Drumroll or balloon which is waiting for its end note.
+enum OpenRoll {
+  Drumroll { time: f64, beat_length: f64, big: bool },
+  Balloon { time: f64 },
+}
+
+/// Converts the value of a `COURSE:` header into the name of the difficulty.
+fn course_name(course: &str) -> String {
+  #[rustfmt::skip]
+  let name = match course.to_lowercase().as_str() {
+    "0" | "easy"          => "Easy",
+    "1" | "normal"        => "Normal",
+    "2" | "hard"          => "Hard",
+    "3" | "oni"           => "Oni",
+    "4" | "edit" | "ura"  => "Ura",
+    "5" | "tower"         => "Tower",
+    "6" | "dan"           => "Dan",
+
+    _ => return course.to_owned(),
+  };
+
+  return name.to_owned();
+}
+
+/// Overall difficulty with hit windows closest to the ones used by the arcade games for the course.
+fn course_overall_difficulty(course: &str) -> f32 {
+  return match course {
+    "Easy" | "Normal" => 3.0,
+    _ => 8.0,
+  };
+}
+
+/// Chart of a single course which is being parsed, notes are timed in milliseconds.
+struct TjaChart {
+  objects: Vec<TaikoHitObject>,
+  timing_points: Vec<TimingPoint>,
+  velocity_points: Vec<VelocityPoint>,
+  effect_points: Vec<EffectPoint>,
+
+  time: f64,
+  bpm: f64,
+  measure: f64,
+
+  /// Whether the measures which are parsed next start with a barline.
+  barline: bool,
+
+  open_roll: Option<OpenRoll>,
+  balloons: std::vec::IntoIter<usize>,
+  overall_difficulty: f32,
+
+  /// Notes and commands of the measure which is not finished yet.
+  pending: Vec<TjaMeasureItem>,
+}
+
+enum TjaMeasureItem {
+  Note(char),
+  Bpm(f64),
+  Scroll(f64),
+  Delay(f64),
+  Gogo(bool),
+  Measure(f64),
+}
+
+impl TjaChart {
+  fn new(header: &TjaHeader, overall_difficulty: f32) -> Self {
+    let mut chart = Self {
+      objects: Vec::new(),
+      timing_points: Vec::new(),
+      velocity_points: Vec::new(),
+      effect_points: Vec::new(),
+
+      // Positive offset means the chart starts before the music does
+      time: -header.offset * 1000.0,
+      bpm: header.bpm,
+      measure: 4.0,
+      barline: true,
+
+      open_roll: None,
+      balloons: header.balloons.clone().into_iter(),
+      overall_difficulty,
+
+      pending: Vec::new(),
+    };
+
+    chart.set_bpm(header.bpm);
+    chart.set_scroll(1.0);
+
+    return chart;
+  }
+
+  fn beat_length(&self) -> f64 {
+    return 60.0 * 1000.0 / self.bpm;
+  }
+
+  fn set_bpm(&mut self, bpm: f64) {
+    self.bpm = bpm;
+    self.timing_point_mut().bpm = bpm;
+  }
+
+  /// Timing point at the current time, a new one based on the current state is started if there is none yet.
+  fn timing_point_mut(&mut self) -> &mut TimingPoint {
+    let time = Time::from_ms(self.time);
+
+    if !matches!(self.timing_points.last(), Some(point) if point.time == time) {
+      self.timing_points.push(TimingPoint {
+        time,
+        bpm: self.bpm,
+        meter: self.measure,
+        ..Default::default()
+      });
+    }
+
+    return self.timing_points.last_mut().unwrap();
+  }
+
+  fn set_measure(&mut self, measure: f64) {
+    self.measure = measure;
+    self.timing_point_mut().meter = measure;
+  }
+
+  fn set_scroll(&mut self, scroll: f64) {
+    let time = Time::from_ms(self.time);
+    match self.velocity_points.last_mut() {
+      Some(point) if point.time == time => point.velocity = scroll,
+      _ => self.velocity_points.push(VelocityPoint { time, velocity: scroll }),
+    }
+  }
+
+  fn set_gogo(&mut self, kiai: bool) {
+    let time = Time::from_ms(self.time);
+    match self.effect_points.last_mut() {
+      Some(point) if point.time == time => point.kiai = kiai,
+      _ => self.effect_points.push(EffectPoint { time, kiai, ..Default::default() }),
+    }
+  }
+
+  /// Spreads the notes of the finished measure evenly over its length, applying commands in between them.
+  fn finish_measure(&mut self) {
+    // Barlines are generated for every measure of a timing point, so hidden ones need a timing point for each measure
+    if !self.barline {
+      self.timing_point_mut().omit_first_barline = true;
+    }
+
+    let items = std::mem::take(&mut self.pending);
+    let note_count = items.iter().filter(|x| matches!(x, TjaMeasureItem::Note(_))).count();
+
+    // The length of a measure is set once its first note is, later changes only apply to the next measure
+    let first_note = items.iter().position(|x| matches!(x, TjaMeasureItem::Note(_))).unwrap_or(items.len());
+    let mut next_measure = None;
+
+    for (idx, item) in items.iter().enumerate() {
+      match *item {
+        TjaMeasureItem::Note(note) => {
+          self.add_note(note);
+          self.time += self.measure / note_count as f64 * self.beat_length();
+        }
+
+        TjaMeasureItem::Bpm(bpm) => self.set_bpm(bpm),
+        TjaMeasureItem::Scroll(scroll) => self.set_scroll(scroll),
+        TjaMeasureItem::Delay(delay) => self.time += delay * 1000.0,
+        TjaMeasureItem::Gogo(kiai) => self.set_gogo(kiai),
+        TjaMeasureItem::Measure(measure) if idx < first_note => self.set_measure(measure),
+        TjaMeasureItem::Measure(measure) => next_measure = Some(measure),
+      }
+    }
+
+    // Empty measures still take time
+    if note_count == 0 {
+      self.time += self.measure * self.beat_length();
+    }
+
+    if let Some(measure) = next_measure {
+      self.set_measure(measure);
+    }
+  }
+
+  fn add_note(&mut self, note: char) {
+    let time = Time::from_ms(self.time);
+
+    let hit = |color, big| {
+      return TaikoHitObject::Hit(TaikoHit {
+        time,
+        color,
+        big,
+        samples: HitSamples::default(),
+      });
+    };
+
+    match note {
+      '1' => self.objects.push(hit(TaikoColor::Don, false)),
+      '2' => self.objects.push(hit(TaikoColor::Kat, false)),
+      '3' | 'A' => self.objects.push(hit(TaikoColor::Don, true)),
+      '4' | 'B' => self.objects.push(hit(TaikoColor::Kat, true)),
+
+      '5' | '6' if self.open_roll.is_none() => {
+        self.open_roll = Some(OpenRoll::Drumroll {
+          time: self.time,
+          beat_length: self.beat_length(),
+          big: note == '6',
+        });
+      }
+
+      // Kusudama balloons are played the same way as regular ones
+      '7' | '9' if self.open_roll.is_none() => {
+        self.open_roll = Some(OpenRoll::Balloon { time: self.time });
+      }
+
+      '8' => {
+        match self.open_roll.take() {
+          Some(OpenRoll::Drumroll { time, beat_length, big }) => {
+            let duration = self.time - time;
+            let tick_spacing = beat_length / 4.0;
+
+            self.objects.push(TaikoHitObject::Drumroll(TaikoDrumroll {
+              time: Time::from_ms(time),
+              duration: Time::from_ms(duration),
+              tick_spacing: Time::from_ms(tick_spacing),
+              tick_count: ((duration + tick_spacing / 2.0) / tick_spacing).ceil() as usize,
+              big,
+              samples: HitSamples::default(),
+            }));
+          }
+
+          Some(OpenRoll::Balloon { time }) => {
+            let duration = Time::from_ms(self.time - time);
+            let required_hits =
+              self.balloons.next().unwrap_or_else(|| calc_swell_required_hits(self.overall_difficulty, duration));
+
+            self.objects.push(TaikoHitObject::Swell(TaikoSwell {
+              time: Time::from_ms(time),
+              duration,
+              required_hits: required_hits.max(1),
+              samples: HitSamples::default(),
+            }));
+          }
+
+          None => {}
+        }
+      }
+
+      // Blanks, bombs and notes inside of rolls
+      _ => {}
+    }
+  }
+}
+
+/// Reads a chart file, TJA files are often not encoded as UTF-8 so invalid characters are replaced.
+fn read_tja(path: &Path) -> Result<String, BeatmapParseError> {
+  let data = std::fs::read(path)?;
+  return Ok(String::from_utf8_lossy(&data).into_owned());
+}
+
+impl Beatmap {
+  /// Reads and leniently parses every course of a TJA chart, malformed lines are logged and skipped.
+  pub fn from_tja_path(path: impl AsRef<Path>) -> Result<Vec<Self>, BeatmapParseError> {
+    let path = path.as_ref();
+    let data = read_tja(path)?;
+
+    let mut warnings = Vec::new();
+    let beatmaps = Self::parse_tja_lenient(data, path.to_owned(), &mut warnings)?;
+
+    for warning in warnings {
+      warn!("{:?}: {}", path, warning);
+    }
+
+    return Ok(beatmaps);
+  }
+
+  /// Reads a single course of a TJA chart, `course` is the index in the order the courses appear in the file.
+  pub fn from_tja_course(path: impl AsRef<Path>, course: usize) -> Result<Self, BeatmapParseError> {
+    return Self::from_tja_path(path)?.into_iter().nth(course).ok_or(BeatmapParseError::NoCharts);
+  }
+
+  /// Parses every course of a TJA chart into a separate beatmap, failing on the first malformed line.
+  pub fn parse_tja<T: AsRef<str>>(data: T, file_path: PathBuf) -> Result<Vec<Self>, BeatmapParseError> {
+    return Self::parse_tja_with(data.as_ref(), file_path, ParseReporter::strict());
+  }
+
+  /// Same as [`Beatmap::parse_tja`], but skips malformed lines and collects them into `warnings` instead.
+  pub fn parse_tja_lenient<T: AsRef<str>>(
+    data: T,
+    file_path: PathBuf,
+    warnings: &mut Vec<BeatmapParseError>,
+  ) -> Result<Vec<Self>, BeatmapParseError> {
+    return Self::parse_tja_with(data.as_ref(), file_path, ParseReporter::lenient(warnings));
+  }
+
+  fn parse_tja_with(
+    data: &str,
+    file_path: PathBuf,
+    mut reporter: ParseReporter,
+  ) -> Result<Vec<Self>, BeatmapParseError> {
+    let mut beatmaps = Vec::new();
+    let mut header = TjaHeader {
+      bpm: 120.0,
+      course: String::from("Oni"),
+      ..Default::default()
+    };
+
+    // Chart of the course between `#START` and `#END`, `None` while reading headers
+    let mut chart = None::<TjaChart>;
+    let mut skip_chart = false;
+
+    for (i, line) in data.lines().enumerate() {
+      let line = line.split("//").next().unwrap_or_default().trim().trim_start_matches('\u{feff}');
+      if line.is_empty() {
+        continue;
+      }
+
+      let section = if chart.is_some() || skip_chart { Some(header.course.as_str()) } else { Some("Header") };
+      let parse_number = |value: &str| value.trim().parse::<f64>().ok();
+
+      if let Some(command) = line.strip_prefix('#') {
+        let (name, value) = command.split_once(char::is_whitespace).unwrap_or((command, ""));
+        let value = value.trim();
+
+        match name {
+          "START" => {
+            // Only the first player's side of double charts is supported
+            skip_chart = value == "P2";
+
+            if !skip_chart {
+              chart = Some(TjaChart::new(&header, course_overall_difficulty(&header.course)));
+            }
+          }
+
+          "END" => {
+            skip_chart = false;
+
+            let Some(mut chart) = chart.take() else {
+              continue;
+            };
+
+            if !chart.pending.is_empty() {
+              chart.finish_measure();
+            }
+
+            let Some(title) = header.title.clone() else {
+              return Err(BeatmapParseError::MissingProperty { section: "Header", key: "TITLE" });
+            };
+
+            let Some(wave) = header.wave.clone() else {
+              return Err(BeatmapParseError::MissingProperty { section: "Header", key: "WAVE" });
+            };
+
+            chart.objects.sort_by(|a, b| a.time().to_seconds().total_cmp(&b.time().to_seconds()));
+
+            let mut properties = IndexMap::<String, IndexMap<String, String>>::new();
+            let general = properties.entry(String::from("[General]")).or_default();
+            general.insert(String::from("PreviewTime"), ((header.demo_start * 1000.0).max(0.0) as u64).to_string());
+            general.insert(String::from("Mode"), String::from("1"));
+
+            beatmaps.push(Beatmap {
+              hit_objects: chart.objects,
+              timing_points: chart.timing_points,
+              velocity_points: chart.velocity_points,
+              break_points: Vec::new(),
+              effect_points: chart.effect_points,
+
+              title,
+              artist: header.subtitle.clone(),
+              creator: header.maker.clone(),
+              variant: header.course.clone(),
+
+              hp_drain_rate: 5.0,
+              overall_difficulty: chart.overall_difficulty,
+
+              velocity_multiplier: TJA_VELOCITY_MULTIPLIER,
+              slider_tick_rate: 1.0,
+
+              file_path: file_path.clone(),
+              audio_path: PathBuf::from(wave),
+              bg_path: PathBuf::from(&header.bg),
+              events: Vec::new(),
+
+              properties,
+
+              hash: None,
+            });
+          }
+
+          _ if skip_chart => {}
+
+          "BPMCHANGE" | "SCROLL" | "DELAY" => {
+            let Some(chart) = &mut chart else {
+              reporter.report(section, i, "Chart command outside of a course")?;
+              continue;
+            };
+
+            let Some(value) = parse_number(value) else {
+              reporter.report(section, i, &format!("Failed to parse #{} value", name))?;
+              continue;
+            };
+
+            chart.pending.push(match name {
+              "BPMCHANGE" => TjaMeasureItem::Bpm(value),
+              "SCROLL" => TjaMeasureItem::Scroll(value),
+              _ => TjaMeasureItem::Delay(value),
+            });
+          }
+
+          "MEASURE" => {
+            let Some(chart) = &mut chart else {
+              reporter.report(section, i, "Chart command outside of a course")?;
+              continue;
+            };
+
+            let Some((numerator, denominator)) =
+              value.split_once('/').and_then(|(a, b)| parse_number(a).zip(parse_number(b)))
+            else {
+              reporter.report(section, i, "Failed to parse #MEASURE value")?;
+              continue;
+            };
+
+            chart.pending.push(TjaMeasureItem::Measure(4.0 * numerator / denominator));
+          }
+
+          // Gogo time is played as kiai time
+          "GOGOSTART" | "GOGOEND" => {
+            let Some(chart) = &mut chart else {
+              reporter.report(section, i, "Chart command outside of a course")?;
+              continue;
+            };
+
+            chart.pending.push(TjaMeasureItem::Gogo(name == "GOGOSTART"));
+          }
+
+          "BARLINEON" | "BARLINEOFF" => {
+            let Some(chart) = &mut chart else {
+              reporter.report(section, i, "Chart command outside of a course")?;
+              continue;
+            };
+
+            chart.barline = name == "BARLINEON";
+          }
+
+          _ => {}
+        }
+
+        continue;
+      }
+
+      if skip_chart {
+        continue;
+      }
+
+      // Notes
+      if let Some(chart) = &mut chart {
+        for char in line.chars() {
+          match char {
+            ',' => chart.finish_measure(),
+            char if char.is_ascii_alphanumeric() => chart.pending.push(TjaMeasureItem::Note(char)),
+            _ => {}
+          }
+        }
+
+        continue;
+      }
+
+      // Headers
+      let Some((key, value)) = line.split_once(':') else {
+        reporter.report(section, i, "Failed to parse header")?;
+        continue;
+      };
+
+      let value = value.trim();
+
+      match key.trim() {
+        "TITLE" => header.title = Some(value.to_owned()),
+        "SUBTITLE" => header.subtitle = value.trim_start_matches("--").trim_start_matches("++").to_owned(),
+        "MAKER" => header.maker = value.to_owned(),
+        "WAVE" => header.wave = Some(value.to_owned()),
+        "BGIMAGE" => header.bg = value.to_owned(),
+
+        "COURSE" => {
+          header.course = course_name(value);
+          header.balloons.clear();
+        }
+
+        "BALLOON" => {
+          let balloons = value.split(',').filter(|x| !x.trim().is_empty()).map(|x| x.trim().parse::<usize>());
+          match balloons.collect::<Result<Vec<_>, _>>() {
+            Ok(balloons) => header.balloons = balloons,
+            Err(_) => reporter.report(section, i, "Failed to parse balloon hit counts")?,
+          }
+        }
+
+        key @ ("BPM" | "OFFSET" | "DEMOSTART") => {
+          let Some(value) = parse_number(value) else {
+            reporter.report(section, i, &format!("Failed to parse {}", key))?;
+            continue;
+          };
+
+          #[rustfmt::skip]
+          match key {
+            "BPM"    => header.bpm = value,
+            "OFFSET" => header.offset = value,
+            _        => header.demo_start = value,
+          };
+        }
+
+        _ => {}
+      }
+    }
+
+    if beatmaps.is_empty() {
+      return Err(BeatmapParseError::NoCharts);
+    }
+
+    return Ok(beatmaps);
+  }
+}
@@ -5,6 +5,15 @@ use apex_framework::{
 use bytemuck::{Pod, Zeroable};
 use glam::{vec3, Vec2, Vec3};
 
+/// Determines how an instance is drawn by the shader.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HitObjectKind {
+  Circle = 0,
+  Finisher = 1,
+  DrumrollBody = 2,
+  Barline = 3,
+}
+
 #[rustfmt::skip]
 #[repr(C)]
 #[derive(Copy, Clone, Pod, Zeroable)]
@@ -12,8 +21,9 @@ pub struct BakedHitObjectModel {
   pub size_offset : Vec3,
   pub velocity    : f32,
   pub color       : Vec3,
-  pub finisher    : u32,
+  pub kind        : u32,
   pub hit         : f32,
+  pub hold        : f32,
 }
 
 #[rustfmt::skip]
@@ -21,18 +31,22 @@ pub struct HitObjectModel {
   pub time     : f32,
   pub size     : Vec2,
   pub color    : Color,
-  pub finisher : bool,
+  pub kind     : HitObjectKind,
   pub velocity : f32,
   pub hit      : Time,
+
+  /// For how long the object stays at the hit position, in the same units as `time`.
+  pub hold     : f32,
 }
 
 impl HitObjectModel {
-  const ATTRIBS: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+  const ATTRIBS: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
     2 => Float32x3,
     3 => Float32,
     4 => Float32x3,
     5 => Uint32,
     6 => Float32,
+    7 => Float32,
   ];
 
   pub fn describe() -> wgpu::VertexBufferLayout<'static> {
@@ -54,8 +68,9 @@ impl Instance for HitObjectModel {
       size_offset : vec3(self.size.x / self.velocity, self.size.y, self.time),
       velocity    : self.velocity,
       color       : self.color.into(),
-      finisher    : if self.finisher { 1 } else { 0 },
+      kind        : self.kind as u32,
       hit         : self.hit.to_seconds() as f32,
+      hold        : self.hold,
     };
   }
 }
@@ -1,12 +1,16 @@
 use std::collections::HashMap;
 
-use bytemuck::Zeroable;
+use bytemuck::{Pod, Zeroable};
 use glam::{vec2, vec3, vec4, Quat, Vec2, Vec4};
 use tap::Tap;
 use wgpu::util::DeviceExt;
 use winit::dpi::PhysicalSize;
 
-use crate::client::gameplay::beatmap::Beatmap;
+use crate::client::gameplay::{
+  beatmap::{active_point, Beatmap},
+  mods::{Mod, Mods},
+  taiko_hit_object::TaikoHitObject,
+};
 
 use apex_framework::{
   graphics::{
@@ -23,7 +27,24 @@ use apex_framework::{
   time::time::Time,
 };
 
-use super::hit_object_model::{BakedHitObjectModel, HitObjectModel};
+use super::hit_object_model::{BakedHitObjectModel, HitObjectKind, HitObjectModel};
+
+/// Distances from the hit position between which objects fade out with Hidden, in playfield units.
+const HIDDEN_FADE_START: f32 = 640.0;
+const HIDDEN_FADE_END: f32 = 256.0;
+
+/// Radius of the area around the hit position which stays visible with Flashlight, before combo shrinks it.
+const FLASHLIGHT_RADIUS: f32 = 384.0;
+
+/// Visibility mods as they are passed to the fragment shader, each one is disabled while its values are zero.
+#[rustfmt::skip]
+#[repr(C)]
+#[derive(Copy, Clone, Pod, Zeroable)]
+pub struct ModsUniform {
+  pub hidden            : Vec2,
+  pub flashlight_radius : f32,
+  pub _padding          : f32,
+}
 
 #[derive(Debug, Clone)]
 pub struct TaikoRendererConfig {
@@ -39,6 +60,8 @@ pub struct TaikoRendererConfig {
   pub hit_position_y: f32,
   pub don: Color,
   pub kat: Color,
+  pub drumroll: Color,
+  pub swell: Color,
 
   pub hit_animation_height: f64,
 }
@@ -52,6 +75,7 @@ pub struct TaikoRenderer {
   pub shader: wgpu::ShaderModule,
 
   pub time_uniform: Uniform<Vec4>,
+  pub mods_uniform: Uniform<ModsUniform>,
 
   pub texture_layout           : wgpu::BindGroupLayout,
   pub circle_texture           : Texture,
@@ -65,8 +89,17 @@ pub struct TaikoRenderer {
   pub instance_buffer : wgpu::Buffer,
   pub instances       : Vec<HitObjectModel>,
 
+  /// Instance index of each hit object (head circle for drumrolls).
+  pub object_instances : Vec<usize>,
+
+  /// Instance indices of drumroll ticks, empty for regular circles.
+  pub tick_instances   : Vec<Vec<usize>>,
+
   pub config: TaikoRendererConfig,
   pub current_beatmap: Beatmap,
+
+  pub mods: Mods,
+  pub combo: usize,
 }
 
 impl TaikoRenderer {
@@ -86,6 +119,7 @@ impl TaikoRenderer {
     );
 
     let time_uniform = Uniform::new(device, wgpu::ShaderStages::VERTEX);
+    let mods_uniform = Uniform::new(device, wgpu::ShaderStages::FRAGMENT);
     let shader = Self::create_shader(device);
     let texture_layout = Self::create_texture_layout(device);
     let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -97,6 +131,7 @@ impl TaikoRenderer {
         &texture_layout,
         &texture_layout,
         &texture_layout,
+        mods_uniform.layout(),
       ],
       push_constant_ranges: &[],
     });
@@ -134,6 +169,7 @@ impl TaikoRenderer {
       shader,
 
       time_uniform,
+      mods_uniform,
 
       texture_layout,
       circle_texture,
@@ -147,8 +183,14 @@ impl TaikoRenderer {
       instance_buffer,
       instances,
 
+      object_instances: Vec::new(),
+      tick_instances: Vec::new(),
+
       config,
       current_beatmap,
+
+      mods: Mods::default(),
+      combo: 0,
     };
 
     renderer.update_camera(queue);
@@ -262,7 +304,17 @@ impl TaikoRenderer {
   pub fn prepare(&mut self, queue: &wgpu::Queue, time: Time) {
     // Update time uniform
     let time_offset = time.to_seconds() * 1000.0 * self.config.conveyor_zoom * -1.0;
-    self.time_uniform.update(queue, &vec4(time_offset as f32, 0.0, 0.0, 0.0));
+    let kiai_flash = self.current_beatmap.kiai_flash(time);
+    self.time_uniform.update(queue, &vec4(time_offset as f32, kiai_flash, 0.0, 0.0));
+
+    let hidden = if self.mods.contains(Mod::Hidden) { vec2(HIDDEN_FADE_START, HIDDEN_FADE_END) } else { Vec2::ZERO };
+    let flashlight_radius = if self.mods.contains(Mod::Flashlight) {
+      FLASHLIGHT_RADIUS * flashlight_scale(self.combo)
+    } else {
+      0.0
+    };
+
+    self.mods_uniform.update(queue, &ModsUniform { hidden, flashlight_radius, _padding: 0.0 });
   }
 
   pub fn render<'rpass>(&'rpass self, rpass: &mut wgpu::RenderPass<'rpass>) {
@@ -274,6 +326,7 @@ impl TaikoRenderer {
     rpass.set_bind_group(3, &self.circle_overlay_texture.bind_group, &[]);
     rpass.set_bind_group(4, &self.finisher_texture.bind_group, &[]);
     rpass.set_bind_group(5, &self.finisher_overlay_texture.bind_group, &[]);
+    self.mods_uniform.bind(rpass, 6);
 
     rpass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
     rpass.set_vertex_buffer(1, self.instance_buffer.slice(..));
@@ -286,21 +339,27 @@ impl TaikoRenderer {
 
 impl TaikoRenderer {
   pub fn set_hit(&mut self, queue: &wgpu::Queue, hit_idx: usize, hit_time: Time) {
-    let len = self.instances.len();
-    let idx = len - hit_idx - 1;
-    let instance = &mut self.instances[idx];
-    instance.hit = hit_time * 1000.0 * self.config.conveyor_zoom * -1.0;
-
-    let single_baked = [instance.bake()];
-    let byte_slice: &[u8] = bytemuck::cast_slice(&single_baked);
-    let offset = (std::mem::size_of::<BakedHitObjectModel>() * idx) as wgpu::BufferAddress;
+    let idx = self.object_instances[hit_idx];
+    self.update_instance_hit(queue, idx, hit_time);
+  }
 
-    queue.write_buffer(&self.instance_buffer, offset, byte_slice);
+  pub fn set_tick_hit(&mut self, queue: &wgpu::Queue, hit_idx: usize, tick_idx: usize, hit_time: Time) {
+    let idx = self.tick_instances[hit_idx][tick_idx];
+    self.update_instance_hit(queue, idx, hit_time);
   }
 
   pub fn set_hit_all(&mut self, queue: &wgpu::Queue) {
-    for instance in self.instances.iter_mut() {
-      instance.hit = Time::from_seconds(instance.time * -1.0);
+    for (i, obj) in self.current_beatmap.hit_objects.iter().enumerate() {
+      let indices = match obj {
+        TaikoHitObject::Hit(_) => std::slice::from_ref(&self.object_instances[i]),
+        TaikoHitObject::Drumroll(_) => self.tick_instances[i].as_slice(),
+        TaikoHitObject::Swell(_) => &[],
+      };
+
+      for idx in indices.iter().copied() {
+        let instance = &mut self.instances[idx];
+        instance.hit = Time::from_seconds(instance.time * -1.0);
+      }
     }
 
     let instance_data = self.instances.iter().map(Instance::bake).collect::<Vec<_>>();
@@ -369,6 +428,26 @@ impl TaikoRenderer {
     self.prepare_instances(device);
   }
 
+  pub fn set_drumroll_color(&mut self, device: &wgpu::Device, value: Color) {
+    self.config.drumroll = value;
+    self.prepare_instances(device);
+  }
+
+  pub fn set_swell_color(&mut self, device: &wgpu::Device, value: Color) {
+    self.config.swell = value;
+    self.prepare_instances(device);
+  }
+
+  /// Applies the visibility mods, Hidden and Flashlight, from the next frame on.
+  pub fn set_mods(&mut self, mods: Mods) {
+    self.mods = mods;
+  }
+
+  /// Current combo of the play, which Flashlight shrinks with.
+  pub fn set_combo(&mut self, combo: usize) {
+    self.combo = combo;
+  }
+
   pub fn set_hit_animation_height(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat, value: f64) {
     self.config.hit_animation_height = value;
     self.recreate_pipeline(device, format);
@@ -376,6 +455,17 @@ impl TaikoRenderer {
 }
 
 impl TaikoRenderer {
+  fn update_instance_hit(&mut self, queue: &wgpu::Queue, idx: usize, hit_time: Time) {
+    let instance = &mut self.instances[idx];
+    instance.hit = hit_time * 1000.0 * self.config.conveyor_zoom * -1.0;
+
+    let single_baked = [instance.bake()];
+    let byte_slice: &[u8] = bytemuck::cast_slice(&single_baked);
+    let offset = (std::mem::size_of::<BakedHitObjectModel>() * idx) as wgpu::BufferAddress;
+
+    queue.write_buffer(&self.instance_buffer, offset, byte_slice);
+  }
+
   fn recreate_pipeline(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat) {
     self.pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
       label: Some("Render Pipeline"),
@@ -428,39 +518,143 @@ impl TaikoRenderer {
   fn prepare_instances(&mut self, device: &wgpu::Device) {
     const OSU_TAIKO_VELOCITY_MULTIPLIER: f64 = 1.4;
     const OSU_TAIKO_CIRCLE_SIZE: f32 = 128.0;
+    const BARLINE_WIDTH: f32 = 3.0;
 
     let circle_size = OSU_TAIKO_CIRCLE_SIZE;
 
+    let object_count = self.current_beatmap.hit_objects.len();
+
     self.instances.clear();
+    self.object_instances = vec![0; object_count];
+    self.tick_instances = vec![Vec::new(); object_count];
+
+    let base_length = 1000.0;
+    let velocity_multiplier = self.current_beatmap.velocity_multiplier as f64;
+    let scroll_multiplier = |bpm: f64, velocity: f64| {
+      let beat_length = 60.0 / bpm * 1000.0; // we want ms...
+      return OSU_TAIKO_VELOCITY_MULTIPLIER * velocity * base_length / beat_length * velocity_multiplier;
+    };
+
+    let conveyor_time = |time: Time| (time.to_seconds() * 1000.0 * self.config.conveyor_zoom) as f32;
+
+    // Barlines go first so they are drawn below the hit objects
+    for time in self.current_beatmap.barlines() {
+      let bpm = active_point(&self.current_beatmap.timing_points, time, |p| p.time).map_or(60.0, |p| p.bpm);
+      let velocity = active_point(&self.current_beatmap.velocity_points, time, |p| p.time).map_or(1.0, |p| p.velocity);
+      let multiplier = scroll_multiplier(bpm, velocity);
+
+      self.instances.push(HitObjectModel {
+        time: conveyor_time(time),
+        size: vec2(BARLINE_WIDTH, circle_size * 1.55),
+        color: Color::new(0.8, 0.8, 0.8, 1.0),
+        kind: HitObjectKind::Barline,
+        velocity: multiplier as f32,
+        hit: Time::zero(),
+        hold: 0.0,
+      });
+    }
 
     let mut idx_t = self.current_beatmap.timing_points.len() - 1;
     let mut idx_v = self.current_beatmap.velocity_points.len() - 1;
-    for obj in self.current_beatmap.hit_objects.iter().rev() {
+    for (i, obj) in self.current_beatmap.hit_objects.iter().enumerate().rev() {
       #[rustfmt::skip] {
-        while self.current_beatmap.timing_points[idx_t].time > obj.time && idx_t != 0 { idx_t -= 1; }
-        while self.current_beatmap.velocity_points[idx_v].time > obj.time && idx_v != 0 { idx_v -= 1; }
+        while self.current_beatmap.timing_points[idx_t].time > obj.time() && idx_t != 0 { idx_t -= 1; }
+        while self.current_beatmap.velocity_points[idx_v].time > obj.time() && idx_v != 0 { idx_v -= 1; }
       };
 
       // Timing
-      let beat_length = 60.0 / self.current_beatmap.timing_points[idx_t].bpm * 1000.0; // we want ms...
+      let bpm = self.current_beatmap.timing_points[idx_t].bpm;
       let velocity = self.current_beatmap.velocity_points[idx_v].velocity;
-
-      let base_length = 1000.0;
-
-      #[rustfmt::skip]
-      let multiplier = OSU_TAIKO_VELOCITY_MULTIPLIER * velocity * base_length / beat_length * self.current_beatmap.velocity_multiplier as f64;
+      let multiplier = scroll_multiplier(bpm, velocity);
 
       let size_big = vec2(circle_size * 1.55, circle_size * 1.55);
       let size_small = vec2(circle_size, circle_size);
-
-      self.instances.push(HitObjectModel {
-        time: (obj.time.to_seconds() * 1000.0 * self.config.conveyor_zoom) as f32,
-        size: if obj.big { size_big } else { size_small },
-        color: if obj.color.is_kat() { self.config.kat } else { self.config.don },
-        finisher: obj.big,
-        velocity: multiplier as f32,
-        hit: Time::zero(),
-      });
+      let size = if obj.is_big() { size_big } else { size_small };
+      let kind = if obj.is_big() { HitObjectKind::Finisher } else { HitObjectKind::Circle };
+
+      match obj {
+        TaikoHitObject::Hit(hit) => {
+          self.object_instances[i] = self.instances.len();
+          self.instances.push(HitObjectModel {
+            time: conveyor_time(hit.time),
+            size,
+            color: if hit.color.is_kat() { self.config.kat } else { self.config.don },
+            kind,
+            velocity: multiplier as f32,
+            hit: Time::zero(),
+            hold: 0.0,
+          });
+        }
+
+        TaikoHitObject::Drumroll(drumroll) => {
+          let start = conveyor_time(drumroll.time);
+          let end = conveyor_time(drumroll.end_time());
+
+          // Instances pushed later are drawn on top, so the body goes first and the head goes last.
+          self.instances.push(HitObjectModel {
+            time: (start + end) / 2.0,
+            size: vec2((end - start) * multiplier as f32, size.y * 0.8),
+            color: self.config.drumroll,
+            kind: HitObjectKind::DrumrollBody,
+            velocity: multiplier as f32,
+            hit: Time::zero(),
+            hold: 0.0,
+          });
+
+          self.instances.push(HitObjectModel {
+            time: end,
+            size,
+            color: self.config.drumroll,
+            kind,
+            velocity: multiplier as f32,
+            hit: Time::zero(),
+            hold: 0.0,
+          });
+
+          let mut ticks = vec![0; drumroll.tick_count];
+          for tick in (0 .. drumroll.tick_count).rev() {
+            ticks[tick] = self.instances.len();
+            self.instances.push(HitObjectModel {
+              time: conveyor_time(drumroll.tick_time(tick)),
+              size: size_small * 0.3,
+              color: Color::new(1.0, 1.0, 1.0, 1.0),
+              kind: HitObjectKind::Circle,
+              velocity: multiplier as f32,
+              hit: Time::zero(),
+              hold: 0.0,
+            });
+          }
+
+          self.tick_instances[i] = ticks;
+
+          self.object_instances[i] = self.instances.len();
+          self.instances.push(HitObjectModel {
+            time: start,
+            size,
+            color: self.config.drumroll,
+            kind,
+            velocity: multiplier as f32,
+            hit: Time::zero(),
+            hold: 0.0,
+          });
+        }
+
+        TaikoHitObject::Swell(swell) => {
+          let start = conveyor_time(swell.time);
+          let end = conveyor_time(swell.end_time());
+
+          self.object_instances[i] = self.instances.len();
+          self.instances.push(HitObjectModel {
+            time: start,
+            size: size_big,
+            color: self.config.swell,
+            kind: HitObjectKind::Finisher,
+            velocity: multiplier as f32,
+            hit: Time::zero(),
+            hold: end - start,
+          });
+        }
+      }
     }
 
     let instance_data = self.instances.iter().map(Instance::bake).collect::<Vec<_>>();
@@ -493,6 +687,7 @@ impl Drawable for TaikoRenderer {
     );
 
     self.time_uniform = Uniform::new(device, wgpu::ShaderStages::VERTEX);
+    self.mods_uniform = Uniform::new(device, wgpu::ShaderStages::FRAGMENT);
     self.shader = Self::create_shader(device);
     self.texture_layout = Self::create_texture_layout(device);
     self.pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
@@ -504,6 +699,7 @@ impl Drawable for TaikoRenderer {
         &self.texture_layout,
         &self.texture_layout,
         &self.texture_layout,
+        self.mods_uniform.layout(),
       ],
       push_constant_ranges: &[],
     });
@@ -572,3 +768,16 @@ impl Drawable for TaikoRenderer {
     self.update_camera(queue);
   }
 }
+
+/// Flashlight shrinks at 100 and 200 combo, the same as in osu!.
+fn flashlight_scale(combo: usize) -> f32 {
+  if combo >= 200 {
+    return 0.8;
+  }
+
+  if combo >= 100 {
+    return 0.9;
+  }
+
+  return 1.0;
+}
@@ -6,6 +6,7 @@ struct SceneUniform {
 @group(0) @binding(0)
 var<uniform> scene: SceneUniform;
 
+// x is the time offset, y is the kiai flash strength
 @group(1) @binding(0)
 var<uniform> time: vec4<f32>;
 
@@ -20,15 +21,18 @@ struct InstanceInput {
     @location(2) size_offset : vec3<f32>,
     @location(3) velocity    : f32,
     @location(4) color       : vec3<f32>,
-    @location(5) finisher    : u32,
+    @location(5) kind        : u32,
     @location(6) hit         : f32,
+    @location(7) hold        : f32,
 };
 
 struct VertexOutput {
-    @builtin(position) clip_position : vec4<f32>,
-    @location(0)       uv_coords     : vec2<f32>,
-    @location(1)       color         : vec4<f32>,
-    @location(2)       finisher      : u32,
+    @builtin(position) clip_position  : vec4<f32>,
+    @location(0)       uv_coords      : vec2<f32>,
+    @location(1)       color          : vec4<f32>,
+    @location(2)       kind           : u32,
+    // Position relative to the hit position, in playfield units
+    @location(3)       world_position : vec2<f32>,
 }
 
 @vertex
@@ -44,11 +48,18 @@ fn vs_main(
         vec4(so.z,  0.0, 0.0, 1.0),
     );
 
+    // Held objects (swells) stay at the hit position until they are hit or the hold is over.
+    var hold = instance.hold;
+    if instance.hit != 0.0 {
+        hold = clamp(-instance.hit - so.z, 0.0, hold);
+    }
+
+    let time_offset = time.x + clamp(-(so.z + time.x), 0.0, hold);
     let time_matrix = mat4x4<f32>(
-        vec4(   1.0, 0.0, 0.0, 0.0),
-        vec4(   0.0, 1.0, 0.0, 0.0),
-        vec4(   0.0, 0.0, 1.0, 0.0),
-        vec4(time.x, 0.0, 0.0, 1.0),
+        vec4(        1.0, 0.0, 0.0, 0.0),
+        vec4(        0.0, 1.0, 0.0, 0.0),
+        vec4(        0.0, 0.0, 1.0, 0.0),
+        vec4(time_offset, 0.0, 0.0, 1.0),
     );
 
     var out: VertexOutput;
@@ -62,11 +73,12 @@ fn vs_main(
         let p = (time_matrix * model_matrix) * vec4<f32>(-0.5, 0.0, 0.0, 1.0);
 
 
+        let hit_offset = instance.hit + hold;
         let hit_matrix = mat4x4<f32>(
-            vec4(         1.0, 0.0, 0.0, 0.0),
-            vec4(         0.0, 1.0, 0.0, 0.0),
-            vec4(         0.0, 0.0, 1.0, 0.0),
-            vec4(instance.hit, 0.0, 0.0, 1.0),
+            vec4(       1.0, 0.0, 0.0, 0.0),
+            vec4(       0.0, 1.0, 0.0, 0.0),
+            vec4(       0.0, 0.0, 1.0, 0.0),
+            vec4(hit_offset, 0.0, 0.0, 1.0),
         );
         let h = (hit_matrix * model_matrix) * vec4<f32>(-0.5, 0.0, 0.0, 1.0);
 
@@ -83,11 +95,13 @@ fn vs_main(
     }
 
     out.clip_position.x *= instance.velocity;
+    out.world_position = out.clip_position.xy;
 
     out.clip_position = scene.view_proj * out.clip_position;
     out.uv_coords = vertex.uv_coords;
-    out.color = vec4(instance.color, 1.0);
-    out.finisher = instance.finisher;
+    // Objects light up on every beat of kiai time
+    out.color = vec4(mix(instance.color, vec3(1.0), time.y * 0.3), 1.0);
+    out.kind = instance.kind;
 
     return out;
 }
@@ -105,6 +119,16 @@ fn vs_main(
 @group(5) @binding(0) var t3 : texture_2d<f32>;
 @group(5) @binding(1) var s3 : sampler;
 
+// Visibility mods, each one is disabled while its values are zero
+struct ModsUniform {
+    // Distances from the hit position at which Hidden starts and finishes fading objects out
+    hidden            : vec2<f32>,
+    flashlight_radius : f32,
+}
+
+@group(6) @binding(0)
+var<uniform> mods: ModsUniform;
+
 fn to_srgb(srgba: vec4<f32>) -> vec4<f32> {
     let srgb = srgba.rgb;
     let cutoff = srgb < vec3<f32>(0.04045);
@@ -113,21 +137,41 @@ fn to_srgb(srgba: vec4<f32>) -> vec4<f32> {
     return vec4(select(higher, lower, cutoff), srgba.a);
 }
 
+fn visibility(position: vec2<f32>) -> f32 {
+    var alpha = 1.0;
+
+    // Objects fade out while approaching the hit position
+    if mods.hidden.x > 0.0 {
+        alpha *= smoothstep(mods.hidden.y, mods.hidden.x, position.x);
+    }
+
+    // Only a circle around the hit position is visible
+    if mods.flashlight_radius > 0.0 {
+        alpha *= 1.0 - smoothstep(mods.flashlight_radius * 0.8, mods.flashlight_radius, length(position));
+    }
+
+    return alpha;
+}
+
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
     let texture_finisher = textureSample(t2, s2, in.uv_coords);
     let overlay_finisher = textureSample(t3, s3, in.uv_coords);
     let texture_circle = textureSample(t0, s0, in.uv_coords);
     let overlay_circle = textureSample(t1, s1, in.uv_coords);
-    if in.finisher == u32(0) {
-        let out = overlay_circle              * overlay_circle.a
-                + (texture_circle * in.color) * (1.0 - overlay_circle.a);
 
-        return to_srgb(out);
+    var out: vec4<f32>;
+    if in.kind == u32(2) || in.kind == u32(3) {
+        // Drumroll body (the circles on both ends are drawn separately) or barline.
+        out = in.color;
+    } else if in.kind == u32(0) {
+        out = overlay_circle              * overlay_circle.a
+            + (texture_circle * in.color) * (1.0 - overlay_circle.a);
     } else {
-        let out = overlay_finisher              * overlay_finisher.a
-                + (texture_finisher * in.color) * (1.0 - overlay_finisher.a);
-
-        return to_srgb(out);
+        out = overlay_finisher              * overlay_finisher.a
+            + (texture_finisher * in.color) * (1.0 - overlay_finisher.a);
     }
+
+    out.a *= visibility(in.world_position);
+    return to_srgb(out);
 }
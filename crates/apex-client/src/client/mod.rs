@@ -7,4 +7,5 @@ pub mod graphics;
 pub mod score;
 pub mod screen;
 pub mod settings;
+pub mod stable;
 pub mod ui;
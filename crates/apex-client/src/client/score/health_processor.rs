@@ -0,0 +1,107 @@
+use crate::client::gameplay::{beatmap::Beatmap, mods::Mods, taiko_hit_object::TaikoHitObject};
+
+use super::judgement_processor::Judgement;
+
+/// Health is drained by misses and refilled by hits, a play passes if more than this is left at the end.
+pub const PASS_HEALTH: f64 = 0.5;
+
+/// Tracks the health of a play following the osu!taiko model: health starts empty and only circles affect it, with
+/// gains scaled so that the whole bar fills up well before the end of a perfect play.
+pub struct HealthProcessor {
+  health: f64,
+
+  hp_multiplier: f64,
+  hp_miss_multiplier: f64,
+
+  total_circles: usize,
+  judged_circles: usize,
+
+  /// How many more times the health bar is refilled once the play can't pass anymore.
+  lives: usize,
+}
+
+impl Default for HealthProcessor {
+  fn default() -> Self {
+    return Self {
+      health: 0.0,
+      hp_multiplier: 0.0,
+      hp_miss_multiplier: 0.0,
+      total_circles: 0,
+      judged_circles: 0,
+      lives: 0,
+    };
+  }
+}
+
+impl HealthProcessor {
+  /// Expects `beatmap` with `mods` already applied, `mods` only decide the extra lives.
+  pub fn new(beatmap: &Beatmap, mods: Mods) -> Self {
+    let hp_drain_rate = beatmap.hp_drain_rate as f64;
+    let total_circles = beatmap.hit_objects.iter().filter(|x| matches!(x, TaikoHitObject::Hit(_))).count();
+
+    return Self {
+      hp_multiplier: 1.0 / (3.0 * total_circles.max(1) as f64 * difficulty_range(hp_drain_rate, 0.5, 0.75, 0.98)),
+      hp_miss_multiplier: difficulty_range(hp_drain_rate, 0.0018, 0.0075, 0.1),
+      total_circles,
+      lives: mods.extra_lives(),
+      ..Default::default()
+    };
+  }
+
+  pub fn feed(&mut self, result: Judgement) {
+    let increase = match result {
+      Judgement::Hit300 => self.great_increase(),
+      Judgement::Hit150 => 1.1 * self.hp_multiplier,
+      Judgement::Miss => -self.hp_miss_multiplier,
+
+      // Drumrolls and swells are bonus objects which neither refill nor drain health
+      Judgement::DrumrollTick | Judgement::SwellTick | Judgement::Swell => return,
+    };
+
+    self.health = (self.health + increase).clamp(0.0, 1.0);
+    self.judged_circles += 1;
+
+    if self.lives > 0 && self.has_failed() {
+      self.lives -= 1;
+      self.health = 1.0;
+    }
+  }
+
+  pub fn health(&self) -> f64 {
+    return self.health;
+  }
+
+  pub fn lives(&self) -> usize {
+    return self.lives;
+  }
+
+  /// Whether the play can no longer pass, even if every remaining circle is hit perfectly. At the end of a play this is
+  /// simply whether there is no more health left than needed to pass. Beatmaps without circles can't be failed.
+  pub fn has_failed(&self) -> bool {
+    if self.total_circles == 0 {
+      return false;
+    }
+
+    let remaining_circles = self.total_circles.saturating_sub(self.judged_circles);
+    let max_health = (self.health + remaining_circles as f64 * self.great_increase()).min(1.0);
+
+    return max_health <= PASS_HEALTH;
+  }
+
+  fn great_increase(&self) -> f64 {
+    return 3.0 * self.hp_multiplier;
+  }
+}
+
+/// Maps a difficulty value from 0 to 10 onto a range, the same way osu! scales its difficulty settings.
+fn difficulty_range(difficulty: f64, min: f64, mid: f64, max: f64) -> f64 {
+  if difficulty > 5.0 {
+    return mid + (max - mid) * (difficulty - 5.0) / 5.0;
+  }
+
+  if difficulty < 5.0 {
+    return mid - (mid - min) * (5.0 - difficulty) / 5.0;
+  }
+
+  return mid;
+}
@@ -1,7 +1,7 @@
 use apex_framework::time::time::Time;
 
 use crate::client::gameplay::{
-  taiko_hit_object::{TaikoColor, TaikoHitObject},
+  taiko_hit_object::{TaikoColor, TaikoHit},
   taiko_player::TaikoInput,
 };
 
@@ -10,6 +10,15 @@ pub enum Judgement {
   Hit300,
   Hit150,
   Miss,
+
+  /// Bonus hit on a drumroll tick, doesn't affect combo or accuracy.
+  DrumrollTick,
+
+  /// Single hit on a swell which hasn't been completed yet.
+  SwellTick,
+
+  /// Swell was completed, grants bonus score without affecting combo or accuracy.
+  Swell,
 }
 
 pub struct HitResult {
@@ -21,7 +30,7 @@ pub struct HitResult {
 
 pub fn check_hit(
   hit_time: Time,
-  hit_object: &TaikoHitObject,
+  hit_object: &TaikoHit,
   hit_input: TaikoInput,
   hit_window_150: Time,
   hit_window_300: Time,
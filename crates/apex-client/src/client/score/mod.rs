@@ -1,4 +1,5 @@
 pub mod grades;
+pub mod health_processor;
 pub mod judgement_processor;
 pub mod score;
 pub mod score_cache;
@@ -1,7 +1,7 @@
 use apex_framework::time::time::Time;
 use jiff::Timestamp;
 
-use crate::client::gameplay::taiko_player::TaikoInput;
+use crate::client::gameplay::{mods::Mods, taiko_player::TaikoInput};
 
 use super::grades::Grade;
 
@@ -18,6 +18,14 @@ pub struct Score {
   pub(crate) accuracy: f32,
   pub(crate) grade: Grade,
   pub(crate) hits: Vec<(Time, TaikoInput)>,
+
+  /// The health drained before the end of the play.
+  pub(crate) failed: bool,
+
+  pub(crate) mods: Mods,
+
+  /// Playback rate the score was set at, `1.0` is realtime.
+  pub(crate) rate: f64,
 }
 
 impl Default for Score {
@@ -34,6 +42,9 @@ impl Default for Score {
       accuracy: 0.0,
       grade: Grade::D,
       hits: Vec::new(),
+      failed: false,
+      mods: Mods::default(),
+      rate: 1.0,
     }
   }
 }
@@ -84,4 +95,16 @@ impl Score {
   pub fn hits(&self) -> &[(Time, TaikoInput)] {
     return &self.hits;
   }
+
+  pub fn failed(&self) -> bool {
+    return self.failed;
+  }
+
+  pub fn mods(&self) -> Mods {
+    return self.mods;
+  }
+
+  pub fn rate(&self) -> f64 {
+    return self.rate;
+  }
 }
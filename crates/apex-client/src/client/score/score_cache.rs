@@ -7,7 +7,10 @@ use tap::Tap;
 
 use apex_framework::time::time::Time;
 
-use crate::client::gameplay::beatmap::BeatmapHash;
+use crate::client::gameplay::{
+  beatmap::BeatmapHash,
+  mods::{DifficultyAdjust, Mod, Mods},
+};
 
 use super::{grades::Grade, score::Score};
 
@@ -42,12 +45,35 @@ impl ScoreCache {
            last_combo integer not null,
            max_combo integer not null,
            accuracy real not null,
-           hits text not null
+           hits text not null,
+           failed integer not null default 0,
+           mods integer not null default 0,
+           rate real not null default 1.0,
+           adjust_od real,
+           adjust_hp real,
+           adjust_scroll real,
+           seed integer
        )",
         (),
       )
       .unwrap();
 
+    // Databases created by older versions lack the newer columns
+    for (column, definition) in [
+      ("failed", "integer not null default 0"),
+      ("mods", "integer not null default 0"),
+      ("rate", "real not null default 1.0"),
+      ("adjust_od", "real"),
+      ("adjust_hp", "real"),
+      ("adjust_scroll", "real"),
+      ("seed", "integer"),
+    ] {
+      if connection.prepare(&format!("select {} from scores", column)).is_err() {
+        let sql = format!("alter table scores add column {} {}", column, definition);
+        connection.execute(&sql, ()).unwrap();
+      }
+    }
+
     return Self {
       conn: connection,
       cache: AHashMap::new(),
@@ -64,7 +90,8 @@ impl ScoreCache {
     let mut stmt = self
       .conn
       .prepare(
-        "select date, username, score_points, result_300, result_150, result_miss, last_combo, max_combo, accuracy, hits
+        "select date, username, score_points, result_300, result_150, result_miss, last_combo, max_combo, accuracy, hits, failed, mods, rate,
+              adjust_od, adjust_hp, adjust_scroll, seed
        from scores
        where path = ?1",
       )
@@ -90,6 +117,14 @@ impl ScoreCache {
           })
           .collect::<Vec<_>>();
 
+        let mut mods = Mods::from_bits(row.get::<_, i64>(11).unwrap() as u64);
+        *mods.difficulty_adjust_mut() = DifficultyAdjust {
+          overall_difficulty: row.get(13).unwrap(),
+          hp_drain_rate: row.get(14).unwrap(),
+          scroll_speed: row.get::<_, Option<f32>>(15).unwrap().unwrap_or(1.0),
+        };
+        mods.set_seed(row.get::<_, Option<i64>>(16).unwrap().unwrap_or(0) as u32);
+
         return Ok(Score {
           date: Timestamp::from_millisecond(row.get(0).unwrap()).unwrap(),
           username: row.get(1).unwrap(),
@@ -102,6 +137,9 @@ impl ScoreCache {
           accuracy: row.get::<_, f32>(8).unwrap(),
           grade: Grade::from_osu_stable(result_300, result_150, result_miss),
           hits,
+          failed: row.get::<_, bool>(10).unwrap(),
+          mods,
+          rate: row.get::<_, f64>(12).unwrap(),
         });
       })
       .unwrap();
@@ -125,10 +163,15 @@ impl ScoreCache {
   pub fn insert(&mut self, beatmap: BeatmapHash, score: Score) -> ScoreId {
     let id = ScoreId(self.scores.len());
 
+    // Difficulty Adjust settings and the Random seed are only stored for scores which were played with them
+    let adjust = score.mods().contains(Mod::DifficultyAdjust).then(|| *score.mods().difficulty_adjust());
+    let seed = score.mods().contains(Mod::Random).then(|| score.mods().seed() as i64);
+
     self.conn.execute(
-      "insert into scores (path, date, username, score_points, result_300, result_150, result_miss, last_combo, max_combo, accuracy, hits)
-       values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
-       (
+      "insert into scores (path, date, username, score_points, result_300, result_150, result_miss, last_combo, max_combo, accuracy, hits, failed, mods, rate,
+                           adjust_od, adjust_hp, adjust_scroll, seed)
+       values (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
+       rusqlite::params![
           beatmap.to_string(),
           score.date().as_millisecond(),
           score.username(),
@@ -145,7 +188,14 @@ impl ScoreCache {
           }).tap_mut(|x| {
             x.pop();
           }),
-       )
+          score.failed(),
+          score.mods().bits() as i64,
+          score.rate(),
+          adjust.and_then(|x| x.overall_difficulty),
+          adjust.and_then(|x| x.hp_drain_rate),
+          adjust.map(|x| x.scroll_speed),
+          seed,
+       ]
     ).unwrap();
 
     self.scores.push(score);
@@ -153,4 +203,19 @@ impl ScoreCache {
 
     return id;
   }
+
+  /// Inserts a score unless the same one is stored already, so importing scores twice doesn't duplicate them.
+  pub fn import(&mut self, beatmap: BeatmapHash, score: Score) -> Option<ScoreId> {
+    let ids = self.beatmap_scores(beatmap).map(<[_]>::to_vec).unwrap_or_default();
+
+    let is_stored = ids.iter().map(|id| self.score_details(*id)).any(|x| {
+      return x.date == score.date && x.username == score.username && x.score_points == score.score_points;
+    });
+
+    if is_stored {
+      return None;
+    }
+
+    return Some(self.insert(beatmap, score));
+  }
 }
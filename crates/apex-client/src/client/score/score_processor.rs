@@ -1,7 +1,10 @@
 use apex_framework::time::time::Time;
 use jiff::Timestamp;
 
-use crate::client::gameplay::taiko_player::TaikoInput;
+use crate::client::gameplay::{
+  mods::{Mod, Mods},
+  taiko_player::TaikoInput,
+};
 
 use super::{grades::Grade, judgement_processor::Judgement, score::Score};
 
@@ -15,6 +18,8 @@ pub struct ScoreProcessor {
   accuracy: f32,
 
   hits: Vec<(Time, TaikoInput)>,
+  mods: Mods,
+  failed: bool,
 }
 
 impl Default for ScoreProcessor {
@@ -28,11 +33,17 @@ impl Default for ScoreProcessor {
       curr_combo: 0,
       max_combo: 0,
       accuracy: 1.0,
+      mods: Mods::default(),
+      failed: false,
     };
   }
 }
 
 impl ScoreProcessor {
+  pub fn new(mods: Mods) -> Self {
+    return Self { mods, ..Default::default() };
+  }
+
   pub fn feed(&mut self, time: Time, input: Option<TaikoInput>, result: Judgement) {
     match result {
       Judgement::Hit300 => {
@@ -47,11 +58,29 @@ impl ScoreProcessor {
         self.curr_combo += 1;
 
         self.score_points += 150 * self.curr_combo;
+
+        if self.mods.contains(Mod::Perfect) {
+          self.failed = true;
+        }
       }
 
       Judgement::Miss => {
         self.result_miss += 1;
         self.curr_combo = 0;
+
+        if self.mods.contains(Mod::SuddenDeath) || self.mods.contains(Mod::Perfect) {
+          self.failed = true;
+        }
+      }
+
+      Judgement::DrumrollTick => {
+        self.score_points += 300;
+      }
+
+      Judgement::SwellTick => {}
+
+      Judgement::Swell => {
+        self.score_points += 5000;
       }
     };
 
@@ -59,7 +88,10 @@ impl ScoreProcessor {
       self.max_combo = self.curr_combo;
     }
 
-    self.accuracy = self.calc_accuracy();
+    // Drumroll ticks and swells are bonus only and don't affect accuracy.
+    if matches!(result, Judgement::Hit300 | Judgement::Hit150 | Judgement::Miss) {
+      self.accuracy = self.calc_accuracy();
+    }
 
     if let Some(input) = input {
       self.hits.push((time, input));
@@ -90,12 +122,28 @@ impl ScoreProcessor {
     return self.max_combo;
   }
 
+  /// Whether the play has failed, either by breaking a condition mod, i.e. Sudden Death or Perfect, or through
+  /// [`ScoreProcessor::fail`].
+  pub fn has_failed(&self) -> bool {
+    return self.failed;
+  }
+
+  /// Fails the play for a reason outside of the judgements, like running out of health.
+  pub fn fail(&mut self) {
+    self.failed = true;
+  }
+
   fn calc_accuracy(&self) -> f32 {
     let n_300 = self.result_300 as f32;
     let n_150 = self.result_150 as f32;
     let n_miss = self.result_miss as f32;
 
-    return (n_300 + n_150 * 0.5) / (n_300 + n_150 + n_miss);
+    let total = n_300 + n_150 + n_miss;
+    if total == 0.0 {
+      return 1.0;
+    }
+
+    return (n_300 + n_150 * 0.5) / total;
   }
 
   pub fn export(&self, date: Timestamp, username: String) -> Score {
@@ -111,6 +159,9 @@ impl ScoreProcessor {
       accuracy: self.accuracy,
       grade: Grade::from_osu_stable(self.result_300, self.result_150, self.result_miss),
       hits: self.hits.clone(),
+      failed: self.failed,
+      mods: self.mods,
+      rate: self.mods.rate(),
     };
   }
 }
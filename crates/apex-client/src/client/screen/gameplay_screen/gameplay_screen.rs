@@ -5,20 +5,32 @@ use jiff::Timestamp;
 use rodio::{source::UniformSourceIterator, Decoder, DeviceTrait};
 
 use crate::client::{
-  audio::game_audio::{GameAudio, GameAudioController},
+  audio::{
+    game_audio::{GameAudio, GameAudioController},
+    hitsound_bank::{HitSound, HitsoundBank},
+  },
   client::Client,
   event::ClientEvent,
   gameplay::{
-    beatmap::Beatmap,
-    taiko_player::{BreakState, TaikoInput, TaikoPlayer},
+    autoplay::AUTOPLAY_USERNAME,
+    beatmap::{active_point, Beatmap},
+    mods::{Mod, Mods},
+    replay_player::ReplayPlayer,
+    taiko_hit_object::HitSamples,
+    taiko_player::{BreakState, HitTarget, TaikoInput, TaikoPlayer},
   },
   graphics::taiko_renderer::taiko_renderer::{TaikoRenderer, TaikoRendererConfig},
-  score::{judgement_processor::Judgement, score_processor::ScoreProcessor},
+  score::{
+    health_processor::HealthProcessor, judgement_processor::Judgement, score::Score, score_processor::ScoreProcessor,
+  },
   settings::Settings,
-  ui::{break_overlay::BreakOverlayView, ingame_overlay::IngameOverlayView},
+  ui::{
+    break_overlay::BreakOverlayView, ingame_overlay::IngameOverlayView, replay_overlay::ReplayOverlayView,
+    swell_overlay::SwellOverlayView,
+  },
 };
 use apex_framework::{
-  audio::{arc_buffer::ArcSamplesBuffer, frameless_source::FramelessSource},
+  audio::frameless_source::FramelessSource,
   core::Core,
   event::EventBus,
   graphics::{
@@ -41,14 +53,30 @@ pub struct GameplayScreen {
   hit_drum_display: HitDrumDisplay,
   ingame_overlay: IngameOverlayView,
   break_overlay: BreakOverlayView,
+  swell_overlay: SwellOverlayView,
+  replay_overlay: ReplayOverlayView,
 
   taiko_player: TaikoPlayer,
   score_processor: ScoreProcessor,
+  health_processor: HealthProcessor,
+  mods: Mods,
+
+  /// Set once the health drained, the play is over but still shown behind the fail screen.
+  failed: bool,
 
-  don_hitsound: ArcSamplesBuffer<f32>,
-  kat_hitsound: ArcSamplesBuffer<f32>,
+  /// Recorded or generated inputs which are played back instead of keyboard input while watching a replay.
+  replay: Option<ReplayPlayer>,
+
+  /// Hides the HUD, only the playfield is shown.
+  cinema: bool,
+
+  hitsounds: HitsoundBank,
 
   hit_pos_sprite: usize,
+  kiai_flash: f32,
+
+  playfield_sprite: usize,
+  playfield_height: f32,
 
   // Hit pos settings
   hit_position_x_px: f32,
@@ -61,6 +89,8 @@ impl GameplayScreen {
   pub fn new(event_bus: EventBus<ClientEvent>, graphics: &Graphics, audio: &GameAudio, settings: &Settings) -> Self {
     let ingame_overlay = IngameOverlayView::new();
     let break_overlay = BreakOverlayView::new();
+    let swell_overlay = SwellOverlayView::new();
+    let replay_overlay = ReplayOverlayView::new();
 
     let x = settings.taiko.general.hit_position_x_px();
     let y = settings.taiko.general.hit_position_y_perc() * graphics.height;
@@ -79,6 +109,8 @@ impl GameplayScreen {
         hit_position_y: y,
         don: settings.taiko.general.don_color(),
         kat: settings.taiko.general.kat_color(),
+        drumroll: settings.taiko.general.drumroll_color(),
+        swell: settings.taiko.general.swell_color(),
         // Apparently setting it to f64::INFINITY leads to a crash, see https://github.com/gfx-rs/wgpu/issues/6082
         hit_animation_height: if settings.taiko.general.hit_animation() { 12.5 } else { 9999999.0 },
       },
@@ -94,6 +126,25 @@ impl GameplayScreen {
     );
 
     let gameplay_scale = settings.taiko.general.gameplay_scale() as f32;
+
+    // Allocated first so it's drawn behind every other sprite
+    let playfield_height = settings.taiko.general.playfield_height();
+    let image = image::DynamicImage::ImageRgba8(image::RgbaImage::from_pixel(16, 16, image::Rgba([255; 4])));
+    let texture = sprite_renderer.add_texture(&image);
+    let playfield_sprite = sprite_renderer.alloc_sprite(
+      &graphics.device,
+      vec2(0.0, y),
+      vec2(graphics.width, playfield_height * gameplay_scale),
+      Origin::CenterLeft,
+      false,
+      false,
+      texture,
+    );
+
+    sprite_renderer.mutate_sprite(&graphics.device, playfield_sprite, |model| {
+      model.color = settings.taiko.general.playfield_color();
+    });
+
     let x_drum = x - 160.0 * gameplay_scale;
     let hit_result_display = HitResultDisplay::new(graphics, &mut sprite_renderer, x, y, gameplay_scale);
     let hit_drum_display = HitDrumDisplay::new(graphics, &mut sprite_renderer, x_drum, y, gameplay_scale);
@@ -101,9 +152,6 @@ impl GameplayScreen {
     let taiko_player = TaikoPlayer::new();
     let score_processor = ScoreProcessor::default();
 
-    let don_hitsound = audio.load_sound("./assets/red.wav");
-    let kat_hitsound = audio.load_sound("./assets/blue.wav");
-
     let taiko_circle_size = 128.0 * gameplay_scale;
     let size = Vec2::splat(taiko_circle_size);
     let origin = Origin::CenterCenter;
@@ -125,14 +173,24 @@ impl GameplayScreen {
       hit_drum_display,
       ingame_overlay,
       break_overlay,
+      swell_overlay,
+      replay_overlay,
 
       score_processor,
+      health_processor: HealthProcessor::default(),
+      mods: Mods::default(),
+      failed: false,
       taiko_player,
+      replay: None,
+      cinema: false,
 
-      don_hitsound,
-      kat_hitsound,
+      hitsounds: HitsoundBank::default(),
 
       hit_pos_sprite,
+      kiai_flash: 0.0,
+
+      playfield_sprite,
+      playfield_height,
 
       hit_position_x_px: x,
       hit_position_y_px: y,
@@ -145,34 +203,113 @@ impl GameplayScreen {
 impl GameplayScreen {
   pub fn hit(&mut self, input: TaikoInput, graphics: &Graphics, audio: &mut GameAudio) {
     let time = audio.position();
+    self.judge_hit(time, input, graphics, true);
+  }
 
-    match input {
-      TaikoInput::DonRight | TaikoInput::DonLeft => {
-        self.audio_controller.play_sound(self.don_hitsound.clone());
-      }
+  /// Inputs which are only judged to catch up after seeking in a replay aren't `live`, they neither play sounds nor show
+  /// up on the drum and the hit delta bar.
+  fn judge_hit(&mut self, time: Time, input: TaikoInput, graphics: &Graphics, live: bool) {
+    if live {
+      self.hit_drum_display.hit(input);
+    }
+
+    let hit = self.taiko_player.hit(time, input);
 
-      TaikoInput::KatLeft | TaikoInput::KatRight => {
-        self.audio_controller.play_sound(self.kat_hitsound.clone());
+    // Big circles also play their finish sound when they are hit
+    let (object, big) = match &hit {
+      Some((result, HitTarget::Object(idx))) => {
+        let big = self.taiko_player.beatmap().hit_objects[*idx].is_big();
+        (Some(*idx), big && result.judgement != Judgement::Miss)
       }
-    }
 
-    self.hit_drum_display.hit(input);
+      Some((_, HitTarget::DrumrollTick { object, .. } | HitTarget::Swell { object, .. })) => (Some(*object), false),
+      None => (None, false),
+    };
+
+    if live {
+      self.play_hitsound(input, object, big, time);
+    }
 
-    if let Some((result, hit_idx)) = self.taiko_player.hit(time, input) {
+    if let Some((result, target)) = hit {
       self.score_processor.feed(time, Some(input), result.judgement);
+      self.health_processor.feed(result.judgement);
       self.hit_result_display.update_hit_result(graphics, &mut self.sprite_renderer, result.judgement);
 
-      if result.judgement != Judgement::Miss {
-        self.taiko_renderer.set_hit(&graphics.queue, hit_idx, time);
+      match target {
+        HitTarget::Object(hit_idx) => {
+          if result.judgement != Judgement::Miss {
+            self.taiko_renderer.set_hit(&graphics.queue, hit_idx, time);
+          }
+
+          if live && result.hit_delta.abs() <= self.taiko_player.hit_window_150() {
+            self.ingame_overlay.hit(result.hit_delta);
+          }
+        }
+
+        HitTarget::DrumrollTick { object, tick } => {
+          self.taiko_renderer.set_tick_hit(&graphics.queue, object, tick, time);
+        }
+
+        HitTarget::Swell { object, remaining } => {
+          if remaining == 0 {
+            self.taiko_renderer.set_hit(&graphics.queue, object, time);
+          }
+        }
       }
+    }
+  }
 
-      if result.hit_delta.abs() <= self.taiko_player.hit_window_150() {
-        self.ingame_overlay.hit(result.hit_delta);
-      }
+  fn process_misses(&mut self, time: Time, graphics: &Graphics) {
+    while self.taiko_player.process_miss(time).is_some() {
+      self.score_processor.feed(time, None, Judgement::Miss);
+      self.health_processor.feed(Judgement::Miss);
+      self.hit_result_display.update_hit_result(graphics, &mut self.sprite_renderer, Judgement::Miss);
     }
   }
 
-  pub fn play(&mut self, beatmap: Beatmap, graphics: &Graphics, audio: &mut GameAudio) {
+  /// Judges the recorded inputs of the replay up to `time`, each at the time it was made rather than when the frame is
+  /// rendered.
+  fn replay_hits(&mut self, time: Time, graphics: &Graphics, live: bool) {
+    while let Some((hit_time, input)) = self.replay.as_mut().and_then(|replay| replay.next_hit(time)) {
+      self.process_misses(hit_time, graphics);
+      self.judge_hit(hit_time, input, graphics, live);
+    }
+  }
+
+  /// Plays the sound of the input using the samples of the hit object, or of the last one before `time` if nothing was
+  /// hit.
+  fn play_hitsound(&self, input: TaikoInput, object: Option<usize>, big: bool, time: Time) {
+    let hit_objects = &self.taiko_player.beatmap().hit_objects;
+    let object = match object {
+      Some(idx) => hit_objects.get(idx),
+      None => active_point(hit_objects, time, |x| x.time()),
+    };
+
+    let default_samples = HitSamples::default();
+    let samples = object.map(|x| x.samples()).unwrap_or(&default_samples);
+
+    let sound = match input {
+      TaikoInput::DonLeft | TaikoInput::DonRight => HitSound::Normal,
+      TaikoInput::KatLeft | TaikoInput::KatRight => HitSound::Clap,
+    };
+
+    self.hitsounds.play(&self.audio_controller, samples, sound);
+
+    if big {
+      self.hitsounds.play(&self.audio_controller, samples, HitSound::Finish);
+    }
+  }
+
+  /// Starts a new play of `beatmap`, with a new pattern if `mods` has Random.
+  pub fn play(&mut self, mut beatmap: Beatmap, mods: Mods, graphics: &Graphics, audio: &mut GameAudio) {
+    let mods = new_seed(mods);
+    mods.apply(&mut beatmap);
+
+    self.load(beatmap, mods, graphics, audio);
+  }
+
+  /// Loads `beatmap`, which `mods` were applied to already.
+  fn load(&mut self, beatmap: Beatmap, mods: Mods, graphics: &Graphics, audio: &mut GameAudio) {
     let config = audio.device().default_output_config().unwrap();
     let audio_path = beatmap.file_path.parent().unwrap().join(&beatmap.audio_path);
     let file = BufReader::new(File::open(audio_path).unwrap());
@@ -181,27 +318,70 @@ impl GameplayScreen {
     // FramelessSource is needed for a audio desync workaround, see https://github.com/RustAudio/rodio/issues/316
     let source = UniformSourceIterator::new(FramelessSource::new(source), config.channels(), config.sample_rate().0);
 
-    let end_time = beatmap.hit_objects.last().unwrap().time;
+    let end_time = beatmap.hit_objects.last().unwrap().end_time();
 
     audio.set_playing(false);
     audio.set_source(source);
     audio.set_length(end_time);
+    audio.apply_mods(mods);
+
+    self.hitsounds = HitsoundBank::load(&beatmap, audio);
 
     self.hit_result_display.reset(graphics, &mut self.sprite_renderer);
     self.taiko_renderer.load_beatmap(&graphics.device, beatmap.clone());
+    self.taiko_renderer.set_mods(mods);
+    self.health_processor = HealthProcessor::new(&beatmap, mods);
     self.taiko_player.play(beatmap);
-    std::mem::take(&mut self.score_processor);
+    self.score_processor = ScoreProcessor::new(mods);
+    self.mods = mods;
+    self.failed = false;
+    self.replay = None;
+    self.cinema = false;
 
     audio.set_position(Time::zero() - audio.lead_in);
     audio.set_playing(true);
   }
 
+  /// Plays `beatmap` with the recorded inputs and mods of `score` instead of the keyboard.
+  pub fn watch(&mut self, mut beatmap: Beatmap, score: Score, graphics: &Graphics, audio: &mut GameAudio) {
+    score.mods().apply(&mut beatmap);
+
+    self.load(beatmap, score.mods(), graphics, audio);
+    self.replay = Some(ReplayPlayer::new(score));
+  }
+
+  /// Plays `beatmap` with perfect generated inputs, `cinema` hides the HUD as well.
+  pub fn autoplay(
+    &mut self,
+    mut beatmap: Beatmap,
+    mods: Mods,
+    cinema: bool,
+    graphics: &Graphics,
+    audio: &mut GameAudio,
+  ) {
+    let mods = new_seed(mods);
+    mods.apply(&mut beatmap);
+
+    // Autoplay has to hit the objects the beatmap is played with
+    let replay = ReplayPlayer::autoplay(&beatmap);
+
+    self.load(beatmap, mods, graphics, audio);
+    self.replay = Some(replay);
+    self.cinema = cinema;
+  }
+
   pub fn reset(&mut self, graphics: &Graphics, audio: &mut GameAudio) {
     self.hit_result_display.reset(graphics, &mut self.sprite_renderer);
     self.taiko_renderer.restart_beatmap(&graphics.queue);
     self.taiko_player.reset();
 
-    std::mem::take(&mut self.score_processor);
+    self.score_processor = ScoreProcessor::new(self.mods);
+    self.health_processor = HealthProcessor::new(self.taiko_player.beatmap(), self.mods);
+    self.failed = false;
+
+    if let Some(replay) = &mut self.replay {
+      replay.rewind();
+    }
 
     audio.set_playing(false);
     audio.set_position(Time::zero() - audio.lead_in);
@@ -211,35 +391,114 @@ impl GameplayScreen {
   pub fn skip_break(&mut self, audio: &mut GameAudio, time: Time) {
     self.taiko_player.skip_break(audio, time);
   }
+
+  /// Jumps to `time` in the replay being watched. There is no way to undo judgements, so all of them are made again
+  /// from the start.
+  pub fn seek(&mut self, time: Time, graphics: &Graphics, audio: &mut GameAudio) {
+    let Some(replay) = &mut self.replay else {
+      return;
+    };
+
+    replay.rewind();
+
+    self.hit_result_display.reset(graphics, &mut self.sprite_renderer);
+    self.taiko_renderer.restart_beatmap(&graphics.queue);
+    self.taiko_player.reset();
+    self.score_processor = ScoreProcessor::new(self.mods);
+    self.health_processor = HealthProcessor::new(self.taiko_player.beatmap(), self.mods);
+
+    self.replay_hits(time, graphics, false);
+    self.process_misses(time, graphics);
+
+    audio.set_position(time);
+  }
 }
 
 impl GameplayScreen {
   pub fn prepare(&mut self, core: &mut Core<Client>, audio: &mut GameAudio, settings: &Settings) {
     let time = audio.position();
 
-    if self.taiko_player.has_ended(time, audio) {
-      // Finish the play if beatmap is over.
+    self.replay_hits(time, &core.graphics, true);
+    self.process_misses(time, &core.graphics);
+
+    // Replays and autoplay are only watched, they play on until the end
+    let no_fail = settings.gameplay.general.no_fail() || self.mods.contains(Mod::NoFail);
+    if !no_fail && self.health_processor.has_failed() {
+      self.score_processor.fail();
+    }
+
+    if !self.failed && self.replay.is_none() && self.score_processor.has_failed() {
+      self.failed = true;
+      audio.set_playing(false);
+
       let beatmap_hash = self.taiko_player.beatmap().hash();
       let score = self.score_processor.export(Timestamp::now(), settings.profile.user.username().clone());
-      self.event_bus.send(ClientEvent::ShowResultScreen { beatmap_hash, score });
+      self.event_bus.send(ClientEvent::Failed { beatmap_hash, score });
+    }
+
+    if !self.failed && self.taiko_player.has_ended(time, audio) {
+      // Finish the play if beatmap is over.
+      let beatmap_hash = self.taiko_player.beatmap().hash();
+
+      match self.replay.as_ref().map(ReplayPlayer::score) {
+        // The watched score is stored already, its results are shown as they were recorded
+        Some(Some(score)) => {
+          let score = score.clone();
+          self.event_bus.send(ClientEvent::ReplayEnded { beatmap_hash, score });
+        }
+
+        // Autoplay scores are never stored, so they are shown like replays as well
+        Some(None) => {
+          let score = self.score_processor.export(Timestamp::now(), String::from(AUTOPLAY_USERNAME));
+          self.event_bus.send(ClientEvent::ReplayEnded { beatmap_hash, score });
+        }
+
+        None => {
+          let score = self.score_processor.export(Timestamp::now(), settings.profile.user.username().clone());
+          self.event_bus.send(ClientEvent::ShowResultScreen { beatmap_hash, score });
+        }
+      }
     }
 
     self.hit_result_display.prepare(&core.graphics, &mut self.sprite_renderer);
     self.hit_drum_display.prepare(&core.graphics, &mut self.sprite_renderer);
 
-    while self.taiko_player.process_miss(time) {
-      self.score_processor.feed(time, None, Judgement::Miss);
-      self
-        .hit_result_display
-        .update_hit_result(&core.graphics, &mut self.sprite_renderer, Judgement::Miss);
+    self.taiko_renderer.set_combo(self.score_processor.curr_combo());
+    self.taiko_renderer.prepare(&core.graphics.queue, time);
+
+    let kiai_flash = self.taiko_player.beatmap().kiai_flash(time);
+    self.hit_drum_display.set_kiai_flash(&core.graphics, &mut self.sprite_renderer, kiai_flash);
+
+    if self.kiai_flash != kiai_flash {
+      self.kiai_flash = kiai_flash;
+
+      // The hit position pulses along with the beat during kiai time
+      self.sprite_renderer.mutate_sprite(&core.graphics.device, self.hit_pos_sprite, |model| {
+        let circle_size = 128.0 * self.gameplay_scale * (1.0 + 0.08 * kiai_flash);
+        model.scale = vec2(circle_size, circle_size);
+      });
     }
 
-    self.taiko_renderer.prepare(&core.graphics.queue, time);
+    if self.cinema {
+      return;
+    }
 
     let hit_window_150 = self.taiko_player.hit_window_150();
     let hit_window_300 = self.taiko_player.hit_window_300();
     let score_processor = &self.score_processor;
-    self.ingame_overlay.prepare(core, audio, score_processor, hit_window_150, hit_window_300);
+    let health = self.health_processor.health();
+    self.ingame_overlay.prepare(core, audio, score_processor, health, hit_window_150, hit_window_300);
+
+    if let Some((swell, remaining_hits)) = self.taiko_player.active_swell(time) {
+      self.swell_overlay.prepare(
+        core,
+        remaining_hits,
+        swell.required_hits,
+        self.hit_position_x_px,
+        self.hit_position_y_px,
+        self.gameplay_scale,
+      );
+    }
 
     let leniency = Time::from_ms(settings.gameplay.audio.break_leniency_end() as f64);
     match self.taiko_player.is_break(time, leniency) {
@@ -265,6 +524,17 @@ impl GameplayScreen {
 
       BreakState::None => {}
     }
+
+    if let Some(replay) = &self.replay {
+      let start = Time::zero() - audio.lead_in;
+      let username = replay.score().map_or(AUTOPLAY_USERNAME, Score::username);
+
+      let rate = self.mods.rate();
+
+      if let Some(position) = self.replay_overlay.prepare(core, audio, start, rate, username) {
+        self.seek(position, &core.graphics, audio);
+      }
+    }
   }
 
   pub fn render<'rpass>(&'rpass self, rpass: &mut wgpu::RenderPass<'rpass>) {
@@ -288,6 +558,7 @@ impl GameplayScreen {
       model.scale = vec2(circle_size, circle_size);
     });
 
+    self.update_playfield(device);
     self.taiko_renderer.set_gameplay_scale(queue, value);
   }
 
@@ -326,9 +597,29 @@ impl GameplayScreen {
       .hit_result_display
       .set_hit_position_y(device, &mut self.sprite_renderer, self.hit_position_y_px);
 
+    self.update_playfield(device);
     self.taiko_renderer.set_hit_position_y(queue, self.hit_position_y_px);
   }
 
+  pub fn set_playfield_height(&mut self, device: &wgpu::Device, value: f32) {
+    self.playfield_height = value;
+    self.update_playfield(device);
+  }
+
+  pub fn set_playfield_color(&mut self, device: &wgpu::Device, value: Color) {
+    self.sprite_renderer.mutate_sprite(device, self.playfield_sprite, |model| {
+      model.color = value;
+    });
+  }
+
+  /// Stretches the playfield band over the whole width of the screen at the hit position.
+  fn update_playfield(&mut self, device: &wgpu::Device) {
+    self.sprite_renderer.mutate_sprite(device, self.playfield_sprite, |model| {
+      model.position = vec2(0.0, self.hit_position_y_px);
+      model.scale = vec2(self.taiko_renderer.config.width, self.playfield_height * self.gameplay_scale);
+    });
+  }
+
   pub fn set_don_color(&mut self, device: &wgpu::Device, value: Color) {
     self.taiko_renderer.set_don_color(device, value);
   }
@@ -337,6 +628,14 @@ impl GameplayScreen {
     self.taiko_renderer.set_kat_color(device, value);
   }
 
+  pub fn set_drumroll_color(&mut self, device: &wgpu::Device, value: Color) {
+    self.taiko_renderer.set_drumroll_color(device, value);
+  }
+
+  pub fn set_swell_color(&mut self, device: &wgpu::Device, value: Color) {
+    self.taiko_renderer.set_swell_color(device, value);
+  }
+
   pub fn set_hit_animation_height(&mut self, device: &wgpu::Device, format: wgpu::TextureFormat, value: f64) {
     self.taiko_renderer.set_hit_animation_height(device, format, value);
   }
@@ -403,3 +702,12 @@ impl Drawable for GameplayScreen {
     self.sprite_renderer.rescale(device, queue, value);
   }
 }
+
+/// Rolls a new pattern if `mods` has Random, a replay keeps the seed it was recorded with instead.
+fn new_seed(mut mods: Mods) -> Mods {
+  if mods.contains(Mod::Random) {
+    mods.set_seed(Timestamp::now().as_nanosecond() as u32);
+  }
+
+  return mods;
+}
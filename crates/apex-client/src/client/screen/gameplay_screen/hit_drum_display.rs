@@ -21,6 +21,8 @@ pub struct HitDrumDisplay {
   last_hit_outer_right: Instant,
   last_hit_outer_left: Instant,
 
+  kiai_flash: f32,
+
   pos_x: f32,
   pos_y: f32,
   gameplay_scale: f32,
@@ -134,6 +136,8 @@ impl HitDrumDisplay {
       last_hit_outer_right: Instant::now(),
       last_hit_outer_left: Instant::now(),
 
+      kiai_flash: 0.0,
+
       pos_x,
       pos_y,
       gameplay_scale,
@@ -180,6 +184,22 @@ impl HitDrumDisplay {
     }
   }
 
+  /// Tints the drum on every beat of kiai time, see [`Beatmap::kiai_flash`].
+  ///
+  /// [`Beatmap::kiai_flash`]: crate::client::gameplay::beatmap::Beatmap::kiai_flash
+  pub fn set_kiai_flash(&mut self, graphics: &Graphics, sprite_renderer: &mut SpriteRenderer, value: f32) {
+    if self.kiai_flash == value {
+      return;
+    }
+
+    self.kiai_flash = value;
+
+    sprite_renderer.mutate_sprite(&graphics.device, self.drum_background_sprite, |model| {
+      model.color.g = 1.0 - 0.25 * value;
+      model.color.b = 1.0 - 0.6 * value;
+    });
+  }
+
   pub fn hit(&mut self, input: TaikoInput) {
     let now = Instant::now();
 
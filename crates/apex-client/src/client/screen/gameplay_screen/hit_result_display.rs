@@ -100,6 +100,11 @@ impl HitResultDisplay {
   }
 
   pub fn update_hit_result(&mut self, graphics: &Graphics, sprite_renderer: &mut SpriteRenderer, judgement: Judgement) {
+    // Bonus judgements don't have a sprite, keep showing the previous one.
+    if matches!(judgement, Judgement::DrumrollTick | Judgement::SwellTick | Judgement::Swell) {
+      return;
+    }
+
     self.last_hit_judgement_time = Instant::now();
     self.last_hit_judgement = judgement;
 
@@ -127,6 +132,8 @@ impl HitResultDisplay {
           model.uv_scale = size;
         });
       }
+
+      Judgement::DrumrollTick | Judgement::SwellTick | Judgement::Swell => {}
     }
   }
 
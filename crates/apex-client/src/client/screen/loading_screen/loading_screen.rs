@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use egui::Widget as _;
+
+use crate::client::gameplay::beatmap_cache::{BeatmapCache, ScanProgress};
+
+/// Shows the progress of the startup beatmap scan, covering the whole screen until the first beatmaps are found.
+pub struct LoadingScreen {
+  progress: Option<ScanProgress>,
+}
+
+impl LoadingScreen {
+  pub fn new() -> Self {
+    return Self { progress: None };
+  }
+
+  pub fn set_progress(&mut self, progress: ScanProgress) {
+    self.progress = Some(progress);
+  }
+
+  pub fn prepare(&mut self, ctx: &egui::Context, beatmap_cache: &BeatmapCache) {
+    if !beatmap_cache.is_scanning() {
+      return;
+    }
+
+    // Nothing to select yet, so there is nothing else to show
+    if beatmap_cache.is_empty() {
+      egui::CentralPanel::default() //
+        .frame(egui::Frame::none().fill(egui::Color32::from_gray(12)))
+        .show(ctx, |ui| {
+          ui.vertical_centered(|ui| {
+            ui.add_space(ui.available_height() / 2.0 - 48.0);
+            ui.set_max_width(320.0);
+
+            ui.label(egui::RichText::new("Loading beatmaps").heading().strong());
+            ui.add_space(8.0);
+
+            self.progress_ui(ui);
+          });
+        });
+
+      return;
+    }
+
+    egui::Window::new("beatmap_scan_progress")
+      .title_bar(false)
+      .resizable(false)
+      .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(16.0, -16.0))
+      .fixed_size(egui::vec2(240.0, 0.0))
+      .show(ctx, |ui| {
+        ui.label(egui::RichText::new("Loading beatmaps").strong());
+        ui.add_space(4.0);
+
+        self.progress_ui(ui);
+      });
+  }
+
+  fn progress_ui(&self, ui: &mut egui::Ui) {
+    let Some(progress) = self.progress.filter(|x| x.total > 0) else {
+      ui.spinner();
+      return;
+    };
+
+    egui::ProgressBar::new(progress.processed as f32 / progress.total as f32) //
+      .desired_width(ui.available_width())
+      .ui(ui);
+
+    ui.horizontal(|ui| {
+      ui.label(format!("{} / {} files", progress.processed, progress.total));
+
+      if progress.failed > 0 {
+        ui.label(egui::RichText::new(format!("{} failed", progress.failed)).color(ui.visuals().warn_fg_color));
+      }
+
+      if let Some(eta) = progress.eta() {
+        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+          ui.label(format_eta(eta));
+        });
+      }
+    });
+  }
+}
+
+fn format_eta(eta: Duration) -> String {
+  let seconds = eta.as_secs();
+
+  if seconds >= 60 {
+    return format!("{}m {:02}s left", seconds / 60, seconds % 60);
+  }
+
+  return format!("{}s left", seconds);
+}
@@ -1,5 +1,6 @@
 pub mod debug_screen;
 pub mod gameplay_screen;
+pub mod loading_screen;
 pub mod pause_screen;
 pub mod recording_screen;
 pub mod result_screen;
@@ -25,13 +25,27 @@ pub struct PauseScreen {
 
   clicked: bool,
   selected_button: SelectedButton,
+
+  /// Shown as the fail screen, a failed play can't be continued.
+  failed: bool,
+
+  /// Fail screen of a broken Perfect play, which has Retry preselected.
+  restart_prompt: bool,
 }
 
 impl PauseScreen {
   pub fn new(event_bus: EventBus<ClientEvent>) -> Self {
     let clicked = false;
     let selected_button = SelectedButton::None;
-    return Self { event_bus, clicked, selected_button };
+    let failed = false;
+    let restart_prompt = false;
+    return Self {
+      event_bus,
+      clicked,
+      selected_button,
+      failed,
+      restart_prompt,
+    };
   }
 
   #[allow(clippy::too_many_arguments)]
@@ -44,11 +58,36 @@ impl PauseScreen {
     game_state: &mut GameState,
     settings: &Settings,
   ) {
+    self.failed = *game_state == GameState::Failed;
+    self.restart_prompt &= self.failed;
+
     egui::CentralPanel::default() //
       .frame(egui::Frame::none().fill(egui::Color32::from_rgba_unmultiplied(0, 0, 0, 220)))
       .show(core.egui.ctx(), |ui| {
         const TEXT_SIZE: f32 = 32.0;
 
+        if self.failed {
+          let text = ui.painter().layout_no_wrap(
+            String::from("Failed"),
+            egui::FontId::proportional(TEXT_SIZE * 2.0),
+            egui::Color32::LIGHT_RED,
+          );
+
+          let pos = egui::pos2(ui.available_width() / 2.0, ui.available_height() / 2.0 - TEXT_SIZE * 3.0);
+          ui.painter().galley(pos - text.size() / 2.0, text, egui::Color32::LIGHT_RED);
+
+          if self.restart_prompt {
+            let text = ui.painter().layout_no_wrap(
+              String::from("Perfect broken, press Enter to restart"),
+              egui::FontId::proportional(TEXT_SIZE * 0.75),
+              egui::Color32::GRAY,
+            );
+
+            let pos = pos + egui::vec2(0.0, TEXT_SIZE * 1.5);
+            ui.painter().galley(pos - text.size() / 2.0, text, egui::Color32::GRAY);
+          }
+        }
+
         ui.with_layout(egui::Layout::top_down_justified(egui::Align::Center), |ui| {
           fn draw_button(
             ui: &egui::Ui,
@@ -109,11 +148,13 @@ impl PauseScreen {
           }
 
           let selected = &mut self.selected_button;
-          draw_button(ui, "Continue", -1.0, selected, SelectedButton::Continue, &mut self.clicked, || {
-            *game_state = GameState::Playing;
+          if !self.failed {
+            draw_button(ui, "Continue", -1.0, selected, SelectedButton::Continue, &mut self.clicked, || {
+              *game_state = GameState::Playing;
 
-            audio.set_playing(true);
-          });
+              audio.set_playing(true);
+            });
+          }
 
           draw_button(ui, "Retry", 0.0, selected, SelectedButton::Retry, &mut self.clicked, || {
             *game_state = GameState::Playing;
@@ -129,8 +170,8 @@ impl PauseScreen {
             let delay_adjusted_position = delay_adjusted_position.max(Time::zero());
 
             let selected = selection_screen.beatmap_selector().selected();
-            if let Some((_, beatmap)) = beatmap_cache.get_index(selected) {
-              Client::play_beatmap_audio_unchecked(audio, &beatmap.file_path, beatmap);
+            if let Some((_, beatmap_set)) = beatmap_cache.set_of(selected) {
+              Client::play_beatmap_audio_unchecked(audio, beatmap_set);
               audio.set_position(delay_adjusted_position);
             };
           });
@@ -146,6 +187,7 @@ impl PauseScreen {
     self.selected_button = match self.selected_button {
       SelectedButton::None => SelectedButton::Quit,
       SelectedButton::Continue => SelectedButton::Quit,
+      SelectedButton::Retry if self.failed => SelectedButton::Quit,
       SelectedButton::Retry => SelectedButton::Continue,
       SelectedButton::Quit => SelectedButton::Retry,
     };
@@ -155,12 +197,25 @@ impl PauseScreen {
     self.selected_button = match self.selected_button {
       SelectedButton::Continue => SelectedButton::Retry,
       SelectedButton::Retry => SelectedButton::Quit,
+      SelectedButton::Quit | SelectedButton::None if self.failed => SelectedButton::Retry,
       SelectedButton::Quit => SelectedButton::Continue,
       SelectedButton::None => SelectedButton::Continue,
     };
   }
 
+  /// Preselects Retry on the upcoming fail screen, so the play can be restarted right away.
+  pub fn offer_restart(&mut self) {
+    self.restart_prompt = true;
+    self.selected_button = SelectedButton::Retry;
+  }
+
   pub fn click(&mut self) {
     self.clicked = true;
   }
+
+  /// Leaves to the song selection, as if Quit was clicked.
+  pub fn quit(&mut self) {
+    self.selected_button = SelectedButton::Quit;
+    self.clicked = true;
+  }
 }
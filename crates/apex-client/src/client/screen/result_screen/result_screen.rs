@@ -1,28 +1,56 @@
-use apex_framework::core::Core;
+use apex_framework::{core::Core, event::EventBus};
 
 use crate::client::{
-  client::Client, gameplay::beatmap::Beatmap, score::score::Score, ui::play_results::PlayResultsView,
+  client::Client,
+  event::ClientEvent,
+  gameplay::{
+    beatmap::{Beatmap, BeatmapHash},
+    beatmap_cache::BeatmapInfo,
+  },
+  score::score::Score,
+  ui::play_results::PlayResultsView,
 };
 
 pub struct ResultScreen {
+  event_bus: EventBus<ClientEvent>,
   play_results: PlayResultsView,
+
+  /// Score which can be watched from here, missing when it has no recorded inputs.
+  replay: Option<(BeatmapHash, Score)>,
 }
 
 impl ResultScreen {
-  pub fn new() -> Self {
-    let play_results = PlayResultsView::new("", Beatmap::default(), Score::default());
+  pub fn new(event_bus: EventBus<ClientEvent>) -> Self {
+    let play_results = PlayResultsView::new("", BeatmapInfo::default(), Beatmap::default(), Score::default());
 
-    return Self { play_results };
+    return Self { event_bus, play_results, replay: None };
   }
 
-  pub fn set_score(&mut self, beatmap: Beatmap, score: Score) {
+  pub fn set_score(&mut self, beatmap_info: BeatmapInfo, beatmap: Beatmap, score: Score) {
     let bg = beatmap.file_path.parent().unwrap().join(&beatmap.bg_path);
     let bg = format!("file://{}", bg.to_str().unwrap());
 
-    self.play_results = PlayResultsView::new(bg, beatmap, score);
+    self.replay = (!score.hits().is_empty()).then(|| (beatmap.hash(), score.clone()));
+    self.play_results = PlayResultsView::new(bg, beatmap_info, beatmap, score);
   }
 
   pub fn prepare(&mut self, core: &mut Core<Client>) {
     self.play_results.prepare(core);
+
+    let Some((beatmap_hash, score)) = &self.replay else {
+      return;
+    };
+
+    egui::Area::new(egui::Id::new("watch_replay"))
+      .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-16.0, -16.0))
+      .show(core.egui.ctx(), |ui| {
+        let text = egui::RichText::new("Watch replay").size(18.0);
+        if ui.add(egui::Button::new(text).min_size(egui::vec2(160.0, 40.0))).clicked() {
+          self.event_bus.send(ClientEvent::WatchReplay {
+            beatmap_hash: *beatmap_hash,
+            score: score.clone(),
+          });
+        }
+      });
   }
 }
@@ -1,7 +1,12 @@
+use std::path::PathBuf;
+
 use crate::client::{
   client::Client,
   event::ClientEvent,
-  gameplay::{beatmap::BeatmapHash, beatmap_cache::BeatmapCache, beatmap_selector::BeatmapSelector},
+  gameplay::{
+    beatmap::BeatmapHash, beatmap_cache::BeatmapCache, beatmap_import_error::BeatmapImportError,
+    beatmap_selector::BeatmapSelector, mods::Mods,
+  },
   score::score_cache::ScoreCache,
   settings::Settings,
   ui::beatmap_selection::BeatmapSelectionView,
@@ -26,7 +31,7 @@ impl SelectionScreen {
     graphics: &Graphics,
     settings: &Settings,
   ) -> Self {
-    let beatmap_selection = BeatmapSelectionView::new(event_bus, beatmap_cache, clock, graphics, settings);
+    let beatmap_selection = BeatmapSelectionView::new(event_bus, clock, graphics, settings);
     let beatmap_selector = BeatmapSelector::new(beatmap_cache);
 
     return Self { beatmap_selection, beatmap_selector };
@@ -50,6 +55,21 @@ impl SelectionScreen {
     self.beatmap_selection.update_scores(score_cache, beatmap_hash);
   }
 
+  /// Shows an archive which could not be imported, until the user dismisses it.
+  pub fn import_failed(&mut self, archive: PathBuf, error: BeatmapImportError) {
+    self.beatmap_selection.import_failed(archive, error);
+  }
+
+  /// Mods picked for the next play.
+  pub fn mods(&self) -> Mods {
+    return self.beatmap_selection.mods();
+  }
+
+  /// Revision of the cache up to which every part of the screen applied changes.
+  pub fn seen_changes(&self) -> usize {
+    return self.beatmap_selector.seen_changes().min(self.beatmap_selection.seen_changes());
+  }
+
   pub fn beatmap_selector(&self) -> &BeatmapSelector {
     return &self.beatmap_selector;
   }
@@ -1,7 +1,8 @@
-use apex_framework::input::Input;
+use apex_framework::{event::EventBus, input::Input};
 
 use crate::client::{
   action::ClientAction,
+  event::ClientEvent,
   settings::{Settings, SettingsProxy},
   ui::game_settings::GameSettingsView,
 };
@@ -11,8 +12,8 @@ pub struct SettingsScreen {
 }
 
 impl SettingsScreen {
-  pub fn new() -> Self {
-    let game_settings = GameSettingsView::new();
+  pub fn new(event_bus: EventBus<ClientEvent>) -> Self {
+    let game_settings = GameSettingsView::new(event_bus);
 
     return Self { game_settings };
   }
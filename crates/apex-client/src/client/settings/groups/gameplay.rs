@@ -10,10 +10,22 @@ pub use super::super::ui as settings_ui;
 #[derive(SettingsGroup!, SmartDefault, Deserialize, Serialize, Debug, Clone)]
 #[serde(default)]
 pub struct GameplaySettingsGroup {
+  #[custom(ui(name = "General"))]
+  pub general: GameplayGeneralSettingsSubgroup,
+
   #[custom(ui(name = "Audio"))]
   pub audio: GameplayAudioSettingsSubgroup,
 }
 
+#[derive(SettingsSubgroup!, SmartDefault, Deserialize, Serialize, Debug, Clone)]
+#[serde(default)]
+pub struct GameplayGeneralSettingsSubgroup {
+  /// Keeps playing when the health drains below the pass threshold instead of failing
+  #[default = false]
+  #[custom(ui(name = "No Fail"))]
+  no_fail: bool,
+}
+
 #[derive(SettingsSubgroup!, SmartDefault, Deserialize, Serialize, Debug, Clone)]
 #[serde(default)]
 pub struct GameplayAudioSettingsSubgroup {
@@ -43,6 +55,8 @@ pub struct GameplayAudioSettingsSubgroup {
   break_leniency_end: u64,
 }
 
+impl GameplayGeneralSettingsSubgroupProxy for ClientSettingsProxy<'_, '_> {}
+
 impl GameplayAudioSettingsSubgroupProxy for ClientSettingsProxy<'_, '_> {
   fn update_lead_in(&mut self, value: &u64) {
     self.audio.lead_in = Time::from_ms(*value as f64);
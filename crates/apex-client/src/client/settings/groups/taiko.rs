@@ -37,6 +37,16 @@ pub struct TaikoGeneralSettingsSubgroup {
   #[custom(ui(name = "Vertical Position", range = 0.0 ..= 1.0))]
   hit_position_y_perc: f32,
 
+  /// Height of the playfield band behind the hit objects
+  #[default = 200.0]
+  #[custom(ui(name = "Playfield Height", range = 0.0 ..= 500.0))]
+  playfield_height: f32,
+
+  /// Color of the playfield band behind the hit objects
+  #[default(Color::new(0.05, 0.05, 0.05, 0.85))]
+  #[custom(ui(name = "Playfield Color"))]
+  playfield_color: Color,
+
   /// Color of the don hit object
   #[default(Color::new(0.92, 0.00, 0.27, 1.00))]
   #[custom(ui(name = "Don Color"))]
@@ -47,6 +57,16 @@ pub struct TaikoGeneralSettingsSubgroup {
   #[custom(ui(name = "Kat Color"))]
   kat_color: Color,
 
+  /// Color of the drumroll hit object
+  #[default(Color::new(0.99, 0.72, 0.02, 1.00))]
+  #[custom(ui(name = "Drumroll Color"))]
+  drumroll_color: Color,
+
+  /// Color of the swell hit object
+  #[default(Color::new(0.96, 0.45, 0.09, 1.00))]
+  #[custom(ui(name = "Swell Color"))]
+  swell_color: Color,
+
   /// Hit animation
   #[default = true]
   #[custom(ui(name = "Hit Animation"))]
@@ -70,6 +90,14 @@ impl TaikoGeneralSettingsSubgroupProxy for ClientSettingsProxy<'_, '_> {
     self.gameplay_screen.set_hit_position_y_perc(self.device, self.queue, *value);
   }
 
+  fn update_playfield_height(&mut self, value: &f32) {
+    self.gameplay_screen.set_playfield_height(self.device, *value);
+  }
+
+  fn update_playfield_color(&mut self, value: &Color) {
+    self.gameplay_screen.set_playfield_color(self.device, *value);
+  }
+
   fn update_don_color(&mut self, value: &Color) {
     self.gameplay_screen.set_don_color(self.device, *value);
   }
@@ -78,6 +106,14 @@ impl TaikoGeneralSettingsSubgroupProxy for ClientSettingsProxy<'_, '_> {
     self.gameplay_screen.set_kat_color(self.device, *value);
   }
 
+  fn update_drumroll_color(&mut self, value: &Color) {
+    self.gameplay_screen.set_drumroll_color(self.device, *value);
+  }
+
+  fn update_swell_color(&mut self, value: &Color) {
+    self.gameplay_screen.set_swell_color(self.device, *value);
+  }
+
   fn update_hit_animation(&mut self, value: &bool) {
     self.gameplay_screen.set_hit_animation_height(
       self.device,
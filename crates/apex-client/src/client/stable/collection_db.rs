@@ -0,0 +1,47 @@
+use std::{
+  fs::File,
+  io::{self, BufReader, Read},
+  path::Path,
+};
+
+use super::stable_reader::StableReader;
+
+#[derive(Debug, Clone)]
+pub struct StableCollection {
+  pub name: String,
+
+  /// MD5s of the `.osu` files in the collection.
+  pub beatmap_md5s: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CollectionDb {
+  pub version: i32,
+  pub collections: Vec<StableCollection>,
+}
+
+impl CollectionDb {
+  pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+    return Self::read(BufReader::new(File::open(path)?));
+  }
+
+  pub fn read(reader: impl Read) -> io::Result<Self> {
+    let mut reader = StableReader::new(reader);
+
+    let version = reader.read_i32()?;
+
+    let mut collections = Vec::new();
+    for _ in 0 .. reader.read_count()? {
+      let name = reader.read_string()?;
+
+      let mut beatmap_md5s = Vec::new();
+      for _ in 0 .. reader.read_count()? {
+        beatmap_md5s.push(reader.read_string()?);
+      }
+
+      collections.push(StableCollection { name, beatmap_md5s });
+    }
+
+    return Ok(Self { version, collections });
+  }
+}
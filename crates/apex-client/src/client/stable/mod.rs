@@ -0,0 +1,9 @@
+pub mod collection_db;
+pub mod osr;
+pub mod osr_error;
+pub mod osu_db;
+pub mod scores_db;
+pub mod stable_import;
+pub mod stable_import_error;
+pub mod stable_reader;
+pub mod stable_writer;
@@ -0,0 +1,179 @@
+use std::{
+  fmt::Write as _,
+  io::{Read, Write},
+};
+
+use apex_framework::time::time::Time;
+use xz2::{
+  read::XzDecoder,
+  stream::{LzmaOptions, Stream},
+  write::XzEncoder,
+};
+
+use crate::client::{gameplay::taiko_player::TaikoInput, score::score::Score};
+
+use super::{
+  osr_error::OsrError,
+  scores_db::read_score,
+  stable_import::{convert_score, TAIKO_MODE},
+  stable_reader::StableReader,
+  stable_writer::StableWriter,
+};
+
+/// osu!stable version written into exported replays.
+const REPLAY_VERSION: i32 = 20240101;
+
+/// Frames with this delta carry the RNG seed instead of any input.
+const SEED_FRAME_DELTA: i64 = -12345;
+
+/// How long keys stay pressed in exported replays, osu! only registers another press of a key after it is released.
+const KEY_HOLD_MS: i64 = 20;
+
+/// Frame key bits of every input, following how osu! maps the legacy mouse buttons to the drum.
+const INPUT_KEYS: [(TaikoInput, u8); 4] = [
+  (TaikoInput::DonLeft, 1 << 0),
+  (TaikoInput::KatLeft, 1 << 1),
+  (TaikoInput::DonRight, 1 << 2),
+  (TaikoInput::KatRight, 1 << 3),
+];
+
+/// A taiko `.osr` replay, `beatmap_md5` is the MD5 of the beatmap file the score was set on.
+#[derive(Debug, Clone)]
+pub struct OsrReplay {
+  pub beatmap_md5: String,
+  pub score: Score,
+}
+
+impl OsrReplay {
+  pub fn read(reader: impl Read) -> Result<Self, OsrError> {
+    let (stable_score, replay_data) = read_score(&mut StableReader::new(reader))?;
+
+    if stable_score.mode != TAIKO_MODE {
+      return Err(OsrError::UnsupportedMode(stable_score.mode));
+    }
+
+    let mut frames = String::new();
+    XzDecoder::new_stream(replay_data.as_slice(), Stream::new_lzma_decoder(u64::MAX)?).read_to_string(&mut frames)?;
+
+    let mut score = convert_score(&stable_score);
+    score.hits = decode_frames(&frames)?;
+
+    return Ok(Self {
+      beatmap_md5: stable_score.beatmap_md5,
+      score,
+    });
+  }
+
+  pub fn write(&self, writer: impl Write) -> Result<(), OsrError> {
+    let stream = Stream::new_lzma_encoder(&LzmaOptions::new_preset(6)?)?;
+    let mut encoder = XzEncoder::new_stream(Vec::new(), stream);
+    encoder.write_all(encode_frames(self.score.hits()).as_bytes())?;
+    let replay_data = encoder.finish()?;
+
+    let score = &self.score;
+    let mut writer = StableWriter::new(writer);
+
+    writer.write_u8(TAIKO_MODE)?;
+    writer.write_i32(REPLAY_VERSION)?;
+    writer.write_string(&self.beatmap_md5)?;
+    writer.write_string(score.username())?;
+
+    // The replay hash is only checked for online scores
+    writer.write_string("")?;
+
+    // 300s, 100s, 50s, gekis, katus and misses, taiko has no 50s and the other two only matter for big notes
+    for count in [score.result_300s(), score.result_150s(), 0, 0, 0, score.result_misses()] {
+      writer.write_u16(count.try_into().unwrap_or(u16::MAX))?;
+    }
+
+    writer.write_i32(score.score_points().try_into().unwrap_or(i32::MAX))?;
+    writer.write_u16(score.max_combo().try_into().unwrap_or(u16::MAX))?;
+    writer.write_bool(score.result_misses() == 0)?;
+
+    writer.write_i32(score.mods().legacy_bits() as i32)?;
+
+    // No life bar graph
+    writer.write_string("")?;
+
+    writer.write_datetime(score.date().as_millisecond())?;
+    writer.write_i32(replay_data.len() as i32)?;
+    writer.write_bytes(&replay_data)?;
+
+    // Online score id
+    writer.write_i64(0)?;
+
+    return Ok(());
+  }
+}
+
+/// Turns key presses into `time|x|y|keys` frames, `hits` have to be in chronological order.
+fn encode_frames(hits: &[(Time, TaikoInput)]) -> String {
+  // Inputs in the same millisecond, e.g. both dons of a big note, share a frame
+  let mut presses = Vec::<(i64, u8)>::new();
+  for (time, input) in hits {
+    let key = INPUT_KEYS.iter().find(|(x, _)| x == input).map_or(0, |(_, key)| *key);
+
+    match presses.last_mut() {
+      Some((last, keys)) if *last == time.to_ms() => *keys |= key,
+      _ => presses.push((time.to_ms(), key)),
+    }
+  }
+
+  let mut frames = String::new();
+  let mut prev_time = 0;
+  let mut push_frame = |time: i64, keys: u8| {
+    write!(frames, "{}|256|-500|{},", time - prev_time, keys).unwrap();
+    prev_time = time;
+  };
+
+  for (idx, (time, keys)) in presses.iter().copied().enumerate() {
+    push_frame(time, keys);
+
+    let release = match presses.get(idx + 1) {
+      Some((next_time, _)) => (time + KEY_HOLD_MS).min(next_time - 1),
+      None => time + KEY_HOLD_MS,
+    };
+
+    if release > time {
+      push_frame(release, 0);
+    }
+  }
+
+  return frames;
+}
+
+/// Collects the key presses of `time|x|y|keys` frames, where time is relative to the previous frame.
+fn decode_frames(frames: &str) -> Result<Vec<(Time, TaikoInput)>, OsrError> {
+  let mut hits = Vec::new();
+  let mut time = 0;
+  let mut pressed = 0;
+
+  for frame in frames.split(',').map(str::trim).filter(|x| !x.is_empty()) {
+    let invalid_frame = || OsrError::InvalidFrame(frame.to_owned());
+
+    let values = frame.split('|').collect::<Vec<_>>();
+    let [delta, _x, _y, keys] = values.as_slice() else {
+      return Err(invalid_frame());
+    };
+
+    // Some clients write fractional values
+    let delta = delta.parse::<f64>().map_err(|_| invalid_frame())? as i64;
+    let keys = keys.parse::<f64>().map_err(|_| invalid_frame())? as u32 as u8 & 0x0f;
+
+    if delta == SEED_FRAME_DELTA {
+      continue;
+    }
+
+    time += delta;
+
+    for (input, key) in INPUT_KEYS {
+      if keys & key != 0 && pressed & key == 0 {
+        hits.push((Time::from_ms(time as f64), input));
+      }
+    }
+
+    pressed = keys;
+  }
+
+  return Ok(hits);
+}
@@ -0,0 +1,16 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum OsrError {
+  #[error("Failed to read or write replay: {0}")]
+  Io(#[from] std::io::Error),
+
+  #[error("Invalid LZMA stream: {0}")]
+  Lzma(#[from] xz2::stream::Error),
+
+  #[error("Unsupported game mode {0}, only taiko replays can be imported")]
+  UnsupportedMode(u8),
+
+  #[error("Invalid replay frame {0:?}")]
+  InvalidFrame(String),
+}
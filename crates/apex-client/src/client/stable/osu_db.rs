@@ -0,0 +1,134 @@
+use std::{
+  fs::File,
+  io::{self, BufReader, Read},
+  path::Path,
+};
+
+use super::stable_reader::{invalid_data, StableReader};
+
+/// Oldest supported version, difficulty settings are stored as floats since then.
+const MIN_VERSION: i32 = 20140609;
+
+/// Beatmap entries are no longer prefixed by their size since this version.
+const UNSIZED_ENTRIES_VERSION: i32 = 20191106;
+
+/// Star ratings are stored as floats instead of doubles since this version.
+const FLOAT_STAR_RATINGS_VERSION: i32 = 20250107;
+
+/// A beatmap from `osu!.db`, only the fields which are needed for importing are kept.
+#[derive(Debug, Clone)]
+pub struct OsuDbBeatmap {
+  pub artist: String,
+  pub title: String,
+  pub creator: String,
+  pub difficulty: String,
+  pub audio_file: String,
+
+  /// Hex encoded MD5 of the `.osu` file, which is how scores and collections refer to beatmaps.
+  pub md5: String,
+  pub osu_file: String,
+
+  /// Directory inside `Songs/`.
+  pub folder_name: String,
+
+  pub beatmapset_id: i32,
+  pub mode: u8,
+}
+
+/// The beatmap list of an osu!stable installation.
+#[derive(Debug, Clone)]
+pub struct OsuDb {
+  pub version: i32,
+  pub player_name: String,
+  pub beatmaps: Vec<OsuDbBeatmap>,
+}
+
+impl OsuDb {
+  pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+    return Self::read(BufReader::new(File::open(path)?));
+  }
+
+  pub fn read(reader: impl Read) -> io::Result<Self> {
+    let mut reader = StableReader::new(reader);
+
+    let version = reader.read_i32()?;
+    if version < MIN_VERSION {
+      return Err(invalid_data(format!("Unsupported osu!.db version {}", version)));
+    }
+
+    // Folder count, whether the account is unlocked and when it will be
+    reader.skip(4 + 1 + 8)?;
+    let player_name = reader.read_string()?;
+
+    let mut beatmaps = Vec::new();
+    for _ in 0 .. reader.read_count()? {
+      beatmaps.push(Self::read_beatmap(&mut reader, version)?);
+    }
+
+    return Ok(Self { version, player_name, beatmaps });
+  }
+
+  fn read_beatmap(reader: &mut StableReader<impl Read>, version: i32) -> io::Result<OsuDbBeatmap> {
+    if version < UNSIZED_ENTRIES_VERSION {
+      reader.skip(4)?;
+    }
+
+    let artist = reader.read_string()?;
+    let _artist_unicode = reader.read_string()?;
+    let title = reader.read_string()?;
+    let _title_unicode = reader.read_string()?;
+    let creator = reader.read_string()?;
+    let difficulty = reader.read_string()?;
+    let audio_file = reader.read_string()?;
+    let md5 = reader.read_string()?;
+    let osu_file = reader.read_string()?;
+
+    // Ranked status, object counts, modification date, AR, CS, HP, OD and slider velocity
+    reader.skip(1 + 2 * 3 + 8 + 4 * 4 + 8)?;
+
+    // Star ratings of every mod combination for each mode, stored as int and float/double pairs
+    let rating_size = if version >= FLOAT_STAR_RATINGS_VERSION { 1 + 4 + 1 + 4 } else { 1 + 4 + 1 + 8 };
+    for _ in 0 .. 4 {
+      let count = reader.read_count()?;
+      reader.skip(count * rating_size)?;
+    }
+
+    // Drain, total and preview time, followed by the timing points with their bpm, offset and whether they are
+    // inherited
+    reader.skip(4 * 3)?;
+    let timing_points = reader.read_count()?;
+    reader.skip(timing_points * (8 + 8 + 1))?;
+
+    let _difficulty_id = reader.read_i32()?;
+    let beatmapset_id = reader.read_i32()?;
+
+    // Thread id, grades of each mode, local offset and stack leniency
+    reader.skip(4 + 4 + 2 + 4)?;
+    let mode = reader.read_u8()?;
+
+    let _source = reader.read_string()?;
+    let _tags = reader.read_string()?;
+    reader.skip(2)?;
+    let _title_font = reader.read_string()?;
+
+    // Unplayed, last played and whether it is an osz2
+    reader.skip(1 + 8 + 1)?;
+    let folder_name = reader.read_string()?;
+
+    // Last online check, the ignore and disable flags, last modification time and mania scroll speed
+    reader.skip(8 + 5 + 4 + 1)?;
+
+    return Ok(OsuDbBeatmap {
+      artist,
+      title,
+      creator,
+      difficulty,
+      audio_file,
+      md5,
+      osu_file,
+      folder_name,
+      beatmapset_id,
+      mode,
+    });
+  }
+}
@@ -0,0 +1,117 @@
+use std::{
+  fs::File,
+  io::{self, BufReader, Read},
+  path::Path,
+};
+
+use super::stable_reader::StableReader;
+
+/// Mod which stores an additional accuracy value with the score.
+const TARGET_PRACTICE: i32 = 1 << 23;
+
+/// A local score from `scores.db` or the header of an `.osr` replay, which share the same layout.
+#[derive(Debug, Clone)]
+pub struct StableScore {
+  pub mode: u8,
+  pub beatmap_md5: String,
+  pub player_name: String,
+
+  pub count_300: u16,
+  pub count_100: u16,
+  pub count_50: u16,
+  pub count_geki: u16,
+  pub count_katu: u16,
+  pub count_miss: u16,
+
+  pub score: i32,
+  pub max_combo: u16,
+  pub perfect: bool,
+  pub mods: i32,
+
+  /// Milliseconds since the unix epoch.
+  pub timestamp: i64,
+}
+
+#[derive(Debug, Clone)]
+pub struct ScoresDb {
+  pub version: i32,
+  pub scores: Vec<StableScore>,
+}
+
+impl ScoresDb {
+  pub fn from_path(path: impl AsRef<Path>) -> io::Result<Self> {
+    return Self::read(BufReader::new(File::open(path)?));
+  }
+
+  pub fn read(reader: impl Read) -> io::Result<Self> {
+    let mut reader = StableReader::new(reader);
+
+    let version = reader.read_i32()?;
+
+    let mut scores = Vec::new();
+    for _ in 0 .. reader.read_count()? {
+      // Scores repeat the MD5 of their beatmap anyway
+      let _beatmap_md5 = reader.read_string()?;
+
+      for _ in 0 .. reader.read_count()? {
+        // The replay itself is stored separately
+        let (score, _) = read_score(&mut reader)?;
+        scores.push(score);
+      }
+    }
+
+    return Ok(Self { version, scores });
+  }
+}
+
+/// Reads a score along with its LZMA compressed replay data, which is missing for the scores in `scores.db`.
+pub(crate) fn read_score(reader: &mut StableReader<impl Read>) -> io::Result<(StableScore, Vec<u8>)> {
+  let mode = reader.read_u8()?;
+  let _version = reader.read_i32()?;
+  let beatmap_md5 = reader.read_string()?;
+  let player_name = reader.read_string()?;
+  let _replay_md5 = reader.read_string()?;
+
+  let count_300 = reader.read_u16()?;
+  let count_100 = reader.read_u16()?;
+  let count_50 = reader.read_u16()?;
+  let count_geki = reader.read_u16()?;
+  let count_katu = reader.read_u16()?;
+  let count_miss = reader.read_u16()?;
+
+  let score = reader.read_i32()?;
+  let max_combo = reader.read_u16()?;
+  let perfect = reader.read_bool()?;
+  let mods = reader.read_i32()?;
+
+  let _life_bar = reader.read_string()?;
+  let timestamp = reader.read_datetime()?;
+
+  let replay_len = reader.read_count()?;
+  let replay_data = reader.read_bytes(replay_len)?;
+
+  let _online_id = reader.read_i64()?;
+
+  if mods & TARGET_PRACTICE != 0 {
+    let _accuracy = reader.read_f64()?;
+  }
+
+  let stable_score = StableScore {
+    mode,
+    beatmap_md5,
+    player_name,
+    count_300,
+    count_100,
+    count_50,
+    count_geki,
+    count_katu,
+    count_miss,
+    score,
+    max_combo,
+    perfect,
+    mods,
+    timestamp,
+  };
+
+  return Ok((stable_score, replay_data));
+}
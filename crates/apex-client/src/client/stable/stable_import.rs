@@ -0,0 +1,255 @@
+use std::path::{Component, Path, PathBuf};
+
+use ahash::{AHashMap, AHashSet};
+use jiff::Timestamp;
+use log::warn;
+
+use crate::client::{
+  gameplay::{beatmap::BeatmapHash, beatmap_cache::BeatmapCache, mods::Mods},
+  score::{grades::Grade, score::Score},
+};
+
+use super::{
+  collection_db::CollectionDb,
+  osu_db::{OsuDb, OsuDbBeatmap},
+  scores_db::{ScoresDb, StableScore},
+  stable_import_error::StableImportError,
+};
+
+pub(crate) const TAIKO_MODE: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SongsTransfer {
+  /// Set directories are symlinked, so both clients share the files. Falls back to copying where symlinks fail.
+  Link,
+  Copy,
+}
+
+/// Everything taken over from an osu!stable installation. Scores and collections refer to beatmaps by the MD5 of
+/// their files, they are matched to the library with [`StableImport::resolve`] once the new sets are loaded.
+#[derive(Debug, Default)]
+pub struct StableImport {
+  /// Set directories which were added to the library.
+  pub directories: Vec<PathBuf>,
+
+  /// Taiko scores along with the MD5 of their beatmap.
+  pub scores: Vec<(String, Score)>,
+  pub collections: Vec<(String, Vec<String>)>,
+}
+
+/// Scores and collections of a [`StableImport`] matched to the beatmaps of the library.
+#[derive(Debug, Default)]
+pub struct ResolvedImport {
+  pub scores: Vec<(BeatmapHash, Score)>,
+  pub collections: Vec<(String, Vec<BeatmapHash>)>,
+
+  /// Taiko scores and collection entries whose beatmap is not in the library.
+  pub unmatched: usize,
+}
+
+/// Imports `Songs/` along with the taiko scores and collections of the osu!stable installation in `osu_dir` into
+/// `library`, directories which exist in the library already are left alone.
+pub fn import(osu_dir: &Path, library: &Path, transfer: SongsTransfer) -> Result<StableImport, StableImportError> {
+  let osu_db_path = osu_dir.join("osu!.db");
+  if !osu_db_path.is_file() {
+    return Err(StableImportError::NotInstalled(osu_dir.to_owned()));
+  }
+
+  let osu_db =
+    OsuDb::from_path(osu_db_path).map_err(|source| StableImportError::Database { name: "osu!.db", source })?;
+
+  // Neither exists until the first score is set or the first collection is created
+  let collection_db_path = osu_dir.join("collection.db");
+  let collections = match collection_db_path.is_file() {
+    true => {
+      CollectionDb::from_path(collection_db_path)
+        .map_err(|source| StableImportError::Database { name: "collection.db", source })?
+        .collections
+    }
+    false => Vec::new(),
+  };
+
+  let scores_db_path = osu_dir.join("scores.db");
+  let scores = match scores_db_path.is_file() {
+    true => {
+      ScoresDb::from_path(scores_db_path)
+        .map_err(|source| StableImportError::Database { name: "scores.db", source })?
+        .scores
+    }
+    false => Vec::new(),
+  };
+
+  let songs = osu_dir.join("Songs");
+  let directories = transfer_songs(&songs, library, &osu_db.beatmaps, transfer)?;
+
+  return Ok(StableImport {
+    directories,
+    scores: scores
+      .iter()
+      .filter(|x| x.mode == TAIKO_MODE)
+      .map(|x| (x.beatmap_md5.clone(), convert_score(x)))
+      .collect(),
+    collections: collections.into_iter().map(|x| (x.name, x.beatmap_md5s)).collect(),
+  });
+}
+
+impl StableImport {
+  /// Matches the scores and collections to the beatmaps in `beatmap_cache`, which has to contain the new sets already.
+  pub fn resolve(self, beatmap_cache: &BeatmapCache) -> ResolvedImport {
+    let mut resolver = HashResolver::new(beatmap_cache);
+    let mut resolved = ResolvedImport::default();
+
+    for (md5, score) in self.scores {
+      match resolver.resolve(&md5) {
+        Some(hash) => resolved.scores.push((hash, score)),
+        None => resolved.unmatched += 1,
+      }
+    }
+
+    for (name, md5s) in self.collections {
+      let mut hashes = Vec::with_capacity(md5s.len());
+
+      for md5 in &md5s {
+        match resolver.resolve(md5) {
+          Some(hash) => hashes.push(hash),
+          None => resolved.unmatched += 1,
+        }
+      }
+
+      resolved.collections.push((name, hashes));
+    }
+
+    return resolved;
+  }
+}
+
+fn transfer_songs(
+  songs: &Path,
+  library: &Path,
+  beatmaps: &[OsuDbBeatmap],
+  transfer: SongsTransfer,
+) -> Result<Vec<PathBuf>, StableImportError> {
+  std::fs::create_dir_all(library)?;
+
+  let mut seen = AHashSet::new();
+  let mut directories = Vec::new();
+
+  for folder_name in beatmaps.iter().map(|x| x.folder_name.as_str()) {
+    if !seen.insert(folder_name) {
+      continue;
+    }
+
+    // The name comes from the database, it must not point anywhere but a single directory inside `Songs/`
+    let mut components = Path::new(folder_name).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+      warn!("Skipping beatmap directory with an invalid name: {:?}", folder_name);
+      continue;
+    }
+
+    let source = songs.join(folder_name);
+    let target = library.join(folder_name);
+
+    if !source.is_dir() || target.symlink_metadata().is_ok() {
+      continue;
+    }
+
+    if transfer == SongsTransfer::Link {
+      match link_dir(&source, &target) {
+        Ok(()) => {
+          directories.push(target);
+          continue;
+        }
+
+        Err(e) => warn!("Failed to link {:?}, copying it instead: {}", source, e),
+      }
+    }
+
+    copy_dir(&source, &target)?;
+    directories.push(target);
+  }
+
+  return Ok(directories);
+}
+
+#[cfg(unix)]
+fn link_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+  return std::os::unix::fs::symlink(source.canonicalize()?, target);
+}
+
+#[cfg(windows)]
+fn link_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+  return std::os::windows::fs::symlink_dir(source.canonicalize()?, target);
+}
+
+#[cfg(not(any(unix, windows)))]
+fn link_dir(_source: &Path, _target: &Path) -> std::io::Result<()> {
+  return Err(std::io::ErrorKind::Unsupported.into());
+}
+
+fn copy_dir(source: &Path, target: &Path) -> std::io::Result<()> {
+  std::fs::create_dir_all(target)?;
+
+  for entry in std::fs::read_dir(source)? {
+    let entry = entry?;
+    let path = entry.path();
+
+    if path.is_dir() {
+      copy_dir(&path, &target.join(entry.file_name()))?;
+    } else {
+      std::fs::copy(&path, target.join(entry.file_name()))?;
+    }
+  }
+
+  return Ok(());
+}
+
+/// Finds the hash of a beatmap by the MD5 of its file, scores and collections often refer to the same beatmaps.
+struct HashResolver<'a> {
+  beatmap_cache: &'a BeatmapCache,
+  resolved: AHashMap<String, Option<BeatmapHash>>,
+}
+
+impl<'a> HashResolver<'a> {
+  fn new(beatmap_cache: &'a BeatmapCache) -> Self {
+    return Self { beatmap_cache, resolved: AHashMap::new() };
+  }
+
+  fn resolve(&mut self, md5: &str) -> Option<BeatmapHash> {
+    if let Some(hash) = self.resolved.get(md5) {
+      return *hash;
+    }
+
+    let hash = self.beatmap_cache.find_by_md5(md5).map(|(hash, _)| hash);
+    self.resolved.insert(md5.to_owned(), hash);
+    return hash;
+  }
+}
+
+pub(crate) fn convert_score(score: &StableScore) -> Score {
+  // Taiko calls its results GREAT, GOOD and MISS, which are stored as 300s and 100s
+  let result_300 = score.count_300 as usize;
+  let result_150 = score.count_100 as usize;
+  let result_miss = score.count_miss as usize;
+
+  let total = (result_300 + result_150 + result_miss).max(1) as f32;
+  let mods = Mods::from_legacy_bits(score.mods as u32);
+
+  return Score {
+    date: Timestamp::from_millisecond(score.timestamp).unwrap_or_default(),
+    username: score.player_name.clone(),
+    score_points: score.score.max(0) as usize,
+    result_300,
+    result_150,
+    result_miss,
+    // osu!stable doesn't store the combo at the end of the play, the max combo is the closest thing to it
+    last_combo: score.max_combo as usize,
+    max_combo: score.max_combo as usize,
+    accuracy: (result_300 as f32 + result_150 as f32 * 0.5) / total,
+    grade: Grade::from_osu_stable(result_300, result_150, result_miss),
+    hits: Vec::new(),
+    // osu!stable only keeps passed plays
+    failed: false,
+    mods,
+    rate: mods.rate(),
+  };
+}
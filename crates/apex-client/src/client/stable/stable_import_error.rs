@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum StableImportError {
+  #[error("{0:?} is not an osu!stable installation, it has no osu!.db")]
+  NotInstalled(PathBuf),
+
+  #[error("Failed to read {name}: {source}")]
+  Database { name: &'static str, source: std::io::Error },
+
+  #[error("Failed to transfer beatmaps: {0}")]
+  Io(#[from] std::io::Error),
+}
@@ -0,0 +1,103 @@
+use std::io::{self, Read};
+
+/// .NET `DateTime` ticks at the unix epoch, a tick is 100ns since 0001-01-01.
+pub(crate) const UNIX_EPOCH_TICKS: i64 = 621_355_968_000_000_000;
+
+macro_rules! read_le {
+  ( $($name:ident: $ty:ty),+ $(,)? ) => { $(
+    pub fn $name(&mut self) -> io::Result<$ty> {
+      let mut bytes = [0; std::mem::size_of::<$ty>()];
+      self.reader.read_exact(&mut bytes)?;
+      return Ok(<$ty>::from_le_bytes(bytes));
+    }
+  )+ };
+}
+
+pub(crate) fn invalid_data(reason: impl Into<String>) -> io::Error {
+  return io::Error::new(io::ErrorKind::InvalidData, reason.into());
+}
+
+/// Reads the primitive types osu!stable databases are made of, numbers are little endian.
+pub struct StableReader<R> {
+  reader: R,
+}
+
+impl<R: Read> StableReader<R> {
+  pub fn new(reader: R) -> Self {
+    return Self { reader };
+  }
+
+  read_le! {
+    read_u8: u8,
+    read_u16: u16,
+    read_i32: i32,
+    read_i64: i64,
+    read_f64: f64,
+  }
+
+  pub fn read_bool(&mut self) -> io::Result<bool> {
+    return Ok(self.read_u8()? != 0);
+  }
+
+  pub fn read_uleb128(&mut self) -> io::Result<u64> {
+    let mut value = 0;
+
+    for shift in (0 .. 64).step_by(7) {
+      let byte = self.read_u8()?;
+      value |= ((byte & 0x7f) as u64) << shift;
+
+      if byte & 0x80 == 0 {
+        return Ok(value);
+      }
+    }
+
+    return Err(invalid_data("ULEB128 value is too long"));
+  }
+
+  /// Strings are either absent (`0x00`) or `0x0b` followed by the ULEB128 length and UTF-8 bytes, absent strings are
+  /// read as empty ones.
+  pub fn read_string(&mut self) -> io::Result<String> {
+    match self.read_u8()? {
+      0x00 => return Ok(String::new()),
+      0x0b => {}
+      marker => return Err(invalid_data(format!("Invalid string marker {:#04x}", marker))),
+    }
+
+    let len = self.read_uleb128()?;
+    let bytes = self.read_bytes(len)?;
+
+    return String::from_utf8(bytes).map_err(|_| invalid_data("String is not valid UTF-8"));
+  }
+
+  /// Reads a `DateTime` as milliseconds since the unix epoch.
+  pub fn read_datetime(&mut self) -> io::Result<i64> {
+    let ticks = self.read_i64()?;
+    return Ok((ticks - UNIX_EPOCH_TICKS) / 10_000);
+  }
+
+  pub fn read_bytes(&mut self, len: u64) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    (&mut self.reader).take(len).read_to_end(&mut bytes)?;
+
+    if bytes.len() as u64 != len {
+      return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    return Ok(bytes);
+  }
+
+  pub fn skip(&mut self, len: u64) -> io::Result<()> {
+    let skipped = io::copy(&mut (&mut self.reader).take(len), &mut io::sink())?;
+
+    if skipped != len {
+      return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+
+    return Ok(());
+  }
+
+  /// Reads a count prefix, negative counts are treated as empty lists.
+  pub fn read_count(&mut self) -> io::Result<u64> {
+    return Ok(self.read_i32()?.max(0) as u64);
+  }
+}
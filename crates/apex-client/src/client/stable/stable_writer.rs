@@ -0,0 +1,66 @@
+use std::io::{self, Write};
+
+use super::stable_reader::UNIX_EPOCH_TICKS;
+
+macro_rules! write_le {
+  ( $($name:ident: $ty:ty),+ $(,)? ) => { $(
+    pub fn $name(&mut self, value: $ty) -> io::Result<()> {
+      return self.writer.write_all(&value.to_le_bytes());
+    }
+  )+ };
+}
+
+/// Writes the primitive types of osu!stable files, the counterpart of [`super::stable_reader::StableReader`].
+pub struct StableWriter<W> {
+  writer: W,
+}
+
+impl<W: Write> StableWriter<W> {
+  pub fn new(writer: W) -> Self {
+    return Self { writer };
+  }
+
+  pub fn into_inner(self) -> W {
+    return self.writer;
+  }
+
+  write_le! {
+    write_u8: u8,
+    write_u16: u16,
+    write_i32: i32,
+    write_i64: i64,
+  }
+
+  pub fn write_bool(&mut self, value: bool) -> io::Result<()> {
+    return self.write_u8(value as u8);
+  }
+
+  pub fn write_uleb128(&mut self, mut value: u64) -> io::Result<()> {
+    while value >= 0x80 {
+      self.write_u8((value & 0x7f) as u8 | 0x80)?;
+      value >>= 7;
+    }
+
+    return self.write_u8(value as u8);
+  }
+
+  /// Empty strings are written as absent ones, like osu!stable does.
+  pub fn write_string(&mut self, value: &str) -> io::Result<()> {
+    if value.is_empty() {
+      return self.write_u8(0x00);
+    }
+
+    self.write_u8(0x0b)?;
+    self.write_uleb128(value.len() as u64)?;
+    return self.writer.write_all(value.as_bytes());
+  }
+
+  /// Writes milliseconds since the unix epoch as a `DateTime`.
+  pub fn write_datetime(&mut self, millis: i64) -> io::Result<()> {
+    return self.write_i64(millis * 10_000 + UNIX_EPOCH_TICKS);
+  }
+
+  pub fn write_bytes(&mut self, bytes: &[u8]) -> io::Result<()> {
+    return self.writer.write_all(bytes);
+  }
+}
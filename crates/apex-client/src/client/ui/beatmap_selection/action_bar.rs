@@ -7,6 +7,8 @@ use tap::Tap;
 
 use crate::client::event::ClientEvent;
 
+use super::mod_select::ModSelect;
+
 pub struct ActionBar {
   event_bus: EventBus<ClientEvent>,
 
@@ -20,7 +22,7 @@ impl ActionBar {
     return Self { event_bus, last_state };
   }
 
-  pub fn prepare(&mut self, ui: &mut egui::Ui, clock: &mut impl AbstractClock) {
+  pub fn prepare(&mut self, ui: &mut egui::Ui, clock: &mut impl AbstractClock, mod_select: &mut ModSelect) {
     egui::Frame::window(ui.style())
       .outer_margin(egui::Margin {
         left: 12.0,
@@ -111,6 +113,26 @@ impl ActionBar {
               })
           });
 
+        egui::Separator::default().vertical().spacing(0.0).ui(ui);
+
+        egui::Frame::none()
+          .outer_margin(egui::Margin::ZERO)
+          .inner_margin(egui::Margin::symmetric(16.0, 16.0))
+          .show(ui, |ui| {
+            let mods = mod_select.mods();
+            let label = if mods.is_empty() { String::from("Mods") } else { mods.to_string() };
+            let text = egui::RichText::new(label).line_height(Some(24.0)).size(18.0);
+            let button = egui::Button::new(text).frame(false).ui(ui);
+
+            if button.hovered() {
+              ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
+
+            if button.clicked() {
+              mod_select.toggle();
+            }
+          });
+
         egui::Separator::default().vertical().spacing(0.0).ui(ui);
         ui.add_space(ui.available_width() - 12.0);
       });
@@ -3,7 +3,7 @@ use egui::Widget;
 
 use crate::client::{
   event::ClientEvent,
-  gameplay::{beatmap::BeatmapHash, beatmap_cache::BeatmapInfo},
+  gameplay::{beatmap::BeatmapHash, beatmap_cache::BeatmapInfo, beatmap_set::BeatmapSet},
   ui::card_component::CardComponent,
 };
 
@@ -13,12 +13,13 @@ pub struct BeatmapCard {
   title: String,
   variant: String,
   difficulty: f64,
+  converted: bool,
 }
 
 impl BeatmapCard {
-  pub fn new(info: &BeatmapInfo) -> Self {
-    let bg = info.file_path.parent().unwrap().join(&info.bg_path);
-    let bg = format!("file://{}", bg.to_str().unwrap());
+  /// Difficulties use the background of their set, so every card of a set shares the same image.
+  pub fn new(info: &BeatmapInfo, set: &BeatmapSet) -> Self {
+    let bg = format!("file://{}", set.bg_path.to_str().unwrap());
 
     return Self {
       card: CardComponent::new(bg),
@@ -26,6 +27,7 @@ impl BeatmapCard {
       title: info.title.clone(),
       variant: info.variant.clone(),
       difficulty: info.difficulty,
+      converted: info.converted,
     };
   }
 
@@ -59,6 +61,10 @@ impl BeatmapCard {
               ui.label(
                 egui::RichText::new(format!("{}  ∙  {:.2} ★", &self.variant, self.difficulty)).strong().size(12.0),
               );
+
+              if self.converted {
+                ui.label(egui::RichText::new("Convert").weak().size(12.0));
+              }
             });
           });
         });
@@ -118,3 +124,55 @@ impl BeatmapCard {
     return card;
   }
 }
+
+/// Card of a collapsed set, showing the song along with the range of its difficulties.
+pub struct BeatmapSetCard {
+  card: CardComponent,
+}
+
+impl BeatmapSetCard {
+  pub fn new(set: &BeatmapSet) -> Self {
+    let bg = format!("file://{}", set.bg_path.to_str().unwrap());
+
+    return Self { card: CardComponent::new(bg) };
+  }
+
+  pub fn prepare(&mut self, ui: &mut egui::Ui, set: &BeatmapSet, stars: (f64, f64)) -> egui::Response {
+    let card = self.card.prepare(ui, 64.0, |ui| {
+      egui::Frame::none() //
+        .inner_margin(egui::Margin::same(8.0))
+        .show(ui, |ui| {
+          ui.horizontal(|ui| {
+            ui.vertical(|ui| {
+              egui::Label::new(egui::RichText::new(&set.title).strong().size(14.0)).truncate().ui(ui);
+
+              ui.add_space(2.0);
+
+              egui::Label::new(egui::RichText::new(&set.artist).strong().size(12.0)).truncate().ui(ui);
+
+              ui.add_space(10.0);
+            });
+
+            ui.with_layout(egui::Layout::right_to_left(egui::Align::Max), |ui| {
+              ui.add_space(4.0);
+
+              let count = set.difficulties().len();
+              let text = if count == 1 {
+                format!("1 difficulty  ∙  {:.2} ★", stars.0)
+              } else {
+                format!("{} difficulties  ∙  {:.2} - {:.2} ★", count, stars.0, stars.1)
+              };
+
+              ui.label(egui::RichText::new(text).strong().size(12.0));
+            });
+          });
+        });
+    });
+
+    if card.hovered() {
+      ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+    }
+
+    return card;
+  }
+}
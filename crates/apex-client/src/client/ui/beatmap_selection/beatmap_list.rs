@@ -1,32 +1,46 @@
 use apex_framework::event::EventBus;
 use egui::{NumExt as _, Widget};
-use instant::Instant;
-use log::debug;
 
 use crate::client::{
   event::ClientEvent,
-  gameplay::{beatmap_cache::BeatmapCache, beatmap_selector::BeatmapSelector},
+  gameplay::{
+    beatmap_cache::{BeatmapCache, BeatmapChange},
+    beatmap_selector::BeatmapSelector,
+  },
 };
 
-use super::beatmap_card::BeatmapCard;
+use super::beatmap_card::{BeatmapCard, BeatmapSetCard};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ListRow {
+  /// Collapsed set, along with the difficulty that gets selected when clicking it.
+  Set(usize, usize),
+  Beatmap(usize),
+}
 
 pub struct BeatmapList {
   event_bus: EventBus<ClientEvent>,
-  beatmap_cards: Vec<BeatmapCard>,
+  /// Cards by cache index, removed beatmaps have none.
+  beatmap_cards: Vec<Option<BeatmapCard>>,
+
+  /// Cards by set index, created once a set is shown collapsed.
+  set_cards: Vec<Option<BeatmapSetCard>>,
   prev_selected: usize,
   scroll_to_selected: bool,
 
-  last_update: Instant,
+  /// How many of the cache's changes were already applied.
+  seen_changes: usize,
 }
 
 impl BeatmapList {
-  pub fn new(event_bus: EventBus<ClientEvent>, beatmap_cards: Vec<BeatmapCard>) -> Self {
+  pub fn new(event_bus: EventBus<ClientEvent>) -> Self {
     return Self {
       event_bus,
-      beatmap_cards,
+      beatmap_cards: Vec::new(),
+      set_cards: Vec::new(),
       prev_selected: 0,
       scroll_to_selected: false,
-      last_update: Instant::now(),
+      seen_changes: 0,
     };
   }
 
@@ -34,20 +48,27 @@ impl BeatmapList {
     self.scroll_to_selected = true;
   }
 
-  pub fn prepare(&mut self, ui: &mut egui::Ui, beatmap_cache: &BeatmapCache, selector: &mut BeatmapSelector) {
-    // TODO: this is going to be very slow on a large number of beatmaps, probably go with event based approach
-    if beatmap_cache.last_update() > self.last_update {
-      debug!("Updating beatmap list");
+  /// Revision of the cache up to which changes were applied.
+  pub fn seen_changes(&self) -> usize {
+    return self.seen_changes;
+  }
 
-      self.last_update = Instant::now();
-      self.beatmap_cards.clear();
+  pub fn prepare(&mut self, ui: &mut egui::Ui, beatmap_cache: &BeatmapCache, selector: &mut BeatmapSelector) {
+    for change in beatmap_cache.changes_since(self.seen_changes).iter() {
+      let (BeatmapChange::Inserted(idx) | BeatmapChange::Removed(idx)) = *change;
 
-      for (_, info) in beatmap_cache.iter() {
-        let card = BeatmapCard::new(info);
-        self.beatmap_cards.push(card);
+      if self.beatmap_cards.len() <= idx {
+        self.beatmap_cards.resize_with(idx + 1, || None);
       }
+
+      self.beatmap_cards[idx] = beatmap_cache
+        .get_index(idx)
+        .zip(beatmap_cache.set_of(idx))
+        .map(|((_, info), (_, set))| BeatmapCard::new(info, set));
     }
 
+    self.seen_changes = beatmap_cache.revision();
+
     egui::Frame::none()
       .fill(egui::Color32::from_black_alpha(128))
       .outer_margin(egui::Margin { left: -9.0, ..Default::default() })
@@ -86,7 +107,20 @@ impl BeatmapList {
               .ui(ui);
           });
 
-        let total_rows = selector.matched().count();
+        // Only the set of the selected beatmap is expanded into its difficulties
+        let selected_set = beatmap_cache.set_of(selector.selected()).map(|(set_idx, _)| set_idx);
+        let rows = selector
+          .matched_sets()
+          .iter()
+          .flat_map(|(set_idx, matched)| {
+            return match Some(*set_idx) == selected_set {
+              true => matched.iter().copied().map(ListRow::Beatmap).collect::<Vec<_>>(),
+              false => vec![ListRow::Set(*set_idx, matched[0])],
+            };
+          })
+          .collect::<Vec<_>>();
+
+        let total_rows = rows.len();
         let row_height_sans_spacing = 72.0;
 
         let spacing = ui.spacing().item_spacing;
@@ -122,9 +156,48 @@ impl BeatmapList {
               let mut new_selected = None;
               let selected_idx = selector.selected();
 
-              for orig_idx in selector.matched().skip(if min_row == 0 { min_row } else { min_row - 1 }).take(max_row) {
-                let card = &mut self.beatmap_cards[orig_idx];
-                let (beatmap_hash, _) = beatmap_cache.get_index(orig_idx).unwrap();
+              for row in rows.iter().skip(if min_row == 0 { min_row } else { min_row - 1 }).take(max_row) {
+                let orig_idx = match *row {
+                  ListRow::Beatmap(orig_idx) => orig_idx,
+
+                  ListRow::Set(set_idx, first_idx) => {
+                    let Some(set) = beatmap_cache.get_set(set_idx) else {
+                      continue;
+                    };
+
+                    if self.set_cards.len() <= set_idx {
+                      self.set_cards.resize_with(set_idx + 1, || None);
+                    }
+
+                    let card = self.set_cards[set_idx].get_or_insert_with(|| BeatmapSetCard::new(set));
+                    let stars = |idx: Option<&usize>| {
+                      return idx
+                        .and_then(|x| beatmap_cache.get_index(*x))
+                        .map(|(_, info)| info.difficulty)
+                        .unwrap_or(0.0);
+                    };
+
+                    let stars = (stars(set.difficulties().first()), stars(set.difficulties().last()));
+
+                    ui.push_id(("set", set_idx), |ui| {
+                      let response = card.prepare(ui, set, stars).interact(egui::Sense::click());
+
+                      // Expands the set by selecting its easiest matching difficulty
+                      if response.clicked() || response.clicked_by(egui::PointerButton::Secondary) {
+                        new_selected = Some(first_idx);
+                        self.event_bus.send(ClientEvent::SelectBeatmap);
+                      }
+                    });
+
+                    continue;
+                  }
+                };
+
+                let (Some(Some(card)), Some((beatmap_hash, _))) =
+                  (self.beatmap_cards.get_mut(orig_idx), beatmap_cache.get_index(orig_idx))
+                else {
+                  continue;
+                };
 
                 ui.push_id(orig_idx, |ui| {
                   let is_selected = orig_idx == selected_idx;
@@ -161,8 +234,8 @@ impl BeatmapList {
                 }
 
                 let mut height = 0.0;
-                for idx in selector.matched() {
-                  if idx == selected_idx {
+                for row in &rows {
+                  if *row == ListRow::Beatmap(selected_idx) {
                     break;
                   }
 
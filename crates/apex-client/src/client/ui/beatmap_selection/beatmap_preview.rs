@@ -67,6 +67,8 @@ pub struct BeatmapPreview {
   conveyor_zoom: f64,
   don_color: Color,
   kat_color: Color,
+  drumroll_color: Color,
+  swell_color: Color,
 
   current_beatmap: Option<Beatmap>,
   new_renderer: Option<TaikoRenderer>,
@@ -93,6 +95,8 @@ impl BeatmapPreview {
         hit_position_y: hit_pos,
         don: settings.taiko.general.don_color(),
         kat: settings.taiko.general.kat_color(),
+        drumroll: settings.taiko.general.drumroll_color(),
+        swell: settings.taiko.general.swell_color(),
         hit_animation_height: 12.5,
       },
     );
@@ -103,6 +107,8 @@ impl BeatmapPreview {
       conveyor_zoom: settings.taiko.general.conveyor_zoom(),
       don_color: settings.taiko.general.don_color(),
       kat_color: settings.taiko.general.kat_color(),
+      drumroll_color: settings.taiko.general.drumroll_color(),
+      swell_color: settings.taiko.general.swell_color(),
       current_beatmap: None,
       new_renderer: Some(taiko_renderer),
       new_scale_factor: None,
@@ -243,6 +249,8 @@ impl Drawable for BeatmapPreview {
         hit_position_y: hit_pos,
         don: self.don_color,
         kat: self.kat_color,
+        drumroll: self.drumroll_color,
+        swell: self.swell_color,
         hit_animation_height: 12.5,
       },
     );
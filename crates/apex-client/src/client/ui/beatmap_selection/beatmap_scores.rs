@@ -7,7 +7,7 @@ use tap::Tap as _;
 
 use crate::client::{
   event::ClientEvent,
-  gameplay::beatmap::BeatmapHash,
+  gameplay::{beatmap::BeatmapHash, mods::Mod},
   score::{
     score::Score,
     score_cache::{ScoreCache, ScoreId},
@@ -91,9 +91,24 @@ impl BeatmapScores {
 
         for (i, (score_id, score)) in sorted.iter().copied().enumerate() {
           write!(&mut self.buffer, "{}", i + 1).unwrap();
-          if render_score(ui, score, &self.buffer).clicked() {
+          let response = render_score(ui, score, &self.buffer);
+          if response.clicked() {
             self.event_bus.send(ClientEvent::ViewScore { beatmap_hash, score_id: score_id });
           }
+
+          response.context_menu(|ui| {
+            let watch = ui.add_enabled(!score.hits().is_empty(), egui::Button::new("Watch replay"));
+            if watch.clicked() {
+              let score = score.clone();
+              self.event_bus.send(ClientEvent::WatchReplay { beatmap_hash, score });
+              ui.close_menu();
+            }
+
+            if ui.button("Export replay").clicked() {
+              self.event_bus.send(ClientEvent::ExportReplay { beatmap_hash, score_id });
+              ui.close_menu();
+            }
+          });
           self.buffer.clear();
 
           ui.add_space(4.0);
@@ -159,9 +174,13 @@ fn render_score(ui: &mut egui::Ui, score: &Score, idx: &str) -> egui::Response {
                       ui.label(egui::RichText::new(score.username()).size(18.0).strong());
 
                       ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
-                        let grade = score.grade();
-                        let color = grade.color();
-                        ui.label(egui::RichText::new(format!("{}", grade)).size(18.0).color(color));
+                        if score.failed() {
+                          ui.label(egui::RichText::new("F").size(18.0).color(egui::Color32::LIGHT_RED));
+                        } else {
+                          let grade = score.grade();
+                          let color = grade.color();
+                          ui.label(egui::RichText::new(format!("{}", grade)).size(18.0).color(color));
+                        }
 
                         ui.label(egui::RichText::new(format!("{}", score.score_points())).size(18.0).strong());
                       });
@@ -179,6 +198,15 @@ fn render_score(ui: &mut egui::Ui, score: &Score, idx: &str) -> egui::Response {
                         ui.label(egui::RichText::new(format!("{}x", score.max_combo())).size(14.0));
                         ui.label(egui::RichText::new("∙").size(14.0));
                         ui.label(egui::RichText::new(format!("{:.2}%", score.accuracy() * 100.0)).size(14.0));
+
+                        if !score.mods().is_empty() {
+                          ui.label(egui::RichText::new("∙").size(14.0));
+
+                          // Laid out right to left, so the badges are added in reverse
+                          for value in score.mods().iter().rev() {
+                            mod_badge(ui, value);
+                          }
+                        }
                       });
                     });
                   });
@@ -194,3 +222,14 @@ fn render_score(ui: &mut egui::Ui, score: &Score, idx: &str) -> egui::Response {
     .on_hover_cursor(egui::CursorIcon::PointingHand)
     .interact(egui::Sense::click());
 }
+
+fn mod_badge(ui: &mut egui::Ui, value: Mod) {
+  egui::Frame::none()
+    .fill(value.kind().color().into())
+    .rounding(3.0)
+    .inner_margin(egui::Margin::symmetric(4.0, 0.0))
+    .show(ui, |ui| {
+      let text = egui::RichText::new(value.acronym()).size(12.0).strong().color(egui::Color32::BLACK);
+      ui.label(text).on_hover_text(value.name());
+    });
+}
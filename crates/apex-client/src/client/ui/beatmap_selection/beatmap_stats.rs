@@ -1,17 +1,22 @@
 use egui::Widget;
 
-use crate::client::gameplay::beatmap_cache::BeatmapInfo;
+use crate::client::gameplay::{beatmap::BeatmapHash, beatmap_cache::BeatmapInfo, mods::Mods};
 
-pub struct BeatmapStats {}
+pub struct BeatmapStats {
+  /// Star rating of the last shown beatmap with the mods it was calculated for.
+  difficulty: Option<(BeatmapHash, Mods, f64)>,
+}
 
 impl BeatmapStats {
   pub fn new() -> Self {
-    return Self {};
+    return Self { difficulty: None };
   }
 
-  pub fn prepare(&mut self, ui: &mut egui::Ui, beatmap: &BeatmapInfo) {
+  pub fn prepare(&mut self, ui: &mut egui::Ui, beatmap_hash: BeatmapHash, beatmap: &BeatmapInfo, mods: Mods) {
     use egui_extras::{Size, StripBuilder};
 
+    let difficulty = self.difficulty(beatmap_hash, beatmap, mods);
+
     egui::Frame::window(ui.style()) //
       .inner_margin(egui::Margin::symmetric(24.0, 16.0))
       .show(ui, |ui| {
@@ -29,19 +34,19 @@ impl BeatmapStats {
               egui::Label::new(egui::RichText::new(text).size(18.0)).truncate().ui(ui);
 
               ui.with_layout(egui::Layout::left_to_right(egui::Align::Max), |ui| {
-                let text = format!("{:.2} HP", beatmap.hp_drain);
+                let text = format!("{:.2} HP", mods.hp_drain_rate(beatmap.hp_drain));
                 egui::Label::new(egui::RichText::new(text).size(16.0).weak()).truncate().ui(ui);
 
                 egui::Label::new(egui::RichText::new("∙").size(16.0).weak()).truncate().ui(ui);
 
-                let text = format!("{:.2} OD", beatmap.overall_difficulty);
+                let text = format!("{:.2} OD", mods.overall_difficulty(beatmap.overall_difficulty));
                 egui::Label::new(egui::RichText::new(text).size(16.0).weak()).truncate().ui(ui);
               });
             });
 
             builder.cell(|ui| {
               ui.with_layout(egui::Layout::top_down(egui::Align::Max), |ui| {
-                let text = format!("{:.2} ★", beatmap.difficulty);
+                let text = format!("{:.2} ★", difficulty);
                 egui::Label::new(egui::RichText::new(text).size(14.0).line_height(Some(18.0))).truncate().ui(ui);
 
                 let text = format!("{} ⏺", beatmap.object_count);
@@ -57,4 +62,24 @@ impl BeatmapStats {
           });
       });
   }
+
+  fn difficulty(&mut self, beatmap_hash: BeatmapHash, beatmap: &BeatmapInfo, mods: Mods) -> f64 {
+    if mods.is_empty() {
+      return beatmap.difficulty;
+    }
+
+    if let Some((hash, cached_mods, difficulty)) = self.difficulty {
+      if hash == beatmap_hash && cached_mods == mods {
+        return difficulty;
+      }
+    }
+
+    let difficulty = beatmap.calc_difficulty(mods).unwrap_or_else(|e| {
+      log::error!("Failed to calculate difficulty of {:?}: {}", beatmap.file_path, e);
+      beatmap.difficulty
+    });
+
+    self.difficulty = Some((beatmap_hash, mods, difficulty));
+    return difficulty;
+  }
 }
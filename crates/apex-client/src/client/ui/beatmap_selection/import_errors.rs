@@ -0,0 +1,43 @@
+use std::path::PathBuf;
+
+use crate::client::gameplay::beatmap_import_error::BeatmapImportError;
+
+/// Lists the archives which failed to import until it is closed.
+pub struct ImportErrors {
+  errors: Vec<(PathBuf, BeatmapImportError)>,
+}
+
+impl ImportErrors {
+  pub fn new() -> Self {
+    return Self { errors: Vec::new() };
+  }
+
+  pub fn push(&mut self, archive: PathBuf, error: BeatmapImportError) {
+    self.errors.push((archive, error));
+  }
+
+  pub fn prepare(&mut self, ctx: &egui::Context) {
+    if self.errors.is_empty() {
+      return;
+    }
+
+    let mut open = true;
+    egui::Window::new("Import failed")
+      .open(&mut open)
+      .anchor(egui::Align2::RIGHT_BOTTOM, egui::vec2(-12.0, -12.0))
+      .collapsible(false)
+      .resizable(false)
+      .show(ctx, |ui| {
+        for (archive, error) in &self.errors {
+          let name = archive.file_name().unwrap_or(archive.as_os_str()).to_string_lossy();
+          ui.label(egui::RichText::new(name).strong());
+          ui.label(error.to_string());
+          ui.add_space(4.0);
+        }
+      });
+
+    if !open {
+      self.errors.clear();
+    }
+  }
+}
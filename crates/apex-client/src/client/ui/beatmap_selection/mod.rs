@@ -5,20 +5,22 @@ use apex_framework::{
   graphics::{drawable::Drawable, graphics::Graphics},
   time::clock::AbstractClock,
 };
-use beatmap_card::BeatmapCard;
 use beatmap_list::BeatmapList;
 use beatmap_preview::BeatmapPreview;
 use beatmap_scores::BeatmapScores;
 use beatmap_stats::BeatmapStats;
+use import_errors::ImportErrors;
+use mod_select::ModSelect;
+use std::path::PathBuf;
+
 use tap::Tap;
 
 use crate::client::{
   client::Client,
   event::ClientEvent,
   gameplay::{
-    beatmap::{Beatmap, BeatmapHash},
-    beatmap_cache::BeatmapCache,
-    beatmap_selector::BeatmapSelector,
+    beatmap::BeatmapHash, beatmap_cache::BeatmapCache, beatmap_import_error::BeatmapImportError,
+    beatmap_selector::BeatmapSelector, mods::Mods,
   },
   score::score_cache::{ScoreCache, ScoreId},
   settings::Settings,
@@ -32,6 +34,8 @@ pub mod beatmap_list;
 pub mod beatmap_preview;
 pub mod beatmap_scores;
 pub mod beatmap_stats;
+pub mod import_errors;
+pub mod mod_select;
 
 pub struct BeatmapSelectionView {
   prev_beatmap: BeatmapHash,
@@ -43,32 +47,29 @@ pub struct BeatmapSelectionView {
   beatmap_preview: BeatmapPreview,
   beatmap_scores: BeatmapScores,
   action_bar: ActionBar,
+  mod_select: ModSelect,
+  import_errors: ImportErrors,
 }
 
 impl BeatmapSelectionView {
   pub fn new(
     event_bus: EventBus<ClientEvent>,
-    beatmap_cache: &BeatmapCache,
     clock: &mut impl AbstractClock,
     graphics: &Graphics,
     settings: &Settings,
   ) -> Self {
-    let mut beatmap_cards = vec![];
-    for (_, info) in beatmap_cache.iter() {
-      let card = BeatmapCard::new(info);
-      beatmap_cards.push(card);
-    }
-
     return Self {
       prev_beatmap: BeatmapHash::default(),
       score_ids: Vec::new(),
 
       beatmap_bg: BackgroundComponent::new(""),
-      beatmap_list: BeatmapList::new(event_bus.clone(), beatmap_cards),
+      beatmap_list: BeatmapList::new(event_bus.clone()),
       beatmap_stats: BeatmapStats::new(),
       beatmap_preview: BeatmapPreview::new(graphics, settings),
       beatmap_scores: BeatmapScores::new(event_bus.clone()),
-      action_bar: ActionBar::new(event_bus, clock),
+      action_bar: ActionBar::new(event_bus.clone(), clock),
+      mod_select: ModSelect::new(event_bus),
+      import_errors: ImportErrors::new(),
     };
   }
 
@@ -76,6 +77,19 @@ impl BeatmapSelectionView {
     self.beatmap_list.scroll_to_selected();
   }
 
+  pub fn mods(&self) -> Mods {
+    return self.mod_select.mods();
+  }
+
+  pub fn import_failed(&mut self, archive: PathBuf, error: BeatmapImportError) {
+    self.import_errors.push(archive, error);
+  }
+
+  /// Revision of the cache up to which the beatmap list applied changes.
+  pub fn seen_changes(&self) -> usize {
+    return self.beatmap_list.seen_changes();
+  }
+
   pub fn prepare(
     &mut self,
     core: &mut Core<Client>,
@@ -86,6 +100,9 @@ impl BeatmapSelectionView {
   ) {
     selector.tick(beatmap_cache);
 
+    // Shown with an empty library too, that's where most imports come from
+    self.import_errors.prepare(core.egui.ctx());
+
     use egui_extras::{Size, StripBuilder};
 
     if beatmap_cache.is_empty() {
@@ -167,15 +184,20 @@ impl BeatmapSelectionView {
     if self.prev_beatmap != beatmap_hash {
       self.prev_beatmap = beatmap_hash;
 
-      let base_path = info.file_path.parent().unwrap();
-      let bg_path = base_path.join(&info.bg_path);
-      let bg = format!("file://{}", bg_path.to_str().unwrap());
-      self.beatmap_bg = BackgroundComponent::new(bg);
+      if let Some((_, beatmap_set)) = beatmap_cache.set_of(selected) {
+        let bg = format!("file://{}", beatmap_set.bg_path.to_str().unwrap());
+        self.beatmap_bg = BackgroundComponent::new(bg);
+      }
 
-      let data = std::fs::read_to_string(&info.file_path).unwrap();
-      let beatmap = Beatmap::parse(data, base_path.to_owned());
+      match info.load_beatmap() {
+        Ok(beatmap) => {
+          self.beatmap_preview.change_beatmap(&core.graphics, core.egui.renderer_mut(), &beatmap);
+        }
 
-      self.beatmap_preview.change_beatmap(&core.graphics, core.egui.renderer_mut(), &beatmap);
+        Err(e) => {
+          log::error!("Failed to load beatmap preview {:?}: {}", info.file_path, e);
+        }
+      }
 
       self.update_scores(score_cache, beatmap_hash);
     }
@@ -193,7 +215,7 @@ impl BeatmapSelectionView {
               egui::Frame::none() //
                 .inner_margin(egui::Margin::same(12.0).tap_mut(|x| x.bottom = 0.0))
                 .show(ui, |ui| {
-                  self.beatmap_stats.prepare(ui, info);
+                  self.beatmap_stats.prepare(ui, beatmap_hash, info, self.mod_select.mods());
                   ui.add_space(8.0);
                   self.beatmap_preview.prepare(ui, clock, egui_renderer);
                   ui.add_space(8.0);
@@ -202,7 +224,7 @@ impl BeatmapSelectionView {
             });
 
             ui.with_layout(egui::Layout::left_to_right(egui::Align::Max), |ui| {
-              self.action_bar.prepare(ui, clock);
+              self.action_bar.prepare(ui, clock, &mut self.mod_select);
             });
           });
 
@@ -211,6 +233,8 @@ impl BeatmapSelectionView {
           });
         });
     });
+
+    self.mod_select.prepare(egui_ctx);
   }
 
   pub fn update_scores(&mut self, score_cache: &mut ScoreCache, beatmap: BeatmapHash) {
@@ -0,0 +1,93 @@
+use apex_framework::event::EventBus;
+
+use crate::client::{
+  event::ClientEvent,
+  gameplay::mods::{Mod, ModKind, Mods},
+};
+
+/// Panel to pick the mods the next play is started with.
+pub struct ModSelect {
+  event_bus: EventBus<ClientEvent>,
+
+  mods: Mods,
+  open: bool,
+}
+
+impl ModSelect {
+  pub fn new(event_bus: EventBus<ClientEvent>) -> Self {
+    return Self {
+      event_bus,
+      mods: Mods::default(),
+      open: false,
+    };
+  }
+
+  pub fn prepare(&mut self, ctx: &egui::Context) {
+    let prev_mods = self.mods;
+
+    egui::Window::new("Mods")
+      .open(&mut self.open)
+      .anchor(egui::Align2::LEFT_BOTTOM, egui::vec2(12.0, -83.0))
+      .collapsible(false)
+      .resizable(false)
+      .show(ctx, |ui| {
+        for kind in ModKind::ALL {
+          ui.label(egui::RichText::new(kind.name()).strong());
+
+          ui.horizontal_wrapped(|ui| {
+            for value in Mod::ALL.into_iter().filter(|x| x.kind() == kind) {
+              let text = egui::RichText::new(value.name()).size(16.0);
+              let button = ui.selectable_label(self.mods.contains(value), text).on_hover_text(value.description());
+
+              if button.clicked() {
+                self.mods.toggle(value);
+              }
+            }
+          });
+
+          ui.add_space(4.0);
+        }
+
+        if self.mods.contains(Mod::DifficultyAdjust) {
+          ui.separator();
+
+          let adjust = self.mods.difficulty_adjust_mut();
+          difficulty_slider(ui, "Overall difficulty", &mut adjust.overall_difficulty);
+          difficulty_slider(ui, "HP drain", &mut adjust.hp_drain_rate);
+          ui.add(egui::Slider::new(&mut adjust.scroll_speed, 0.5 ..= 2.0).step_by(0.05).text("Scroll speed"));
+        }
+
+        ui.separator();
+
+        if ui.add_enabled(!self.mods.is_empty(), egui::Button::new("Reset")).clicked() {
+          self.mods = Mods::default();
+        }
+      });
+
+    if self.mods != prev_mods {
+      self.event_bus.send(ClientEvent::ModsChanged(self.mods));
+    }
+  }
+
+  pub fn toggle(&mut self) {
+    self.open = !self.open;
+  }
+
+  pub fn mods(&self) -> Mods {
+    return self.mods;
+  }
+}
+
+/// Difficulty setting which is taken from the beatmap until its checkbox is ticked.
+fn difficulty_slider(ui: &mut egui::Ui, label: &str, value: &mut Option<f32>) {
+  ui.horizontal(|ui| {
+    let mut is_custom = value.is_some();
+    if ui.checkbox(&mut is_custom, label).changed() {
+      *value = is_custom.then_some(5.0);
+    }
+
+    if let Some(value) = value {
+      ui.add(egui::Slider::new(value, 0.0 ..= 10.0).step_by(0.1));
+    }
+  });
+}
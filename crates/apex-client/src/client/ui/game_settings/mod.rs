@@ -1,43 +1,59 @@
 use egui::Widget;
 use log::debug;
 
-use apex_framework::input::{
-  keybinds::{Bind, KeyCombination},
-  Input,
+use apex_framework::{
+  event::EventBus,
+  input::{
+    keybinds::{Bind, KeyCombination},
+    Input,
+  },
 };
 use tap::Tap;
 
 use crate::client::{
   action::ClientAction,
+  event::ClientEvent,
   settings::{Settings, SettingsProxy},
 };
 
 pub mod tab_controls;
+pub mod tab_import;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum GameSettingsTab {
   General,
   Controls,
+  Import,
 }
 
 pub struct GameSettingsView {
   pub tab: GameSettingsTab,
   pub is_open: bool,
 
+  event_bus: EventBus<ClientEvent>,
+
   buffer: String,
   current_bind: Option<KeyCombination>,
   bind_cache: Vec<(KeyCombination, Bind<ClientAction>)>,
+
+  stable_path: String,
+  copy_songs: bool,
 }
 
 impl GameSettingsView {
-  pub fn new() -> Self {
+  pub fn new(event_bus: EventBus<ClientEvent>) -> Self {
     return Self {
       tab: GameSettingsTab::General,
       is_open: false,
 
+      event_bus,
+
       buffer: String::new(),
       current_bind: None,
       bind_cache: vec![],
+
+      stable_path: String::new(),
+      copy_songs: false,
     };
   }
 
@@ -87,6 +103,7 @@ impl GameSettingsView {
           .horizontal(|mut strip| {
             let mut scroll = None;
             let mut scroll_to_controls = false;
+            let mut scroll_to_import = false;
 
             strip.cell(|ui| {
               let padding = 8.0;
@@ -98,7 +115,7 @@ impl GameSettingsView {
                   let button_count = settings.group_count();
                   let button_size = 32.0;
 
-                  let buttons_panel = button_size * (button_count + 1) as f32;
+                  let buttons_panel = button_size * (button_count + 2) as f32;
                   let offset = ui.available_height() / 2.0 - buttons_panel / 2.0 - button_size - padding;
 
                   ui.vertical_centered(|ui| {
@@ -130,6 +147,20 @@ impl GameSettingsView {
                       scroll_to_controls = true;
                     }
 
+                    let button = ui.add(
+                      egui::Button::new(egui::RichText::new("📥").size(16.0))
+                        .frame(false)
+                        .min_size(egui::vec2(32.0, 32.0)),
+                    );
+
+                    if button.hovered() {
+                      ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+                    }
+
+                    if button.clicked() {
+                      scroll_to_import = true;
+                    }
+
                     ui.add_space(offset - padding + 3.0);
 
                     if egui::Button::new("⏴")
@@ -173,6 +204,23 @@ impl GameSettingsView {
                       ui.add_space(6.0);
 
                       self.controls_tab(ui, input);
+
+                      ui.add_space(16.0);
+
+                      ui.horizontal(|ui| {
+                        let title = ui.label(egui::RichText::new("📥 Import").size(24.0).strong());
+
+                        if scroll_to_import {
+                          title.scroll_to_me(Some(egui::Align::Center));
+                        }
+
+                        ui.add_space(-10.0);
+                        ui.add(egui::Separator::default().horizontal().shrink(24.0).spacing(0.0));
+                      });
+
+                      ui.add_space(6.0);
+
+                      self.import_tab(ui);
                     });
                 });
             });
@@ -0,0 +1,35 @@
+use std::path::PathBuf;
+
+use egui::Widget as _;
+
+use crate::client::{event::ClientEvent, stable::stable_import::SongsTransfer};
+
+use super::GameSettingsView;
+
+impl GameSettingsView {
+  pub(super) fn import_tab(&mut self, ui: &mut egui::Ui) {
+    ui.label("osu!stable folder");
+
+    egui::TextEdit::singleline(&mut self.stable_path)
+      .hint_text("C:\\Users\\...\\AppData\\Local\\osu!")
+      .margin(egui::Margin::symmetric(8.0, 6.0))
+      .desired_width(ui.available_width() - 40.0)
+      .ui(ui);
+
+    ui.add_space(2.0);
+
+    egui::Checkbox::new(&mut self.copy_songs, "Copy beatmaps instead of linking them").ui(ui);
+
+    ui.add_space(2.0);
+
+    let path = self.stable_path.trim();
+    let button = ui.add_enabled(!path.is_empty(), egui::Button::new("Import beatmaps, scores and collections"));
+
+    if button.clicked() {
+      self.event_bus.send(ClientEvent::ImportStable {
+        osu_dir: PathBuf::from(path),
+        transfer: if self.copy_songs { SongsTransfer::Copy } else { SongsTransfer::Link },
+      });
+    }
+  }
+}
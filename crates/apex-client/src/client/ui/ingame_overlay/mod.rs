@@ -5,7 +5,10 @@ use apex_framework::{
 use delta_bar::{HitDeltaBar, HitDeltaBarOptions};
 use instant::Instant;
 
-use crate::client::{client::Client, score::score_processor::ScoreProcessor};
+use crate::client::{
+  client::Client,
+  score::{health_processor::PASS_HEALTH, score_processor::ScoreProcessor},
+};
 
 pub mod delta_bar;
 
@@ -43,6 +46,7 @@ impl IngameOverlayView {
     core: &mut Core<Client>,
     clock: &mut impl AbstractClock,
     score_processor: &ScoreProcessor,
+    health: f64,
     hit_window_150: Time,
     hit_window_300: Time,
   ) {
@@ -54,6 +58,23 @@ impl IngameOverlayView {
 
         self.delta_bar.prepare(ui, width / 2.0, height - 16.0, hit_window_150, hit_window_300);
 
+        // Health bar, the marker shows how much health is needed to pass
+        {
+          let bar_width = 320.0;
+          let bar_height = 12.0;
+
+          let rect = egui::Rect::from_min_size(egui::pos2(8.0, 8.0), egui::vec2(bar_width, bar_height));
+          ui.painter().rect_filled(rect, egui::Rounding::ZERO, egui::Color32::from_black_alpha(160));
+
+          let color = if health > PASS_HEALTH { egui::Color32::LIGHT_GREEN } else { egui::Color32::LIGHT_RED };
+          let fill = rect.with_max_x(rect.min.x + bar_width * health as f32);
+          ui.painter().rect_filled(fill, egui::Rounding::ZERO, color);
+
+          let x = rect.min.x + bar_width * PASS_HEALTH as f32;
+          let marker = egui::Rect::from_center_size(egui::pos2(x, rect.center().y), egui::vec2(2.0, bar_height + 4.0));
+          ui.painter().rect_filled(marker, egui::Rounding::ZERO, egui::Color32::LIGHT_GRAY);
+        }
+
         // let draw_hit_key = |i: f32, elapsed: f32| {
         //   let fade = 0.2;
         //   let max_brightness = 200;
@@ -6,3 +6,5 @@ pub mod game_settings;
 pub mod ingame_overlay;
 pub mod play_results;
 pub mod recording_panel;
+pub mod replay_overlay;
+pub mod swell_overlay;
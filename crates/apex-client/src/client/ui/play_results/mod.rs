@@ -6,13 +6,11 @@ use tap::{Pipe, Tap};
 use crate::client::{
   client::Client,
   gameplay::{
-    beatmap::{calc_hit_window_150, calc_hit_window_300, Beatmap},
+    beatmap::{calc_hit_window_150, calc_hit_window_300, Beatmap, BeatmapHash},
     beatmap_cache::BeatmapInfo,
+    simulator::{Simulator, SimulatorConfig},
   },
-  score::{
-    judgement_processor::{check_hit, Judgement},
-    score::Score,
-  },
+  score::{judgement_processor::Judgement, score::Score},
 };
 
 use super::{background_component::BackgroundComponent, beatmap_selection::beatmap_stats::BeatmapStats};
@@ -20,52 +18,43 @@ use super::{background_component::BackgroundComponent, beatmap_selection::beatma
 pub struct PlayResultsView {
   background: BackgroundComponent,
   beatmap_stats: BeatmapStats,
+  beatmap_hash: BeatmapHash,
   beatmap_info: BeatmapInfo,
   score: Score,
   hits: Vec<(Time, Time, Judgement)>,
 }
 
 impl PlayResultsView {
-  pub fn new(source: impl Into<ImageSource<'static>>, beatmap: Beatmap, score: Score) -> Self {
+  pub fn new(
+    source: impl Into<ImageSource<'static>>,
+    beatmap_info: BeatmapInfo,
+    beatmap: Beatmap,
+    score: Score,
+  ) -> Self {
     let image = source.into();
     let background = BackgroundComponent::new(image.clone());
     let beatmap_stats = BeatmapStats::new();
 
-    let mut hits = Vec::with_capacity(score.hits.len());
-    let mut current_circle = 0;
-    for (hit_time, hit_input) in score.hits.iter().copied() {
-      let hit_window_300 = calc_hit_window_300(beatmap.overall_difficulty);
-      let hit_window_150 = calc_hit_window_150(beatmap.overall_difficulty);
-
-      while let Some(hit_object) = beatmap.hit_objects.get(current_circle) {
-        let hit_window_end_time = hit_object.time + hit_window_150;
+    // Scores only store totals, so the recorded inputs are judged again. Circles which were never hit show up as late
+    // as possible.
+    let config = SimulatorConfig { mods: score.mods(), ..Default::default() };
 
-        if hit_window_end_time >= hit_time {
-          if let Some(result) = check_hit(hit_time, hit_object, hit_input, hit_window_150, hit_window_300) {
-            hits.push((hit_time, result.hit_delta, result.judgement));
-
-            current_circle += 1;
-            break;
-          }
-        }
-
-        // Unhit hit object which can not be hit anymore counts as a miss.
-        hits.push((hit_time, hit_object.time - hit_time, Judgement::Miss));
-
-        current_circle += 1;
-      }
+    let mut simulator = Simulator::new(beatmap.clone(), &config);
+    for (hit_time, hit_input) in score.hits.iter().copied() {
+      simulator.hit(hit_time, hit_input);
     }
 
-    // TODO: bad, please fix
-    let beatmap_info = if beatmap.file_path.exists() {
-      BeatmapInfo::from_path(beatmap.file_path)
-    } else {
-      BeatmapInfo::default()
-    };
+    let hit_window_150 = calc_hit_window_150(score.mods().overall_difficulty(beatmap.overall_difficulty));
+    let hits = simulator
+      .judgements()
+      .iter()
+      .map(|x| (x.time, x.hit_delta.unwrap_or(hit_window_150), x.judgement))
+      .collect();
 
     return Self {
       background,
       beatmap_stats,
+      beatmap_hash: beatmap.hash(),
       beatmap_info,
       score,
       hits,
@@ -86,7 +75,7 @@ impl PlayResultsView {
               egui::Frame::none() //
                 .inner_margin(egui::Margin::same(12.0))
                 .show(ui, |ui| {
-                  self.beatmap_stats.prepare(ui, &self.beatmap_info);
+                  self.beatmap_stats.prepare(ui, self.beatmap_hash, &self.beatmap_info, self.score.mods());
 
                   ui.add_space(8.0);
 
@@ -166,8 +155,9 @@ impl PlayResultsView {
         let pos = ui.cursor().min;
         let mid = pos.y + height / 2.0;
 
-        let hit_window_150 = calc_hit_window_150(self.beatmap_info.overall_difficulty);
-        let hit_window_300 = calc_hit_window_300(self.beatmap_info.overall_difficulty);
+        let overall_difficulty = self.score.mods().overall_difficulty(self.beatmap_info.overall_difficulty);
+        let hit_window_150 = calc_hit_window_150(overall_difficulty);
+        let hit_window_300 = calc_hit_window_300(overall_difficulty);
 
         let mid_offset_150 = (hit_window_300.to_ms() as f32 / (hit_window_150 * 1.5).to_ms() as f32) * (height / 2.0);
         let mid_offset_miss = (hit_window_150.to_ms() as f32 / (hit_window_150 * 1.5).to_ms() as f32) * (height / 2.0);
@@ -272,6 +262,7 @@ impl PlayResultsView {
             Judgement::Hit300 => egui::Color32::GOLD,
             Judgement::Hit150 => egui::Color32::LIGHT_BLUE,
             Judgement::Miss => egui::Color32::RED,
+            Judgement::DrumrollTick | Judgement::SwellTick | Judgement::Swell => continue,
           };
 
           let pos_x = pos.x + (hit.to_seconds() / length * width as f64) as f32;
@@ -162,8 +162,14 @@ impl RecordingPanelView {
 
         ui.horizontal(|ui| {
           if ui.button("⏺ Record").clicked() {
-            let data = std::fs::read_to_string(path).unwrap();
-            let beatmap = Beatmap::parse(data, path.to_owned());
+            let beatmap = match Beatmap::from_path(path) {
+              Ok(beatmap) => beatmap,
+              Err(e) => {
+                log::error!("Failed to load beatmap {:?}: {}", path, e);
+                return;
+              }
+            };
+
             let info = cache.get(beatmap.hash()).unwrap();
             let preview_time = info.preview_time;
             let audio_path = info.audio_path.clone();
@@ -213,6 +219,8 @@ impl RecordingPanelView {
                   hit_position_y: 256.0,
                   don: Color::new(0.92, 0.00, 0.27, 1.00),
                   kat: Color::new(0.00, 0.47, 0.67, 1.00),
+                  drumroll: Color::new(0.99, 0.72, 0.02, 1.00),
+                  swell: Color::new(0.96, 0.45, 0.09, 1.00),
                   hit_animation_height: 12.5,
                 },
               );
@@ -0,0 +1,103 @@
+use apex_framework::{
+  core::Core,
+  time::{clock::AbstractClock, time::Time},
+};
+use egui::Widget as _;
+
+use crate::client::client::Client;
+
+const SPEEDS: [f64; 8] = [0.25, 0.5, 0.75, 1.0, 1.25, 1.5, 1.75, 2.0];
+
+/// Playback controls shown while watching a replay.
+pub struct ReplayOverlayView {
+  was_playing: bool,
+}
+
+impl ReplayOverlayView {
+  pub fn new() -> Self {
+    return Self { was_playing: false };
+  }
+
+  /// Returns the position to seek to, once the seek bar is released. Speeds are relative to `rate`, the playback rate
+  /// of the replay's mods.
+  pub fn prepare(
+    &mut self,
+    core: &Core<Client>,
+    clock: &mut impl AbstractClock,
+    start: Time,
+    rate: f64,
+    username: &str,
+  ) -> Option<Time> {
+    let mut seek = None;
+
+    egui::Area::new(egui::Id::new("replay_overlay"))
+      .anchor(egui::Align2::CENTER_TOP, egui::vec2(0.0, 16.0))
+      .show(core.egui.ctx(), |ui| {
+        egui::Frame::window(ui.style()).inner_margin(egui::Margin::symmetric(12.0, 8.0)).show(ui, |ui| {
+          ui.horizontal(|ui| {
+            ui.label(egui::RichText::new(format!("Watching {}", username)).size(16.0).strong());
+            egui::Separator::default().vertical().ui(ui);
+
+            let symbol = if clock.is_playing() { "⏸" } else { "⏵" };
+            let button = egui::Button::new(egui::RichText::new(symbol).size(18.0)).frame(false).ui(ui);
+
+            if button.hovered() {
+              ui.ctx().set_cursor_icon(egui::CursorIcon::PointingHand);
+            }
+
+            if button.clicked() {
+              clock.toggle();
+            }
+
+            let mut pos = clock.position().to_seconds();
+            ui.style_mut().spacing.slider_width = 320.0;
+
+            let slider = egui::Slider::new(&mut pos, start.to_seconds() ..= clock.length().to_seconds())
+              .handle_shape(egui::style::HandleShape::Rect { aspect_ratio: 0.5 })
+              .show_value(false)
+              .smart_aim(false)
+              .trailing_fill(true)
+              .ui(ui);
+
+            if slider.drag_started() {
+              self.was_playing = clock.is_playing();
+
+              if self.was_playing {
+                clock.set_playing(false);
+              }
+            }
+
+            // Judgements are rebuilt from the start on every seek, which is too slow to do while dragging
+            if slider.drag_stopped() {
+              seek = Some(Time::from_seconds(pos));
+              clock.set_playing(self.was_playing);
+            } else if slider.changed() && !slider.dragged() {
+              seek = Some(Time::from_seconds(pos));
+            }
+
+            ui.label(format!("{} / {}", format_time(Time::from_seconds(pos)), format_time(clock.length())));
+
+            let current_speed = clock.speed() / rate;
+
+            egui::ComboBox::from_id_salt("replay_speed")
+              .selected_text(format!("{}x", current_speed))
+              .width(64.0)
+              .show_ui(ui, |ui| {
+                for speed in SPEEDS {
+                  if ui.selectable_label(current_speed == speed, format!("{}x", speed)).clicked() {
+                    clock.set_speed(speed * rate);
+                  }
+                }
+              });
+          });
+        });
+      });
+
+    return seek;
+  }
+}
+
+fn format_time(time: Time) -> String {
+  let seconds = time.to_seconds().max(0.0) as u64;
+  return format!("{}:{:02}", seconds / 60, seconds % 60);
+}
@@ -0,0 +1,46 @@
+use apex_framework::core::Core;
+
+use crate::client::client::Client;
+
+pub struct SwellOverlayView {}
+
+impl SwellOverlayView {
+  pub fn new() -> Self {
+    return Self {};
+  }
+
+  pub fn prepare(
+    &mut self,
+    core: &Core<Client>,
+    remaining_hits: usize,
+    required_hits: usize,
+    hit_position_x: f32,
+    hit_position_y: f32,
+    gameplay_scale: f32,
+  ) {
+    egui::CentralPanel::default().frame(egui::Frame::none()).show(core.egui.ctx(), |ui| {
+      const COUNTER_TEXT_SIZE: f32 = 48.0;
+
+      let center = egui::pos2(hit_position_x, hit_position_y);
+      let radius = 128.0 * 1.55 / 2.0 * gameplay_scale + 12.0;
+
+      // Ring around the swell which shrinks as it's being hit
+      let progress = 1.0 - remaining_hits as f32 / required_hits.max(1) as f32;
+      ui.painter().circle_stroke(
+        center,
+        radius * (1.0 - progress * 0.5),
+        egui::Stroke::new(4.0, egui::Color32::from_rgba_unmultiplied(255, 255, 255, 160)),
+      );
+
+      let text = ui.painter().layout_no_wrap(
+        format!("{}", remaining_hits),
+        egui::FontId::proportional(COUNTER_TEXT_SIZE),
+        egui::Color32::PLACEHOLDER,
+      );
+
+      let size = text.size();
+      let pos = egui::pos2(center.x - size.x / 2.0, center.y - radius - size.y - 8.0);
+      ui.painter().galley(pos, text, ui.style().visuals.strong_text_color());
+    });
+  }
+}
@@ -0,0 +1,81 @@
+mod common;
+
+use apex_client::client::gameplay::{
+  autoplay::autoplay_hits,
+  simulator::{simulate, SimulatorConfig},
+  taiko_hit_object::{TaikoColor, TaikoHit, TaikoHitObject},
+  taiko_player::TaikoInput,
+};
+use apex_framework::time::time::Time;
+use common::valid_beatmap;
+
+#[test]
+fn autoplay_hits_every_object() {
+  let beatmap = valid_beatmap();
+  let hits = autoplay_hits(&beatmap);
+  let hits = hits.iter().map(|(time, input)| (time.to_ms(), *input)).collect::<Vec<_>>();
+
+  assert!(hits.is_sorted_by_key(|(time, _)| *time));
+
+  // Small circles alternate between hands, big ones are hit with both
+  assert_eq!(
+    hits[.. 4],
+    [
+      (1000, TaikoInput::DonLeft),
+      (1500, TaikoInput::KatRight),
+      (2000, TaikoInput::DonLeft),
+      (2000, TaikoInput::DonRight),
+    ]
+  );
+
+  let TaikoHitObject::Drumroll(drumroll) = &beatmap.hit_objects[3] else {
+    panic!("expected a drumroll");
+  };
+  let ticks = &hits[4 .. 4 + drumroll.tick_count];
+  for (idx, (time, input)) in ticks.iter().enumerate() {
+    assert_eq!(*time, drumroll.tick_time(idx).to_ms());
+    assert_eq!(input.color(), TaikoColor::Don);
+  }
+
+  let TaikoHitObject::Swell(swell) = &beatmap.hit_objects[4] else {
+    panic!("expected a swell");
+  };
+  let swell_hits = &hits[4 + drumroll.tick_count ..];
+  assert_eq!(swell_hits.len(), swell.required_hits);
+  assert!(swell_hits.iter().all(|(time, _)| *time >= swell.time.to_ms() && *time < swell.end_time().to_ms()));
+  assert!(swell_hits.windows(2).all(|x| x[0].1.color() != x[1].1.color()));
+}
+
+#[test]
+fn autoplay_hits_finishers_with_both_keys() {
+  // Finishers 60ms apart, so a second key which doesn't belong to its finisher would hit the next one as a 150
+  let finishers = [
+    (1000.0, TaikoColor::Don),
+    (1060.0, TaikoColor::Don),
+    (1120.0, TaikoColor::Kat),
+    (1180.0, TaikoColor::Kat),
+    (1240.0, TaikoColor::Don),
+  ];
+
+  let mut beatmap = valid_beatmap();
+  beatmap.hit_objects.splice(
+    .. 3,
+    finishers.map(|(time, color)| {
+      TaikoHitObject::Hit(TaikoHit {
+        time: Time::from_ms(time),
+        color,
+        big: true,
+        ..Default::default()
+      })
+    }),
+  );
+
+  let inputs = autoplay_hits(&beatmap);
+  assert_eq!(inputs.iter().filter(|(time, _)| time.to_ms() < 2000).count(), 2 * finishers.len());
+
+  let result = simulate(beatmap, &SimulatorConfig::default(), &inputs);
+  assert_eq!(result.score.result_300s(), finishers.len());
+  assert_eq!(result.score.result_150s(), 0);
+  assert_eq!(result.score.result_misses(), 0);
+  assert_eq!(result.score.max_combo(), finishers.len());
+}
@@ -0,0 +1,100 @@
+use std::{
+  io::{Cursor, Write as _},
+  path::{Path, PathBuf},
+};
+
+use ahash::AHashMap;
+use apex_client::client::gameplay::{beatmap_import_error::BeatmapImportError, beatmap_importer::import_archive};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+fn fixture(name: &str) -> Vec<u8> {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps").join(name);
+  return std::fs::read(path).unwrap();
+}
+
+fn archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+  let mut writer = ZipWriter::new(Cursor::new(Vec::new()));
+
+  for (name, data) in entries {
+    writer.start_file(*name, SimpleFileOptions::default()).unwrap();
+    writer.write_all(data).unwrap();
+  }
+
+  return writer.finish().unwrap().into_inner();
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+  let dir = std::env::temp_dir().join(format!("apex-beatmap-import-{}-{}", name, std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  return dir;
+}
+
+#[test]
+fn imports_archive_once() {
+  let dir = temp_dir("once");
+  let file = archive(&[("valid.osu", &fixture("valid.osu")), ("audio.mp3", b"")]);
+
+  let mut known = AHashMap::new();
+  let target = import_archive(&file, &dir, &mut known).unwrap();
+
+  assert_eq!(target, dir.join("Apex - Fixture"));
+  assert!(target.join("valid.osu").is_file());
+  assert!(target.join("audio.mp3").is_file());
+
+  match import_archive(&file, &dir, &mut known) {
+    Err(BeatmapImportError::AlreadyImported(existing)) => assert_eq!(existing, target),
+    other => panic!("expected a duplicate, got {:?}", other),
+  }
+
+  // An unrelated set with the same name never overwrites the existing one
+  let mut known = AHashMap::new();
+  assert_eq!(import_archive(&file, &dir, &mut known).unwrap(), dir.join("Apex - Fixture (2)"));
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn adds_new_difficulties_to_existing_sets() {
+  let dir = temp_dir("partial");
+  let mut known = AHashMap::new();
+
+  let file = archive(&[("valid.osu", &fixture("valid.osu")), ("audio.mp3", b"original")]);
+  let target = import_archive(&file, &dir, &mut known).unwrap();
+
+  // Only the new difficulty is extracted, the files of the set stay as they are
+  let file = archive(&[
+    ("valid.osu", b"replaced"),
+    ("renamed.osu", &fixture("valid.osu")),
+    ("converted.osu", &fixture("converted.osu")),
+    ("audio.mp3", b"replaced"),
+  ]);
+  assert_eq!(import_archive(&file, &dir, &mut known).unwrap(), target);
+
+  assert_eq!(std::fs::read(target.join("valid.osu")).unwrap(), fixture("valid.osu"));
+  assert_eq!(std::fs::read(target.join("audio.mp3")).unwrap(), b"original");
+  assert!(!target.join("renamed.osu").exists());
+  assert!(target.join("converted.osu").is_file());
+  assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn rejects_invalid_archives() {
+  let dir = temp_dir("invalid");
+  let mut known = AHashMap::new();
+
+  let traversal = archive(&[("valid.osu", &fixture("valid.osu")), ("../escaped.osu", b"")]);
+  assert!(matches!(import_archive(&traversal, &dir, &mut known), Err(BeatmapImportError::UnsafePath(_))));
+
+  let empty = archive(&[("audio.mp3", b"")]);
+  assert!(matches!(import_archive(&empty, &dir, &mut known), Err(BeatmapImportError::NoBeatmaps)));
+
+  let broken = archive(&[("mania.osu", &fixture("mania.osu"))]);
+  assert!(matches!(import_archive(&broken, &dir, &mut known), Err(BeatmapImportError::InvalidBeatmap { .. })));
+
+  assert!(matches!(import_archive(b"not an archive", &dir, &mut known), Err(BeatmapImportError::Archive(_))));
+
+  // Nothing is extracted unless the whole archive is valid
+  assert!(!dir.exists());
+}
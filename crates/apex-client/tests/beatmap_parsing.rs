@@ -0,0 +1,468 @@
+use std::path::{Path, PathBuf};
+
+use apex_client::client::gameplay::{
+  beatmap::{calc_swell_required_hits, Beatmap, SampleSet},
+  beatmap_cache::{BeatmapCache, BeatmapChange, BeatmapInfo},
+  beatmap_parse_error::BeatmapParseError,
+  taiko_hit_object::TaikoHitObject,
+};
+use apex_framework::time::time::Time;
+use rusqlite::Connection;
+
+fn fixtures_dir() -> PathBuf {
+  return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps");
+}
+
+fn fixture(name: &str) -> (String, PathBuf) {
+  let path = fixtures_dir().join(name);
+  let data = std::fs::read_to_string(&path).unwrap();
+  return (data, path);
+}
+
+#[test]
+fn parses_valid_beatmap() {
+  let (data, path) = fixture("valid.osu");
+  let beatmap = Beatmap::parse(&data, path.clone()).unwrap();
+
+  assert_eq!(beatmap.title, "Fixture");
+  assert_eq!(beatmap.variant, "Oni");
+  assert_eq!(beatmap.hit_objects.len(), 5);
+  assert!(matches!(beatmap.hit_objects[3], TaikoHitObject::Drumroll(_)));
+  assert!(matches!(beatmap.hit_objects[4], TaikoHitObject::Swell(_)));
+
+  let mut warnings = Vec::new();
+  let lenient = Beatmap::parse_lenient(&data, path.clone(), &mut warnings).unwrap();
+  assert!(warnings.is_empty());
+  assert_eq!(lenient.hash(), beatmap.hash());
+
+  let info = BeatmapInfo::parse(&data, path).unwrap();
+  assert_eq!(info.title, "Fixture");
+}
+
+#[test]
+fn converts_sliders_to_drumrolls() {
+  let (data, path) = fixture("valid.osu");
+
+  let drumroll = |data: &str| {
+    let beatmap = Beatmap::parse(data, path.clone()).unwrap();
+    let TaikoHitObject::Drumroll(drumroll) = &beatmap.hit_objects[3] else {
+      panic!("expected a drumroll");
+    };
+
+    return (drumroll.duration.to_ms(), drumroll.tick_spacing.to_ms(), drumroll.tick_count);
+  };
+
+  // 140px at a slider multiplier of 1.4 last one beat at 120 BPM, osu!stable puts a tick on every 1/4 including the end
+  assert_eq!(drumroll(&data), (500, 125, 5));
+
+  // A tick rate of 3 gives 1/3 ticks instead
+  assert_eq!(drumroll(&data.replace("SliderTickRate:1", "SliderTickRate:3")), (500, 167, 4));
+
+  // Doubled slider velocity halves the duration, ticks are still spaced by the uninherited beat length
+  let data = data.replace("0,500,4,1,0,100,1,0", "0,500,4,1,0,100,1,0\n2500,-50,4,1,0,100,0,0");
+  assert_eq!(drumroll(&data), (250, 125, 3));
+}
+
+#[test]
+fn converts_spinners_to_swells() {
+  let (data, path) = fixture("valid.osu");
+  let beatmap = Beatmap::parse(&data, path).unwrap();
+
+  let TaikoHitObject::Swell(swell) = &beatmap.hit_objects[4] else {
+    panic!("expected a swell");
+  };
+  assert_eq!((swell.time.to_ms(), swell.duration.to_ms()), (4000, 1000));
+  assert_eq!(swell.required_hits, 8);
+
+  // Required hits of osu!stable for a given overall difficulty and duration, at least one
+  let cases = [
+    (0.0, 1000.0, 4),
+    (5.0, 1000.0, 8),
+    (8.0, 1000.0, 10),
+    (10.0, 2000.0, 24),
+    (5.0, 0.0, 1),
+  ];
+  for (od, duration, required_hits) in cases {
+    assert_eq!(calc_swell_required_hits(od, Time::from_ms(duration)), required_hits, "OD {} {}ms", od, duration);
+  }
+}
+
+#[test]
+fn converts_standard_beatmaps() {
+  let (data, path) = fixture("converted.osu");
+  let beatmap = Beatmap::parse(&data, path.clone()).unwrap();
+
+  let objects = beatmap
+    .hit_objects
+    .iter()
+    .map(|x| {
+      match x {
+        TaikoHitObject::Hit(hit) => (hit.time.to_ms(), if hit.color.is_kat() { "kat" } else { "don" }),
+        TaikoHitObject::Drumroll(drumroll) => (drumroll.time.to_ms(), "drumroll"),
+        TaikoHitObject::Swell(swell) => (swell.time.to_ms(), "swell"),
+      }
+    })
+    .collect::<Vec<_>>();
+
+  // The short slider is split into hits using its edge sounds, the long one stays a drumroll
+  assert_eq!(
+    objects,
+    [
+      (1000, "don"),
+      (2000, "don"),
+      (2250, "kat"),
+      (3000, "drumroll"),
+      (6000, "swell"),
+    ]
+  );
+
+  assert!(BeatmapInfo::parse(&data, path).unwrap().converted);
+
+  let (data, path) = fixture("valid.osu");
+  assert!(!BeatmapInfo::parse(&data, path).unwrap().converted);
+}
+
+#[test]
+fn parses_timing_point_effects() {
+  let (data, path) = fixture("valid.osu");
+  let timing_points = "0,500,4,3,1,70,1,8\n1000,-50,4,2,0,40,0,1\n3000,500,4,1,0,100,1,0\n";
+  let data = data.replace("0,500,4,1,0,100,1,0\n", timing_points);
+  let beatmap = Beatmap::parse(&data, path).unwrap();
+
+  assert!(beatmap.timing_points[0].omit_first_barline);
+  assert!(!beatmap.timing_points[1].omit_first_barline);
+
+  assert_eq!(beatmap.effect_points.len(), 3);
+  assert_eq!(beatmap.effect_points[0].sample_set, SampleSet::Drum);
+  assert_eq!(beatmap.effect_points[0].sample_index, 1);
+  assert_eq!(beatmap.effect_points[0].volume, 70);
+  assert_eq!(beatmap.effect_points[1].sample_set, SampleSet::Soft);
+
+  assert!(!beatmap.is_kiai(Time::from_ms(500.0)));
+  assert!(beatmap.is_kiai(Time::from_ms(1000.0)));
+  assert!(!beatmap.is_kiai(Time::from_ms(3000.0)));
+
+  // Kiai flashes on every beat and fades out until the next one
+  assert_eq!(beatmap.kiai_flash(Time::from_ms(1000.0)), 1.0);
+  assert_eq!(beatmap.kiai_flash(Time::from_ms(1250.0)), 0.5);
+  assert_eq!(beatmap.kiai_flash(Time::from_ms(500.0)), 0.0);
+
+  // The first barline of the first timing point is omitted, the second one starts a new measure
+  let barlines = beatmap.barlines().iter().map(|x| x.to_ms()).collect::<Vec<_>>();
+  assert_eq!(barlines, [2000, 3000, 5000]);
+}
+
+#[test]
+fn inherits_hit_samples() {
+  let (data, path) = fixture("valid.osu");
+  let data = data
+    .replace("0,500,4,1,0,100,1,0\n", "0,500,4,3,2,70,1,0\n")
+    .replace("256,192,1500,1,2,0:0:0:0:", "256,192,1500,1,2,2:1:0:40:");
+  let beatmap = Beatmap::parse(&data, path).unwrap();
+
+  // Everything is inherited from the timing point
+  let samples = beatmap.hit_objects[0].samples();
+  assert_eq!((samples.sample_set, samples.addition_set), (SampleSet::Drum, SampleSet::Drum));
+  assert_eq!((samples.index, samples.volume), (2, 70));
+
+  // Only the index is inherited
+  let samples = beatmap.hit_objects[1].samples();
+  assert_eq!((samples.sample_set, samples.addition_set), (SampleSet::Soft, SampleSet::Normal));
+  assert_eq!((samples.index, samples.volume), (2, 40));
+
+  // Drumrolls and swells have samples as well
+  assert_eq!(beatmap.hit_objects[3].samples().sample_set, SampleSet::Drum);
+  assert_eq!(beatmap.hit_objects[4].samples().volume, 70);
+}
+
+#[test]
+fn reports_malformed_lines() {
+  for (name, expected_section, expected_line) in [
+    ("bad_hit_object.osu", "[HitObjects]", 37),
+    ("bad_timing_point.osu", "[TimingPoints]", 30),
+  ] {
+    let (data, path) = fixture(name);
+
+    let Err(BeatmapParseError::InvalidLine { section, line, .. }) = Beatmap::parse(&data, path.clone()) else {
+      panic!("{} should fail to parse in strict mode", name);
+    };
+
+    assert_eq!(section, expected_section, "{}", name);
+    assert_eq!(line, expected_line, "{}", name);
+
+    let mut warnings = Vec::new();
+    let beatmap = Beatmap::parse_lenient(&data, path, &mut warnings).unwrap();
+    assert_eq!(warnings.len(), 1, "{}", name);
+    assert_eq!(beatmap.hit_objects.len(), 5, "{}", name);
+  }
+}
+
+#[test]
+fn rejects_missing_properties() {
+  for (name, expected_section, expected_key) in [
+    ("missing_title.osu", "[Metadata]", "Title"),
+    ("missing_audio.osu", "[General]", "AudioFilename"),
+    ("empty.osu", "[Metadata]", "Title"),
+  ] {
+    let (data, path) = fixture(name);
+
+    let mut warnings = Vec::new();
+    let Err(BeatmapParseError::MissingProperty { section, key }) = Beatmap::parse_lenient(&data, path, &mut warnings)
+    else {
+      panic!("{} should fail to parse", name);
+    };
+
+    assert_eq!(section, expected_section, "{}", name);
+    assert_eq!(key, expected_key, "{}", name);
+  }
+}
+
+#[test]
+fn rejects_unsupported_modes() {
+  let (data, path) = fixture("mania.osu");
+
+  let result = Beatmap::parse(&data, path);
+  assert!(matches!(result, Err(BeatmapParseError::UnsupportedMode(mode)) if mode == "3"));
+}
+
+#[test]
+fn reports_missing_files() {
+  let path = fixtures_dir().join("does_not_exist.osu");
+
+  assert!(matches!(Beatmap::from_path(&path), Err(BeatmapParseError::Io(_))));
+  assert!(matches!(BeatmapInfo::from_path(&path), Err(BeatmapParseError::Io(_))));
+
+  // The cause is part of the message
+  let cause = std::fs::read(&path).unwrap_err();
+  let error = Beatmap::from_path(&path).err().unwrap();
+  assert_eq!(error.to_string(), format!("Failed to read beatmap file: {}", cause));
+}
+
+#[test]
+fn cache_skips_broken_beatmaps() {
+  let mut cache = BeatmapCache::new(Connection::open_in_memory().unwrap());
+  cache.load_difficulties(fixtures_dir());
+
+  let mut broken = cache
+    .broken()
+    .iter()
+    .map(|(path, _)| path.file_name().unwrap().to_str().unwrap().to_owned())
+    .collect::<Vec<_>>();
+
+  broken.sort();
+  assert_eq!(broken, ["empty.osu", "mania.osu", "missing_audio.osu", "missing_title.osu"]);
+
+  // The broken line of `bad_hit_object.osu` is skipped, which leaves the same beatmap as `valid.osu`
+  let valid = Beatmap::from_path(fixtures_dir().join("valid.osu")).unwrap();
+  assert!(cache.get(valid.hash()).is_some());
+
+  // Every course of a TJA chart is a separate difficulty
+  let courses = cache.iter().filter(|(_, info)| info.file_path.ends_with("chart.tja")).map(|(_, info)| info.course);
+  assert_eq!(courses.collect::<Vec<_>>(), [Some(0), Some(1)]);
+}
+
+#[test]
+fn cache_groups_difficulties_into_sets() {
+  let mut cache = BeatmapCache::new(Connection::open_in_memory().unwrap());
+  cache.load_difficulties(fixtures_dir());
+
+  // Nothing was removed, so positions are cache indices
+  let chart_idx = cache.iter().position(|(_, info)| info.file_path.ends_with("chart.tja")).unwrap();
+  let (_, chart_set) = cache.set_of(chart_idx).unwrap();
+  assert_eq!(chart_set.difficulties().len(), 2);
+
+  for (_, set) in cache.sets() {
+    let stars = set.difficulties().iter().map(|idx| cache.get_index(*idx).unwrap().1.difficulty).collect::<Vec<_>>();
+    assert!(stars.windows(2).all(|x| x[0] <= x[1]));
+  }
+}
+
+#[test]
+fn cache_only_parses_changed_files() {
+  let dir = std::env::temp_dir().join(format!("apex-beatmap-index-{}", std::process::id()));
+  let beatmaps_dir = dir.join("beatmaps");
+  let beatmap_path = beatmaps_dir.join("1 Fixture").join("valid.osu");
+  let db_path = dir.join("index.db");
+
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(beatmap_path.parent().unwrap()).unwrap();
+  std::fs::copy(fixtures_dir().join("valid.osu"), &beatmap_path).unwrap();
+
+  let scan = || {
+    let (tx, rx) = std::sync::mpsc::channel();
+    let mut cache = BeatmapCache::new(Connection::open(&db_path).unwrap());
+    cache.load_beatmaps(&beatmaps_dir, move |progress| tx.send(progress).unwrap());
+    cache.wait_for_scan();
+
+    let progress = rx.try_iter().last().unwrap();
+    assert!(progress.is_done());
+    return (cache, progress.total);
+  };
+
+  let (cache, parsed) = scan();
+  let (hash, info) = cache.get_index(0).unwrap();
+  assert_eq!((cache.len(), parsed), (1, 1));
+
+  // Garbage of the same size and modification time is never read, the index still has the beatmap
+  let modified = std::fs::metadata(&beatmap_path).unwrap().modified().unwrap();
+  let size = std::fs::metadata(&beatmap_path).unwrap().len() as usize;
+  std::fs::write(&beatmap_path, "x".repeat(size)).unwrap();
+  std::fs::File::options().write(true).open(&beatmap_path).unwrap().set_modified(modified).unwrap();
+
+  let (cache, parsed) = scan();
+  assert_eq!(parsed, 0);
+  assert!(cache.broken().is_empty());
+  assert_eq!(cache.get(hash).map(|x| (&x.title, x.difficulty)), Some((&info.title, info.difficulty)));
+
+  // Changed files are parsed again
+  std::fs::write(&beatmap_path, "").unwrap();
+  let (cache, parsed) = scan();
+  assert_eq!(parsed, 1);
+  assert!(cache.is_empty());
+  assert_eq!(cache.broken().len(), 1);
+
+  // Broken files are remembered as well and only parsed again once they change
+  let error = cache.broken()[0].1.to_string();
+  let (cache, parsed) = scan();
+  assert_eq!(parsed, 0);
+  assert_eq!(cache.broken().iter().map(|(_, e)| e.to_string()).collect::<Vec<_>>(), [error]);
+
+  // Deleted files are removed from the index
+  std::fs::remove_file(&beatmap_path).unwrap();
+  let (cache, _) = scan();
+  assert!(cache.is_empty() && cache.broken().is_empty());
+
+  let conn = Connection::open(&db_path).unwrap();
+  for table in ["beatmaps", "broken_beatmaps"] {
+    let count = conn.query_row(&format!("select count(*) from {}", table), (), |row| row.get::<_, i64>(0)).unwrap();
+    assert_eq!(count, 0, "{}", table);
+  }
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn watcher_tracks_added_and_removed_beatmaps() {
+  let dir = std::env::temp_dir().join(format!("apex-beatmap-watch-{}", std::process::id()));
+  let set_dir = dir.join("1 Fixture");
+
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).unwrap();
+
+  let mut cache = BeatmapCache::new(Connection::open_in_memory().unwrap());
+  cache.load_beatmaps(&dir, |_| {});
+  cache.wait_for_scan();
+  cache.watch(&dir);
+
+  fn poll_until(cache: &mut BeatmapCache, done: impl Fn(&BeatmapCache) -> bool) {
+    let start = std::time::Instant::now();
+    while !done(cache) {
+      assert!(start.elapsed() < std::time::Duration::from_secs(10), "watcher did not pick up the change");
+
+      std::thread::sleep(std::time::Duration::from_millis(50));
+      cache.poll();
+    }
+  }
+
+  std::fs::create_dir_all(&set_dir).unwrap();
+  std::fs::copy(fixtures_dir().join("valid.osu"), set_dir.join("valid.osu")).unwrap();
+  poll_until(&mut cache, |cache| cache.len() == 1);
+
+  // Removing the whole set removes its beatmaps, without shifting indices around
+  std::fs::remove_dir_all(&set_dir).unwrap();
+  poll_until(&mut cache, |cache| cache.is_empty());
+
+  assert_eq!(cache.changes_since(0).last(), Some(&BeatmapChange::Removed(0)));
+  assert!(cache.get_index(0).is_none());
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cache_reuses_indices_once_removals_are_acknowledged() {
+  let dir = std::env::temp_dir().join(format!("apex-beatmap-changes-{}", std::process::id()));
+  let set_dir = dir.join("1 Fixture");
+
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&set_dir).unwrap();
+
+  let mut cache = BeatmapCache::new(Connection::open_in_memory().unwrap());
+  let replace = |cache: &mut BeatmapCache, from: &str, to: &str| {
+    std::fs::remove_file(set_dir.join(from)).unwrap();
+    std::fs::copy(fixtures_dir().join(to), set_dir.join(to)).unwrap();
+    cache.load_difficulties(&set_dir);
+  };
+
+  std::fs::copy(fixtures_dir().join("valid.osu"), set_dir.join("valid.osu")).unwrap();
+  cache.load_difficulties(&set_dir);
+
+  // Nobody has seen the removal yet, so the index stays taken
+  replace(&mut cache, "valid.osu", "converted.osu");
+  let changes = [
+    BeatmapChange::Inserted(0),
+    BeatmapChange::Removed(0),
+    BeatmapChange::Inserted(1),
+  ];
+  assert_eq!(*cache.changes_since(0), changes);
+
+  let revision = cache.revision();
+  cache.acknowledge_changes(revision);
+  assert!(cache.changes_since(revision).is_empty());
+
+  // Consumers which start after that get every index instead of the dropped changes
+  assert_eq!(*cache.changes_since(0), [BeatmapChange::Removed(0), BeatmapChange::Inserted(1)]);
+
+  // The new beatmap takes the free index, the one of the beatmap it replaces is not free yet
+  replace(&mut cache, "converted.osu", "bad_timing_point.osu");
+  assert_eq!(*cache.changes_since(revision), [BeatmapChange::Removed(1), BeatmapChange::Inserted(0)]);
+  assert!(cache.get_index(0).unwrap().1.file_path.ends_with("bad_timing_point.osu"));
+  assert_eq!(cache.len(), 1);
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn cache_updates_sets_with_their_difficulties() {
+  let dir = std::env::temp_dir().join(format!("apex-beatmap-set-{}", std::process::id()));
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(&dir).unwrap();
+
+  // Beatmaps are identified by their hit objects, so every file gets an extra hit to tell them apart
+  let (data, _) = fixture("valid.osu");
+  let write = |name: &str, bg: &str| {
+    let time = 6000 + name.as_bytes()[0] as u32;
+    let data = data.replace("\"bg.jpg\"", &format!("\"{}\"", bg)) + &format!("256,192,{},1,0,0:0:0:0:\n", time);
+    std::fs::write(dir.join(name).with_extension("osu"), data).unwrap();
+  };
+
+  let mut cache = BeatmapCache::new(Connection::open_in_memory().unwrap());
+  let set_bg = |cache: &BeatmapCache| {
+    let sets = cache.sets().map(|(_, set)| set).collect::<Vec<_>>();
+    assert_eq!(sets.len(), 1);
+
+    let first = cache.get_index(sets[0].difficulties()[0]).unwrap().1;
+    assert!(sets[0].bg_path.ends_with(&first.bg_path));
+    return first.bg_path.clone();
+  };
+
+  write("a", "a.jpg");
+  write("b", "b.jpg");
+  cache.load_difficulties(&dir);
+
+  // The set follows its remaining difficulty once the one it took its files from is gone
+  let (removed, remaining) = match set_bg(&cache) == Path::new("a.jpg") {
+    true => ("a", "b"),
+    false => ("b", "a"),
+  };
+
+  std::fs::remove_file(dir.join(removed).with_extension("osu")).unwrap();
+  cache.load_difficulties(&dir);
+  assert_eq!(set_bg(&cache), Path::new(remaining).with_extension("jpg"));
+
+  // And picks up changes of that difficulty
+  write(remaining, "changed.jpg");
+  cache.load_difficulties(&dir);
+  assert_eq!(set_bg(&cache), PathBuf::from("changed.jpg"));
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,189 @@
+use std::{fmt::Write, path::PathBuf};
+
+use apex_client::client::gameplay::beatmap::Beatmap;
+use apex_framework::time::time::Time;
+
+/// Small xorshift generator, good enough to come up with random beatmaps.
+struct Rng(u64);
+
+impl Rng {
+  fn next_u64(&mut self) -> u64 {
+    self.0 ^= self.0 << 13;
+    self.0 ^= self.0 >> 7;
+    self.0 ^= self.0 << 17;
+    return self.0;
+  }
+
+  fn range(&mut self, min: f64, max: f64) -> f64 {
+    return min + (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64 * (max - min);
+  }
+
+  fn chance(&mut self, probability: f64) -> bool {
+    return self.range(0.0, 1.0) < probability;
+  }
+}
+
+/// Generates a beatmap with fractional times, uncommon difficulty values and unknown sections.
+fn random_beatmap(rng: &mut Rng) -> String {
+  let mut data = String::new();
+
+  writeln!(data, "osu file format v14").unwrap();
+  writeln!(data).unwrap();
+  writeln!(data, "[General]").unwrap();
+  writeln!(data, "AudioFilename: audio.mp3").unwrap();
+  writeln!(data, "PreviewTime: {}", rng.range(0.0, 10000.0).round()).unwrap();
+  writeln!(data, "Mode: {}", if rng.chance(0.5) { 0 } else { 1 }).unwrap();
+  writeln!(data).unwrap();
+  writeln!(data, "[Editor]").unwrap();
+  writeln!(data, "DistanceSpacing: {}", rng.range(0.1, 3.0)).unwrap();
+  writeln!(data).unwrap();
+  writeln!(data, "[Metadata]").unwrap();
+  writeln!(data, "Title:Random: {}", rng.next_u64()).unwrap();
+  writeln!(data, "Artist:Apex").unwrap();
+  writeln!(data, "Creator:apex").unwrap();
+  writeln!(data, "Version:Random").unwrap();
+  writeln!(data).unwrap();
+  writeln!(data, "[Difficulty]").unwrap();
+  writeln!(data, "HPDrainRate:{}", rng.range(0.0, 10.0)).unwrap();
+  writeln!(data, "OverallDifficulty:{:.1}", rng.range(0.0, 10.0)).unwrap();
+  writeln!(data, "SliderMultiplier:{}", rng.range(0.4, 3.6)).unwrap();
+  writeln!(data, "SliderTickRate:{}", rng.range(1.0, 4.0).floor()).unwrap();
+  writeln!(data).unwrap();
+  writeln!(data, "[Events]").unwrap();
+  writeln!(data, "0,0,\"bg.png\",0,0").unwrap();
+
+  if rng.chance(0.5) {
+    writeln!(data, "Video,{},\"video.mp4\"", rng.range(-1000.0, 0.0).round()).unwrap();
+  }
+
+  if rng.chance(0.5) {
+    writeln!(data, "//Storyboard Layer 0 (Background)").unwrap();
+    writeln!(data, "Sprite,Background,Centre,\"sb/light.png\",320,240").unwrap();
+    writeln!(data, " F,0,{},,1,0", rng.range(0.0, 10000.0).round()).unwrap();
+  }
+
+  for _ in 0 .. rng.range(0.0, 3.0) as usize {
+    let start = rng.range(0.0, 60000.0);
+    writeln!(data, "2,{},{}", start, start + rng.range(5000.0, 20000.0)).unwrap();
+  }
+
+  writeln!(data).unwrap();
+  writeln!(data, "[TimingPoints]").unwrap();
+
+  let mut time = if rng.chance(0.5) { 0.0 } else { rng.range(0.0, 2000.0) };
+  for _ in 0 .. rng.range(1.0, 10.0) as usize {
+    let sample_set = rng.next_u64() % 4;
+    let sample_index = rng.next_u64() % 3;
+    let volume = rng.next_u64() % 101;
+    let sample = format!("{},{},{}", sample_set, sample_index, volume);
+
+    if rng.chance(0.3) {
+      let effects = (rng.next_u64() % 2) | ((rng.next_u64() % 2) << 3);
+      writeln!(data, "{},{},4,{},1,{}", time, 60000.0 / rng.range(60.0, 300.0), sample, effects).unwrap();
+    } else {
+      let effects = rng.next_u64() % 2;
+      writeln!(data, "{},{},4,{},0,{}", time, -100.0 / rng.range(0.1, 10.0), sample, effects).unwrap();
+    }
+
+    time += rng.range(0.0, 10000.0);
+  }
+
+  writeln!(data).unwrap();
+  writeln!(data, "[HitObjects]").unwrap();
+
+  let mut time = rng.range(0.0, 1000.0);
+  for _ in 0 .. rng.range(0.0, 100.0) as usize {
+    let hitsounds = rng.next_u64() % 16;
+
+    let result = match rng.next_u64() % 8 {
+      0 => {
+        let slides = 1 + rng.next_u64() % 3;
+        let edges = (0 ..= slides).map(|_| (rng.next_u64() % 16).to_string()).collect::<Vec<_>>();
+        let length = rng.range(10.0, 500.0);
+
+        writeln!(data, "256,192,{},2,{},L|356:192,{},{},{}", time, hitsounds, slides, length, edges.join("|"))
+      }
+      1 => writeln!(data, "256,192,{},12,0,{}", time, time + rng.range(100.0, 5000.0)),
+      _ => {
+        let (sample_set, addition_set) = (rng.next_u64() % 4, rng.next_u64() % 4);
+        let (index, volume) = (rng.next_u64() % 3, rng.next_u64() % 101);
+        let samples = format!("{}:{}:{}:{}:", sample_set, addition_set, index, volume);
+
+        writeln!(data, "256,192,{},1,{},{}", time, hitsounds, samples)
+      }
+    };
+
+    result.unwrap();
+
+    time += rng.range(0.0, 1000.0);
+  }
+
+  return data;
+}
+
+fn write(beatmap: &Beatmap) -> String {
+  let mut data = Vec::new();
+  beatmap.write(&mut data).unwrap();
+  return String::from_utf8(data).unwrap();
+}
+
+#[test]
+fn roundtrip_preserves_fixture() {
+  let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps/valid.osu");
+  let beatmap = Beatmap::from_path(&path).unwrap();
+
+  let written = Beatmap::parse(write(&beatmap), path).unwrap();
+  assert_eq!(written.hash(), beatmap.hash());
+  assert_eq!(written.title, beatmap.title);
+  assert_eq!(written.audio_path, beatmap.audio_path);
+  assert_eq!(written.bg_path, beatmap.bg_path);
+  assert_eq!(written.properties, beatmap.properties);
+}
+
+#[test]
+fn roundtrip_preserves_tja_gogo_time() {
+  let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps/chart.tja");
+  let beatmap = Beatmap::from_tja_course(&path, 0).unwrap();
+
+  // Gogo time ends without a scroll change, so it needs a timing point of its own
+  let written = Beatmap::parse(write(&beatmap), path).unwrap();
+  for time in [4000.0, 5000.0, 6500.0, 7000.0, 8000.0] {
+    assert_eq!(written.is_kiai(Time::from_ms(time)), beatmap.is_kiai(Time::from_ms(time)), "{}ms", time);
+  }
+
+  assert_eq!(written.hit_objects.len(), beatmap.hit_objects.len());
+}
+
+#[test]
+fn roundtrip_preserves_hash() {
+  let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+
+  for i in 0 .. 500 {
+    let data = random_beatmap(&mut rng);
+    let beatmap = Beatmap::parse(&data, PathBuf::new()).unwrap();
+
+    let written = write(&beatmap);
+    let parsed = Beatmap::parse(&written, PathBuf::new())
+      .unwrap_or_else(|e| panic!("beatmap {} failed to parse after writing: {}\n{}", i, e, written));
+
+    assert_eq!(parsed.hash(), beatmap.hash(), "beatmap {} changed after writing:\n{}\n{}", i, data, written);
+    assert_eq!(parsed.title, beatmap.title, "beatmap {}", i);
+    // Converted osu!standard beatmaps are written as taiko ones
+    let mut properties = beatmap.properties.clone();
+    properties["[General]"].insert("Mode".to_owned(), "1".to_owned());
+    assert_eq!(parsed.properties, properties, "beatmap {}", i);
+    assert_eq!(parsed.bg_path, beatmap.bg_path, "beatmap {}", i);
+    assert_eq!(parsed.events, beatmap.events, "beatmap {}", i);
+
+    for (a, b) in parsed.hit_objects.iter().zip(&beatmap.hit_objects) {
+      assert_eq!(a.samples(), b.samples(), "beatmap {}", i);
+    }
+
+    for time in beatmap.velocity_points.iter().map(|p| p.time) {
+      assert_eq!(parsed.is_kiai(time), beatmap.is_kiai(time), "beatmap {}", i);
+    }
+
+    // Writing is deterministic, so the second round has to be identical
+    assert_eq!(write(&parsed), written, "beatmap {}", i);
+  }
+}
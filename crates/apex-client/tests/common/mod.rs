@@ -0,0 +1,11 @@
+use std::path::Path;
+
+use apex_client::client::gameplay::beatmap::Beatmap;
+
+/// Don at 1000ms, kat at 1500ms, big don at 2000ms, then a drumroll and a swell. OD 5 and HP 5, so 300s are within 35ms
+/// and 150s within 80ms.
+pub fn valid_beatmap() -> Beatmap {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps/valid.osu");
+  let data = std::fs::read_to_string(&path).unwrap();
+  return Beatmap::parse(&data, path).unwrap();
+}
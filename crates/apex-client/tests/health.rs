@@ -0,0 +1,110 @@
+mod common;
+
+use apex_client::client::{
+  gameplay::mods::{Mod, Mods},
+  score::{
+    health_processor::{HealthProcessor, PASS_HEALTH},
+    judgement_processor::Judgement,
+  },
+};
+use common::valid_beatmap;
+
+#[test]
+fn starts_empty() {
+  let health = HealthProcessor::new(&valid_beatmap(), Mods::default());
+
+  assert_eq!(health.health(), 0.0);
+  assert!(!health.has_failed());
+}
+
+#[test]
+fn perfect_play_fills_the_bar() {
+  let mut health = HealthProcessor::new(&valid_beatmap(), Mods::default());
+
+  for _ in 0 .. 3 {
+    health.feed(Judgement::Hit300);
+  }
+
+  assert_eq!(health.health(), 1.0);
+  assert!(!health.has_failed());
+}
+
+#[test]
+fn bonus_objects_dont_affect_health() {
+  let mut health = HealthProcessor::new(&valid_beatmap(), Mods::default());
+  health.feed(Judgement::Hit300);
+  let before = health.health();
+
+  health.feed(Judgement::DrumrollTick);
+  health.feed(Judgement::SwellTick);
+  health.feed(Judgement::Swell);
+
+  assert_eq!(health.health(), before);
+}
+
+#[test]
+fn cant_fail_without_circles() {
+  let mut beatmap = valid_beatmap();
+  beatmap.hit_objects.drain(.. 3);
+
+  // Only the drumroll and the swell are left, which never fill the bar
+  let mut health = HealthProcessor::new(&beatmap, Mods::default());
+  health.feed(Judgement::DrumrollTick);
+  health.feed(Judgement::Swell);
+
+  assert_eq!(health.health(), 0.0);
+  assert!(!health.has_failed());
+}
+
+#[test]
+fn fails_once_passing_is_impossible() {
+  let mut health = HealthProcessor::new(&valid_beatmap(), Mods::default());
+
+  health.feed(Judgement::Miss);
+  assert_eq!(health.health(), 0.0);
+  assert!(!health.has_failed());
+
+  // A single perfect hit on the last circle can't make up for two misses
+  health.feed(Judgement::Miss);
+  assert!(health.has_failed());
+}
+
+#[test]
+fn fails_below_threshold_at_the_end() {
+  let mut health = HealthProcessor::new(&valid_beatmap(), Mods::default());
+
+  for _ in 0 .. 3 {
+    assert!(!health.has_failed());
+    health.feed(Judgement::Hit150);
+  }
+
+  assert!(health.health() < PASS_HEALTH);
+  assert!(health.has_failed());
+}
+
+#[test]
+fn misses_drain_up_to_a_tenth_of_the_bar() {
+  let mut beatmap = valid_beatmap();
+  beatmap.hp_drain_rate = 10.0;
+
+  let mut health = HealthProcessor::new(&beatmap, Mods::default());
+  health.feed(Judgement::Hit300);
+  health.feed(Judgement::Hit300);
+  let before = health.health();
+
+  health.feed(Judgement::Miss);
+  assert!((before - health.health() - 0.1).abs() < 1e-9);
+}
+
+#[test]
+fn extra_lives_refill_the_bar() {
+  let mut health = HealthProcessor::new(&valid_beatmap(), Mods::from_iter([Mod::Easy]));
+  assert_eq!(health.lives(), 2);
+
+  health.feed(Judgement::Miss);
+  health.feed(Judgement::Miss);
+
+  assert!(!health.has_failed());
+  assert_eq!(health.health(), 1.0);
+  assert_eq!(health.lives(), 1);
+}
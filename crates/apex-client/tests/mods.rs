@@ -0,0 +1,249 @@
+mod common;
+
+use apex_client::client::{
+  gameplay::{
+    beatmap::Beatmap,
+    mods::{DifficultyAdjust, Mod, Mods},
+    taiko_hit_object::{TaikoColor, TaikoHit, TaikoHitObject},
+  },
+  score::{judgement_processor::Judgement, score_processor::ScoreProcessor},
+};
+use apex_framework::time::time::Time;
+use common::valid_beatmap;
+use jiff::Timestamp;
+
+#[test]
+fn rate_mods_are_exclusive() {
+  let mut mods = Mods::default();
+
+  mods.insert(Mod::DoubleTime);
+  mods.insert(Mod::HalfTime);
+  assert!(!mods.contains(Mod::DoubleTime));
+  assert!(mods.contains(Mod::HalfTime));
+
+  mods.toggle(Mod::Nightcore);
+  assert_eq!(mods.iter().collect::<Vec<_>>(), vec![Mod::Nightcore]);
+
+  mods.toggle(Mod::Nightcore);
+  assert!(mods.is_empty());
+}
+
+#[test]
+fn rate_and_pitch() {
+  let cases = [
+    (Mods::default(), 1.0, true),
+    (Mods::from_iter([Mod::DoubleTime]), 1.5, true),
+    (Mods::from_iter([Mod::Nightcore]), 1.5, false),
+    (Mods::from_iter([Mod::HalfTime]), 0.75, true),
+    (Mods::from_iter([Mod::Daycore]), 0.75, false),
+  ];
+
+  for (mods, rate, preserves_pitch) in cases {
+    assert_eq!(mods.rate(), rate, "{}", mods);
+    assert_eq!(mods.preserves_pitch(), preserves_pitch, "{}", mods);
+  }
+}
+
+#[test]
+fn legacy_bits_round_trip() {
+  // osu!stable stores Nightcore along with Double Time
+  let nightcore = Mods::from_iter([Mod::Nightcore]);
+  assert_eq!(nightcore.legacy_bits() as u64, Mod::Nightcore.bit() | Mod::DoubleTime.bit());
+  assert_eq!(Mods::from_legacy_bits(nightcore.legacy_bits()), nightcore);
+
+  let double_time = Mods::from_iter([Mod::DoubleTime]);
+  assert_eq!(Mods::from_legacy_bits(double_time.legacy_bits()), double_time);
+
+  // Daycore doesn't exist in osu!stable and is stored as Half Time
+  let daycore = Mods::from_iter([Mod::Daycore]);
+  assert_eq!(daycore.legacy_bits() as u64, Mod::HalfTime.bit());
+  assert_eq!(Mods::from_legacy_bits(daycore.legacy_bits()), Mods::from_iter([Mod::HalfTime]));
+
+  // and Perfect along with Sudden Death
+  let perfect = Mods::from_iter([Mod::Perfect]);
+  assert_eq!(perfect.legacy_bits() as u64, Mod::Perfect.bit() | Mod::SuddenDeath.bit());
+  assert_eq!(Mods::from_legacy_bits(perfect.legacy_bits()), perfect);
+
+  // Bits of unsupported mods, e.g. Relax, are dropped
+  assert_eq!(Mods::from_legacy_bits(1 << 7 | Mod::HalfTime.bit() as u32), Mods::from_iter([Mod::HalfTime]));
+}
+
+#[test]
+fn visibility_mods_combine_with_others() {
+  let mods = Mods::from_iter([Mod::Hidden, Mod::Flashlight, Mod::DoubleTime]);
+
+  assert_eq!(mods.iter().count(), 3);
+  assert_eq!(mods.rate(), 1.5);
+  assert_eq!(Mods::from_legacy_bits(mods.legacy_bits()), mods);
+}
+
+#[test]
+fn difficulty_mods_are_exclusive() {
+  let mut mods = Mods::from_iter([Mod::HardRock, Mod::DoubleTime]);
+
+  mods.insert(Mod::Easy);
+  assert_eq!(mods.iter().collect::<Vec<_>>(), vec![Mod::Easy, Mod::DoubleTime]);
+
+  mods.insert(Mod::DifficultyAdjust);
+  assert_eq!(mods.iter().collect::<Vec<_>>(), vec![Mod::DoubleTime, Mod::DifficultyAdjust]);
+}
+
+#[test]
+fn condition_mods_are_exclusive() {
+  let mut mods = Mods::from_iter([Mod::NoFail, Mod::Hidden]);
+
+  mods.insert(Mod::SuddenDeath);
+  assert_eq!(mods.iter().collect::<Vec<_>>(), vec![Mod::SuddenDeath, Mod::Hidden]);
+
+  mods.insert(Mod::Perfect);
+  assert_eq!(mods.iter().collect::<Vec<_>>(), vec![Mod::Perfect, Mod::Hidden]);
+}
+
+#[test]
+fn condition_mods_fail_the_play() {
+  let feed = |mods: Mods, judgements: &[Judgement]| {
+    let mut score_processor = ScoreProcessor::new(mods);
+    for judgement in judgements {
+      score_processor.feed(Time::zero(), None, *judgement);
+    }
+
+    // Exported scores are marked as failed as well
+    assert_eq!(score_processor.export(Timestamp::default(), String::new()).failed(), score_processor.has_failed());
+    return score_processor.has_failed();
+  };
+
+  let sudden_death = Mods::from_iter([Mod::SuddenDeath]);
+  assert!(!feed(sudden_death, &[Judgement::Hit300, Judgement::Hit150, Judgement::DrumrollTick]));
+  assert!(feed(sudden_death, &[Judgement::Hit300, Judgement::Miss]));
+
+  let perfect = Mods::from_iter([Mod::Perfect]);
+  assert!(!feed(perfect, &[Judgement::Hit300, Judgement::SwellTick, Judgement::Swell]));
+  assert!(feed(perfect, &[Judgement::Hit300, Judgement::Hit150]));
+
+  // Health is handled by the health processor, No Fail only ignores it
+  assert!(!feed(Mods::from_iter([Mod::NoFail]), &[Judgement::Miss, Judgement::Miss]));
+  assert!(!feed(Mods::default(), &[Judgement::Miss]));
+
+  // Running out of health fails the play through the score processor too
+  let mut score_processor = ScoreProcessor::new(Mods::default());
+  score_processor.fail();
+  assert!(score_processor.export(Timestamp::default(), String::new()).failed());
+}
+
+#[test]
+fn difficulty_settings() {
+  let hard_rock = Mods::from_iter([Mod::HardRock]);
+  assert_eq!(hard_rock.overall_difficulty(5.0), 7.0);
+  assert_eq!(hard_rock.overall_difficulty(8.0), 10.0);
+  assert_eq!(hard_rock.scroll_speed(), 1.4);
+  assert_eq!(hard_rock.extra_lives(), 0);
+
+  let easy = Mods::from_iter([Mod::Easy]);
+  assert_eq!(easy.overall_difficulty(5.0), 2.5);
+  assert_eq!(easy.hp_drain_rate(6.0), 3.0);
+  assert!(easy.scroll_speed() < 1.0);
+  assert_eq!(easy.extra_lives(), 2);
+
+  let mut adjust = Mods::from_iter([Mod::DifficultyAdjust]);
+  *adjust.difficulty_adjust_mut() = DifficultyAdjust {
+    overall_difficulty: Some(9.0),
+    hp_drain_rate: None,
+    scroll_speed: 1.2,
+  };
+
+  assert_eq!(adjust.overall_difficulty(5.0), 9.0);
+  assert_eq!(adjust.hp_drain_rate(6.0), 6.0);
+  assert_eq!(adjust.scroll_speed(), 1.2);
+
+  // Settings only apply while Difficulty Adjust is active
+  adjust.remove(Mod::DifficultyAdjust);
+  assert_eq!(adjust.overall_difficulty(5.0), 5.0);
+  assert_eq!(adjust.scroll_speed(), 1.0);
+}
+
+#[test]
+fn applied_mods_keep_the_beatmap_hash() {
+  let beatmap = valid_beatmap();
+
+  let mut modded = beatmap.clone();
+  Mods::from_iter([Mod::HardRock]).apply(&mut modded);
+
+  assert_eq!(modded.overall_difficulty, (beatmap.overall_difficulty * 1.4).min(10.0));
+  assert_eq!(modded.velocity_multiplier, beatmap.velocity_multiplier * 1.4);
+  assert_eq!(modded.hash(), beatmap.hash());
+}
+
+/// Colours of the circles, as kats.
+fn colors(beatmap: &Beatmap) -> Vec<bool> {
+  return beatmap
+    .hit_objects
+    .iter()
+    .filter_map(|x| {
+      match x {
+        TaikoHitObject::Hit(hit) => Some(hit.color.is_kat()),
+        _ => None,
+      }
+    })
+    .collect();
+}
+
+#[test]
+fn swap_inverts_colors() {
+  let beatmap = valid_beatmap();
+
+  let mut swapped = beatmap.clone();
+  Mods::from_iter([Mod::Swap]).apply(&mut swapped);
+
+  let inverted = colors(&beatmap).into_iter().map(|x| !x).collect::<Vec<_>>();
+  assert_eq!(colors(&swapped), inverted);
+  assert_eq!(swapped.hash(), beatmap.hash());
+}
+
+#[test]
+fn random_is_reproducible_by_seed() {
+  let hits = (0 .. 64).map(|i| {
+    TaikoHitObject::Hit(TaikoHit {
+      time: Time::from_ms(i as f64 * 100.0),
+      color: TaikoColor::Don,
+      ..Default::default()
+    })
+  });
+
+  let beatmap = Beatmap {
+    hit_objects: hits.collect(),
+    ..Default::default()
+  };
+
+  let randomized = |seed: u32| {
+    let mut mods = Mods::from_iter([Mod::Random]);
+    mods.set_seed(seed);
+
+    let mut modded = beatmap.clone();
+    mods.apply(&mut modded);
+    assert_eq!(modded.hash(), beatmap.hash());
+
+    return colors(&modded);
+  };
+
+  assert_eq!(randomized(7), randomized(7));
+  assert_ne!(randomized(7), randomized(8));
+  assert!(randomized(7).iter().any(|x| *x));
+}
+
+#[test]
+fn pattern_mods_are_exclusive() {
+  let mut mods = Mods::from_iter([Mod::Random]);
+
+  mods.insert(Mod::Swap);
+  assert_eq!(mods.iter().collect::<Vec<_>>(), vec![Mod::Swap]);
+
+  // Swap doesn't exist in osu!stable
+  assert_eq!(mods.legacy_bits(), 0);
+}
+
+#[test]
+fn displays_acronyms() {
+  assert_eq!(Mods::default().to_string(), "NM");
+  assert_eq!(Mods::from_iter([Mod::Daycore]).to_string(), "DC");
+  assert_eq!(Mods::from_iter([Mod::Hidden, Mod::DoubleTime]).to_string(), "DTHD");
+}
@@ -0,0 +1,102 @@
+use std::io::Write as _;
+
+use apex_client::client::{
+  gameplay::taiko_player::TaikoInput,
+  stable::{osr::OsrReplay, osr_error::OsrError, stable_writer::StableWriter},
+};
+use xz2::{
+  stream::{LzmaOptions, Stream},
+  write::XzEncoder,
+};
+
+const MILLIS_2024: i64 = 1_704_067_200_000;
+const BEATMAP_MD5: &str = "0123456789abcdef0123456789abcdef";
+
+fn compress(frames: &str) -> Vec<u8> {
+  let stream = Stream::new_lzma_encoder(&LzmaOptions::new_preset(6).unwrap()).unwrap();
+  let mut encoder = XzEncoder::new_stream(Vec::new(), stream);
+  encoder.write_all(frames.as_bytes()).unwrap();
+  return encoder.finish().unwrap();
+}
+
+/// Writes a replay the way osu!stable does, with 300, 100 and miss counts.
+fn osr(mode: u8, counts: [u16; 3], frames: &str) -> Vec<u8> {
+  let replay_data = compress(frames);
+
+  let mut writer = StableWriter::new(Vec::new());
+  writer.write_u8(mode).unwrap();
+  writer.write_i32(20240101).unwrap();
+  writer.write_string(BEATMAP_MD5).unwrap();
+  writer.write_string("peppy").unwrap();
+  writer.write_string("").unwrap();
+  for count in [counts[0], counts[1], 0, 0, 0, counts[2]] {
+    writer.write_u16(count).unwrap();
+  }
+  writer.write_i32(123_456).unwrap();
+  writer.write_u16(200).unwrap();
+  writer.write_bool(false).unwrap();
+  writer.write_i32(0).unwrap();
+  writer.write_string("").unwrap();
+  writer.write_datetime(MILLIS_2024).unwrap();
+  writer.write_i32(replay_data.len() as i32).unwrap();
+  writer.write_bytes(&replay_data).unwrap();
+  writer.write_i64(0).unwrap();
+
+  return writer.into_inner();
+}
+
+fn hit_times(replay: &OsrReplay) -> Vec<(i64, TaikoInput)> {
+  return replay.score.hits().iter().map(|(time, input)| (time.to_ms(), *input)).collect();
+}
+
+#[test]
+fn reads_taiko_replay() {
+  // Keys stay pressed across frames and the seed frame at the end doesn't advance the time
+  let frames = "0|256|-500|0,1000|256|-500|1,10|256|-500|1,10|256|-500|0,30|256|-500|10,20|256|-500|14,-12345|0|0|42,";
+  let replay = OsrReplay::read(osr(1, [180, 15, 5], frames).as_slice()).unwrap();
+
+  assert_eq!(replay.beatmap_md5, BEATMAP_MD5);
+  assert_eq!(replay.score.username(), "peppy");
+  assert_eq!(replay.score.result_300s(), 180);
+  assert_eq!(replay.score.result_150s(), 15);
+  assert_eq!(replay.score.result_misses(), 5);
+  assert_eq!(replay.score.score_points(), 123_456);
+  assert_eq!(replay.score.date().as_millisecond(), MILLIS_2024);
+
+  assert_eq!(
+    hit_times(&replay),
+    [
+      (1000, TaikoInput::DonLeft),
+      (1050, TaikoInput::KatLeft),
+      (1050, TaikoInput::KatRight),
+      (1070, TaikoInput::DonRight),
+    ]
+  );
+}
+
+#[test]
+fn round_trips_replays() {
+  let frames = "1000|256|-500|1,5|256|-500|4,200|256|-500|1,1|256|-500|0,1|256|-500|1,";
+  let replay = OsrReplay::read(osr(1, [3, 1, 0], frames).as_slice()).unwrap();
+
+  let mut exported = Vec::new();
+  replay.write(&mut exported).unwrap();
+  let reimported = OsrReplay::read(exported.as_slice()).unwrap();
+
+  assert_eq!(reimported.beatmap_md5, replay.beatmap_md5);
+  assert_eq!(reimported.score.username(), replay.score.username());
+  assert_eq!(reimported.score.result_300s(), 3);
+  assert_eq!(reimported.score.result_150s(), 1);
+  assert_eq!(reimported.score.max_combo(), replay.score.max_combo());
+  assert_eq!(reimported.score.date(), replay.score.date());
+  assert_eq!(hit_times(&reimported), hit_times(&replay));
+}
+
+#[test]
+fn rejects_other_modes() {
+  let result = OsrReplay::read(osr(0, [1, 0, 0], "1000|256|192|1,").as_slice());
+  assert!(matches!(result, Err(OsrError::UnsupportedMode(0))));
+
+  let result = OsrReplay::read(osr(1, [1, 0, 0], "1000|256").as_slice());
+  assert!(matches!(result, Err(OsrError::InvalidFrame(_))));
+}
@@ -0,0 +1,21 @@
+use apex_client::client::{
+  gameplay::taiko_player::TaikoInput,
+  score::{judgement_processor::Judgement, score_processor::ScoreProcessor},
+};
+use apex_framework::time::time::Time;
+
+#[test]
+fn bonus_judgements_keep_accuracy() {
+  let mut score = ScoreProcessor::default();
+
+  // A drumroll before the first circle leaves nothing to calculate accuracy from
+  score.feed(Time::from_ms(500.0), Some(TaikoInput::DonLeft), Judgement::DrumrollTick);
+  assert_eq!(score.accuracy(), 1.0);
+
+  score.feed(Time::from_ms(1000.0), Some(TaikoInput::DonRight), Judgement::Hit150);
+  score.feed(Time::from_ms(1100.0), Some(TaikoInput::DonLeft), Judgement::DrumrollTick);
+  score.feed(Time::from_ms(1200.0), Some(TaikoInput::KatLeft), Judgement::SwellTick);
+  score.feed(Time::from_ms(1300.0), Some(TaikoInput::DonLeft), Judgement::Swell);
+  assert_eq!(score.accuracy(), 0.5);
+  assert_eq!(score.max_combo(), 1);
+}
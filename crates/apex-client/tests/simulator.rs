@@ -0,0 +1,82 @@
+mod common;
+
+use apex_client::client::{
+  gameplay::{
+    autoplay::autoplay_hits,
+    simulator::{simulate, ObjectJudgement, SimulatorConfig},
+    taiko_player::TaikoInput,
+  },
+  score::judgement_processor::Judgement,
+};
+use apex_framework::time::time::Time;
+use common::valid_beatmap;
+
+fn circle_judgements(judgements: &[ObjectJudgement]) -> Vec<Judgement> {
+  return judgements.iter().filter(|x| x.object < 3).map(|x| x.judgement).collect();
+}
+
+#[test]
+fn autoplay_is_perfect() {
+  let beatmap = valid_beatmap();
+  let inputs = autoplay_hits(&beatmap);
+  let result = simulate(beatmap, &SimulatorConfig::default(), &inputs);
+
+  assert_eq!(result.score.result_300s(), 3);
+  assert_eq!(result.score.result_150s(), 0);
+  assert_eq!(result.score.result_misses(), 0);
+  assert_eq!(result.score.max_combo(), 3);
+  assert_eq!(result.score.accuracy(), 1.0);
+
+  // The second key of the big circle belongs to it and isn't judged on its own
+  assert_eq!(circle_judgements(&result.judgements), [Judgement::Hit300; 3]);
+  assert!(result.judgements.iter().filter(|x| x.object < 3).all(|x| x.hit_delta == Some(Time::zero())));
+
+  assert!(result.judgements.iter().any(|x| x.object == 3 && x.judgement == Judgement::DrumrollTick));
+  assert!(result.judgements.iter().any(|x| x.object == 4 && x.judgement == Judgement::Swell));
+}
+
+#[test]
+fn misses_everything_without_inputs() {
+  let result = simulate(valid_beatmap(), &SimulatorConfig::default(), &[]);
+
+  assert_eq!(result.score.result_misses(), 3);
+  assert_eq!(result.score.score_points(), 0);
+  assert!(result.score.hits().is_empty());
+
+  // Misses are judged once the hit window is over
+  let misses = result.judgements.iter().map(|x| (x.object, x.time.to_ms(), x.hit_delta)).collect::<Vec<_>>();
+  assert_eq!(misses, [(0, 1080, None), (1, 1580, None), (2, 2080, None)]);
+}
+
+#[test]
+fn judges_inputs_by_hit_window() {
+  let inputs = [
+    (Time::from_ms(966.0), TaikoInput::DonLeft),
+    (Time::from_ms(1540.0), TaikoInput::KatLeft),
+    (Time::from_ms(2000.0), TaikoInput::KatRight),
+  ];
+  let result = simulate(valid_beatmap(), &SimulatorConfig::default(), &inputs);
+
+  // Hitting the wrong color is a miss as well
+  assert_eq!(circle_judgements(&result.judgements), [Judgement::Hit300, Judgement::Hit150, Judgement::Miss]);
+  assert_eq!(result.judgements[0].hit_delta.map(|x| x.to_ms()), Some(-34));
+  assert_eq!(result.score.max_combo(), 2);
+  assert_eq!(result.score.hits(), inputs);
+}
+
+#[test]
+fn overrides_overall_difficulty() {
+  let inputs = [(Time::from_ms(1060.0), TaikoInput::DonRight)];
+
+  let result = simulate(valid_beatmap(), &SimulatorConfig::default(), &inputs);
+  assert_eq!(circle_judgements(&result.judgements)[0], Judgement::Hit150);
+
+  // 150s are only within 50ms at OD 10
+  let config = SimulatorConfig {
+    overall_difficulty: Some(10.0),
+    ..Default::default()
+  };
+  let result = simulate(valid_beatmap(), &config, &inputs);
+  assert_eq!(circle_judgements(&result.judgements)[0], Judgement::Miss);
+  assert_eq!(result.judgements[0].hit_delta, None);
+}
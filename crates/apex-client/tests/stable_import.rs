@@ -0,0 +1,248 @@
+use std::path::{Path, PathBuf};
+
+use apex_client::client::{
+  gameplay::{beatmap::Beatmap, beatmap_cache::BeatmapCache},
+  stable::{
+    collection_db::CollectionDb,
+    osu_db::OsuDb,
+    scores_db::ScoresDb,
+    stable_import::{self, SongsTransfer},
+    stable_import_error::StableImportError,
+  },
+};
+use rusqlite::Connection;
+
+/// .NET ticks of 2024-01-01T00:00:00Z.
+const TICKS_2024: i64 = 638_396_640_000_000_000;
+const MILLIS_2024: i64 = 1_704_067_200_000;
+
+const FIXTURE_MD5: &str = "0123456789abcdef0123456789abcdef";
+const FIXTURE_FOLDER: &str = "1 Apex - Fixture";
+
+/// Writes the primitives of osu!stable databases, the counterpart of `StableReader`.
+#[derive(Default)]
+struct DbWriter(Vec<u8>);
+
+impl DbWriter {
+  fn bytes(&mut self, bytes: &[u8]) -> &mut Self {
+    self.0.extend_from_slice(bytes);
+    return self;
+  }
+
+  fn u8(&mut self, value: u8) -> &mut Self {
+    return self.bytes(&[value]);
+  }
+
+  fn i32(&mut self, value: i32) -> &mut Self {
+    return self.bytes(&value.to_le_bytes());
+  }
+
+  fn i64(&mut self, value: i64) -> &mut Self {
+    return self.bytes(&value.to_le_bytes());
+  }
+
+  fn string(&mut self, value: &str) -> &mut Self {
+    if value.is_empty() {
+      return self.u8(0x00);
+    }
+
+    self.u8(0x0b);
+
+    let mut len = value.len();
+    while len >= 0x80 {
+      self.u8((len as u8 & 0x7f) | 0x80);
+      len >>= 7;
+    }
+
+    return self.u8(len as u8).bytes(value.as_bytes());
+  }
+}
+
+fn osu_db(version: i32, beatmaps: &[(&str, &str, &str)]) -> Vec<u8> {
+  let mut w = DbWriter::default();
+  w.i32(version).i32(1).u8(1).i64(0).string("player").i32(beatmaps.len() as i32);
+
+  for (md5, folder_name, osu_file) in beatmaps {
+    if version < 20191106 {
+      w.i32(0);
+    }
+
+    w.string("Apex").string("").string("Fixture").string("").string("mapper").string("Oni");
+    w.string("audio.mp3").string(md5).string(osu_file);
+
+    // Ranked status, object counts, modification date, difficulty settings and slider velocity
+    w.u8(4).bytes(&[0; 6]).i64(0).bytes(&[0; 16]).bytes(&1.4f64.to_le_bytes());
+
+    for _ in 0 .. 4 {
+      w.i32(1).u8(0x08).i32(0);
+
+      match version >= 20250107 {
+        true => w.u8(0x0c).bytes(&2.5f32.to_le_bytes()),
+        false => w.u8(0x0d).bytes(&2.5f64.to_le_bytes()),
+      };
+    }
+
+    w.i32(60).i32(60_000).i32(1_000);
+    w.i32(1).bytes(&500.0f64.to_le_bytes()).bytes(&0.0f64.to_le_bytes()).u8(1);
+    w.i32(2).i32(1).i32(0);
+    w.bytes(&[9; 4]).bytes(&[0; 2]).bytes(&0.7f32.to_le_bytes()).u8(1);
+    w.string("").string("tags").bytes(&[0; 2]).string("").u8(0).i64(0).u8(0);
+    w.string(folder_name).i64(0).bytes(&[0; 5]).i32(0).u8(0);
+  }
+
+  w.i32(0);
+  return w.0;
+}
+
+fn collection_db(collections: &[(&str, &[&str])]) -> Vec<u8> {
+  let mut w = DbWriter::default();
+  w.i32(20240101).i32(collections.len() as i32);
+
+  for (name, md5s) in collections {
+    w.string(name).i32(md5s.len() as i32);
+
+    for md5 in *md5s {
+      w.string(md5);
+    }
+  }
+
+  return w.0;
+}
+
+/// Every score has 100 GREATs, 10 GOODs and 2 misses.
+fn scores_db(scores: &[(u8, &str, i32)]) -> Vec<u8> {
+  let mut w = DbWriter::default();
+  w.i32(20240101).i32(scores.len() as i32);
+
+  for (mode, md5, mods) in scores {
+    w.string(md5).i32(1);
+    w.u8(*mode).i32(20240101).string(md5).string("player").string("replay");
+
+    for count in [100u16, 10, 0, 20, 3, 2] {
+      w.bytes(&count.to_le_bytes());
+    }
+
+    w.i32(123_456).bytes(&80u16.to_le_bytes()).u8(0).i32(*mods).string("");
+    w.i64(TICKS_2024).i32(-1).i64(0);
+
+    if mods & (1 << 23) != 0 {
+      w.bytes(&0.5f64.to_le_bytes());
+    }
+  }
+
+  return w.0;
+}
+
+fn fixture_path(name: &str) -> PathBuf {
+  return Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps").join(name);
+}
+
+#[test]
+fn reads_osu_db_versions() {
+  // Before and after the size prefix was dropped and the switch to float star ratings
+  for version in [20150203, 20191106, 20250107] {
+    let data = osu_db(
+      version,
+      &[
+        (FIXTURE_MD5, FIXTURE_FOLDER, "valid.osu"),
+        ("other", "2 Other", "other.osu"),
+      ],
+    );
+    let db = OsuDb::read(data.as_slice()).unwrap();
+
+    assert_eq!(db.version, version);
+    assert_eq!(db.player_name, "player");
+    assert_eq!(db.beatmaps.len(), 2);
+
+    let beatmap = &db.beatmaps[0];
+    assert_eq!((beatmap.artist.as_str(), beatmap.title.as_str()), ("Apex", "Fixture"));
+    assert_eq!((beatmap.md5.as_str(), beatmap.osu_file.as_str()), (FIXTURE_MD5, "valid.osu"));
+    assert_eq!((beatmap.folder_name.as_str(), beatmap.beatmapset_id, beatmap.mode), (FIXTURE_FOLDER, 1, 1));
+    assert_eq!(db.beatmaps[1].folder_name, "2 Other");
+  }
+
+  assert!(OsuDb::read(osu_db(20130101, &[]).as_slice()).is_err());
+  assert!(OsuDb::read(&osu_db(20191106, &[(FIXTURE_MD5, FIXTURE_FOLDER, "valid.osu")])[.. 40]).is_err());
+}
+
+#[test]
+fn reads_collection_and_scores_db() {
+  let db = CollectionDb::read(collection_db(&[("Favourites", &["a", "b"]), ("Empty", &[])]).as_slice()).unwrap();
+  assert_eq!(db.collections.len(), 2);
+  assert_eq!((db.collections[0].name.as_str(), db.collections[0].beatmap_md5s.len()), ("Favourites", 2));
+  assert!(db.collections[1].beatmap_md5s.is_empty());
+
+  // Target Practice stores an additional accuracy value
+  let db = ScoresDb::read(scores_db(&[(1, "a", 0), (0, "b", 1 << 23), (1, "c", 0)]).as_slice()).unwrap();
+  assert_eq!(db.scores.len(), 3);
+
+  let score = &db.scores[2];
+  assert_eq!((score.mode, score.beatmap_md5.as_str(), score.player_name.as_str()), (1, "c", "player"));
+  assert_eq!((score.count_300, score.count_100, score.count_miss), (100, 10, 2));
+  assert_eq!((score.score, score.max_combo, score.timestamp), (123_456, 80, MILLIS_2024));
+}
+
+#[test]
+fn imports_stable_installation() {
+  let dir = std::env::temp_dir().join(format!("apex-stable-import-{}", std::process::id()));
+  let osu_dir = dir.join("osu!");
+  let library = dir.join("beatmaps");
+
+  let _ = std::fs::remove_dir_all(&dir);
+  std::fs::create_dir_all(osu_dir.join("Songs").join(FIXTURE_FOLDER)).unwrap();
+  std::fs::copy(fixture_path("valid.osu"), osu_dir.join("Songs").join(FIXTURE_FOLDER).join("valid.osu")).unwrap();
+
+  // Scores and collections are matched to the library by the MD5 of the beatmap files
+  let data = std::fs::read_to_string(fixture_path("valid.osu")).unwrap();
+  let hash = Beatmap::parse(data.clone(), fixture_path("valid.osu")).unwrap().hash();
+  let md5 = format!("{:x}", md5::compute(&data));
+
+  let beatmaps = [
+    (md5.as_str(), FIXTURE_FOLDER, "valid.osu"),
+    ("missing", "3 Missing", "missing.osu"),
+  ];
+  std::fs::write(osu_dir.join("osu!.db"), osu_db(20250107, &beatmaps)).unwrap();
+  std::fs::write(osu_dir.join("collection.db"), collection_db(&[("Favourites", &[&md5, "unknown"])])).unwrap();
+  std::fs::write(osu_dir.join("scores.db"), scores_db(&[(1, &md5, 0), (1, "unknown", 0), (0, &md5, 0)])).unwrap();
+
+  let import = stable_import::import(&osu_dir, &library, SongsTransfer::Copy).unwrap();
+  assert_eq!(import.directories, [library.join(FIXTURE_FOLDER)]);
+  assert!(library.join(FIXTURE_FOLDER).join("valid.osu").is_file());
+
+  let mut beatmap_cache = BeatmapCache::new(Connection::open_in_memory().unwrap());
+  beatmap_cache.load_difficulties(library.join(FIXTURE_FOLDER));
+  let import = import.resolve(&beatmap_cache);
+
+  // Only taiko scores are imported, the one for an unknown beatmap is counted along with the collection entry
+  assert_eq!(import.unmatched, 2);
+  assert_eq!(import.scores.len(), 1);
+
+  let (score_hash, score) = &import.scores[0];
+  assert_eq!(*score_hash, hash);
+  assert_eq!((score.result_300s(), score.result_150s(), score.result_misses()), (100, 10, 2));
+  assert_eq!((score.score_points(), score.max_combo(), score.username()), (123_456, 80, "player"));
+  assert_eq!(score.date().as_millisecond(), MILLIS_2024);
+  assert!((score.accuracy() - 105.0 / 112.0).abs() < 1e-6);
+
+  assert_eq!(import.collections, [(String::from("Favourites"), vec![hash])]);
+
+  // Sets which are in the library already are left alone, their scores are still matched
+  let import = stable_import::import(&osu_dir, &library, SongsTransfer::Link).unwrap();
+  assert!(import.directories.is_empty());
+  assert_eq!(import.resolve(&beatmap_cache).scores.len(), 1);
+
+  #[cfg(unix)]
+  {
+    let linked = dir.join("linked");
+    let import = stable_import::import(&osu_dir, &linked, SongsTransfer::Link).unwrap();
+    assert_eq!(import.directories, [linked.join(FIXTURE_FOLDER)]);
+    assert!(linked.join(FIXTURE_FOLDER).symlink_metadata().unwrap().file_type().is_symlink());
+  }
+
+  assert!(matches!(
+    stable_import::import(&dir, &library, SongsTransfer::Copy),
+    Err(StableImportError::NotInstalled(_))
+  ));
+
+  std::fs::remove_dir_all(&dir).unwrap();
+}
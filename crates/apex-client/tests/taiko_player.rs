@@ -0,0 +1,97 @@
+use std::path::Path;
+
+use apex_client::client::{
+  gameplay::{
+    beatmap::Beatmap,
+    taiko_player::{HitTarget, TaikoInput, TaikoPlayer},
+  },
+  score::judgement_processor::Judgement,
+};
+use apex_framework::time::time::Time;
+
+/// Plays the valid fixture starting at its drumroll, so the circles before it don't have to be missed first.
+fn play_from_drumroll(edit: impl FnOnce(String) -> String) -> TaikoPlayer {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps/valid.osu");
+  let data = edit(std::fs::read_to_string(&path).unwrap());
+
+  let mut beatmap = Beatmap::parse(data, path).unwrap();
+  beatmap.hit_objects.drain(.. 3);
+
+  let mut player = TaikoPlayer::new();
+  player.play(beatmap);
+  return player;
+}
+
+#[test]
+fn judges_drumroll_ticks() {
+  let mut player = play_from_drumroll(|data| data);
+
+  // Ticks are 125ms apart from 2500ms to 3000ms and can be hit up to half of that early or late
+  let inputs = [
+    (2500.0, TaikoInput::DonLeft),
+    (2560.0, TaikoInput::KatRight),
+    (2750.0, TaikoInput::DonRight),
+    (3050.0, TaikoInput::KatLeft),
+    (3100.0, TaikoInput::DonLeft),
+  ];
+
+  // Inputs between two ticks hit neither of them, ticks which have passed are skipped
+  let mut ticks = Vec::new();
+  for (time, input) in inputs {
+    let Some((result, target)) = player.hit(Time::from_ms(time), input) else {
+      continue;
+    };
+    let HitTarget::DrumrollTick { object: 0, tick } = target else {
+      panic!("expected a drumroll tick");
+    };
+
+    assert_eq!(result.judgement, Judgement::DrumrollTick);
+    ticks.push((tick, result.hit_delta.to_ms()));
+  }
+
+  assert_eq!(ticks, [(0, 0), (2, 0), (4, 50)]);
+}
+
+#[test]
+fn hits_the_circle_after_a_drumroll() {
+  let mut player =
+    play_from_drumroll(|data| data.replace("256,192,4000,12,0,5000,0:0:0:0:", "256,192,3050,1,2,0:0:0:0:"));
+
+  let hit = player.hit(Time::from_ms(3000.0), TaikoInput::DonLeft);
+  assert!(matches!(hit, Some((_, HitTarget::DrumrollTick { object: 0, tick: 4 }))));
+
+  // Ticks can still be hit until 3062.5ms, but with all of them gone the kat belongs to the circle
+  let (result, target) = player.hit(Time::from_ms(3040.0), TaikoInput::KatLeft).unwrap();
+  assert!(matches!(target, HitTarget::Object(1)));
+  assert_eq!(result.judgement, Judgement::Hit300);
+  assert_eq!(result.hit_delta.to_ms(), -10);
+}
+
+#[test]
+fn judges_swells() {
+  let mut player = play_from_drumroll(|data| data);
+
+  // 8 hits alternating between don and kat, starting at 4000ms
+  let mut inputs = vec![(3900.0, TaikoInput::DonLeft)];
+  for i in 0 .. 8 {
+    let input = if i % 2 == 0 { TaikoInput::DonLeft } else { TaikoInput::KatRight };
+    inputs.push((4000.0 + i as f64 * 100.0, input));
+
+    // Hitting the same color twice in a row doesn't count
+    if i == 2 {
+      inputs.push((4250.0, input));
+    }
+  }
+
+  let mut judgements = Vec::new();
+  for (time, input) in inputs {
+    if let Some((result, target)) = player.hit(Time::from_ms(time), input) {
+      assert!(matches!(target, HitTarget::Swell { object: 1, .. }));
+      judgements.push(result.judgement);
+    }
+  }
+
+  let mut expected = vec![Judgement::SwellTick; 7];
+  expected.push(Judgement::Swell);
+  assert_eq!(judgements, expected);
+}
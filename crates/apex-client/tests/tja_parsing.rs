@@ -0,0 +1,129 @@
+use std::path::{Path, PathBuf};
+
+use apex_client::client::gameplay::{
+  beatmap::Beatmap, beatmap_parse_error::BeatmapParseError, taiko_hit_object::TaikoHitObject,
+};
+use apex_framework::time::time::Time;
+
+fn fixture() -> (String, PathBuf) {
+  let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/beatmaps/chart.tja");
+  let data = std::fs::read_to_string(&path).unwrap();
+  return (data, path);
+}
+
+fn objects(beatmap: &Beatmap) -> Vec<(i64, &'static str)> {
+  return beatmap
+    .hit_objects
+    .iter()
+    .map(|x| {
+      match x {
+        TaikoHitObject::Hit(hit) if hit.big => (hit.time.to_ms(), if hit.color.is_kat() { "KAT" } else { "DON" }),
+        TaikoHitObject::Hit(hit) => (hit.time.to_ms(), if hit.color.is_kat() { "kat" } else { "don" }),
+        TaikoHitObject::Drumroll(drumroll) => (drumroll.time.to_ms(), "drumroll"),
+        TaikoHitObject::Swell(swell) => (swell.time.to_ms(), "swell"),
+      }
+    })
+    .collect();
+}
+
+#[test]
+fn parses_every_course() {
+  let (data, path) = fixture();
+  let beatmaps = Beatmap::parse_tja(&data, path).unwrap();
+  assert_eq!(beatmaps.len(), 2);
+
+  let oni = &beatmaps[0];
+  assert_eq!(oni.title, "Fixture Chart");
+  assert_eq!(oni.artist, "Apex");
+  assert_eq!(oni.variant, "Oni");
+  assert_eq!(oni.audio_path, Path::new("audio.ogg"));
+
+  assert_eq!(
+    objects(oni),
+    [
+      (1000, "don"),
+      (2000, "kat"),
+      (3000, "DON"),
+      (4000, "KAT"),
+      (5000, "drumroll"),
+      (6000, "swell"),
+    ]
+  );
+
+  // Gogo time is played as kiai
+  assert!(!oni.is_kiai(Time::from_ms(4000.0)));
+  assert!(oni.is_kiai(Time::from_ms(6500.0)));
+  assert!(!oni.is_kiai(Time::from_ms(7000.0)));
+
+  let TaikoHitObject::Drumroll(drumroll) = &oni.hit_objects[4] else {
+    unreachable!()
+  };
+  assert_eq!(drumroll.duration.to_seconds(), 0.4375);
+
+  let TaikoHitObject::Swell(swell) = &oni.hit_objects[5] else {
+    unreachable!()
+  };
+  assert_eq!(swell.duration.to_ms(), 500);
+  assert_eq!(swell.required_hits, 12);
+
+  let timing = oni.timing_points.iter().map(|x| (x.time.to_ms(), x.bpm)).collect::<Vec<_>>();
+  assert_eq!(timing, [(1000, 120.0), (5000, 240.0)]);
+
+  let velocity = oni.velocity_points.iter().map(|x| (x.time.to_ms(), x.velocity)).collect::<Vec<_>>();
+  assert_eq!(velocity, [(1000, 1.0), (5000, 1.5)]);
+
+  let barlines = oni.barlines().iter().map(|x| x.to_ms()).collect::<Vec<_>>();
+  assert_eq!(barlines, [1000, 3000, 5000, 6000]);
+
+  let normal = &beatmaps[1];
+  assert_eq!(normal.variant, "Normal");
+  assert_eq!(objects(normal), [(1000, "don"), (3000, "don"), (3500, "don"), (4000, "don")]);
+
+  // The second measure is shorter and has its barline hidden
+  let meter = normal.timing_points.iter().map(|x| (x.time.to_ms(), x.meter)).collect::<Vec<_>>();
+  assert_eq!(meter, [(1000, 4.0), (3000, 3.0)]);
+  assert_eq!(normal.barlines(), [Time::from_ms(1000.0)]);
+  assert_ne!(normal.hash(), oni.hash());
+}
+
+#[test]
+fn changes_the_measure_once_the_current_one_is_finished() {
+  let (data, path) = fixture();
+
+  // The measure which is being parsed keeps its length, only the next one is shorter
+  let data = data.replace("1,\n#MEASURE 3/4\n", "1\n#MEASURE 3/4\n1,\n");
+  let normal = &Beatmap::parse_tja(&data, path).unwrap()[1];
+  assert_eq!(
+    objects(normal),
+    [
+      (1000, "don"),
+      (2000, "don"),
+      (3000, "don"),
+      (3500, "don"),
+      (4000, "don")
+    ]
+  );
+
+  let meter = normal.timing_points.iter().map(|x| (x.time.to_ms(), x.meter)).collect::<Vec<_>>();
+  assert_eq!(meter, [(1000, 4.0), (3000, 3.0)]);
+}
+
+#[test]
+fn reports_malformed_charts() {
+  let (data, path) = fixture();
+
+  let broken = data.replace("#SCROLL 1.5", "#SCROLL fast");
+  let result = Beatmap::parse_tja(&broken, path.clone());
+  assert!(matches!(result, Err(BeatmapParseError::InvalidLine { line: 16, .. })));
+
+  let mut warnings = Vec::new();
+  let beatmaps = Beatmap::parse_tja_lenient(&broken, path.clone(), &mut warnings).unwrap();
+  assert_eq!(warnings.len(), 1);
+  assert_eq!(beatmaps.len(), 2);
+
+  let result = Beatmap::parse_tja(data.replace("WAVE:audio.ogg", ""), path.clone());
+  assert!(matches!(result, Err(BeatmapParseError::MissingProperty { key: "WAVE", .. })));
+
+  let result = Beatmap::parse_tja("TITLE:Empty\nWAVE:audio.ogg\n", path);
+  assert!(matches!(result, Err(BeatmapParseError::NoCharts)));
+}
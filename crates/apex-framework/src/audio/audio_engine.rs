@@ -7,6 +7,8 @@ use crate::time::{
   time::Time,
 };
 
+use super::rate_source::PlaybackRate;
+
 #[derive(Debug, Error)]
 pub enum AudioEngineError {
   #[error("Failed to acquire output stream")]
@@ -16,6 +18,11 @@ pub enum AudioEngineError {
   PlayError(#[from] rodio::PlayError),
 }
 
+/// Plays a single source along with a clock.
+///
+/// The sink always plays in realtime, sources which should follow the speed of the clock have to be wrapped in a
+/// [`RateSource`](super::rate_source::RateSource) of [`AudioEngine::playback_rate`]. That way sounds layered on top,
+/// like hitsounds, keep their pitch and length.
 pub struct AudioEngine {
   #[allow(unused)]
   stream: OutputStream,
@@ -26,6 +33,7 @@ pub struct AudioEngine {
   device: Device,
   sink: Sink,
   clock: Clock,
+  playback_rate: PlaybackRate,
 }
 
 impl AudioEngine {
@@ -33,6 +41,7 @@ impl AudioEngine {
     let (stream, stream_handle) = OutputStream::try_from_device(&device)?;
     let sink = Sink::try_new(&stream_handle)?;
     let clock = Clock::new();
+    let playback_rate = PlaybackRate::new();
 
     return Ok(Self {
       stream,
@@ -40,6 +49,7 @@ impl AudioEngine {
       device,
       sink,
       clock,
+      playback_rate,
     });
   }
 
@@ -69,6 +79,10 @@ impl AudioEngine {
   pub fn device(&self) -> &Device {
     return &self.device;
   }
+
+  pub fn playback_rate(&self) -> &PlaybackRate {
+    return &self.playback_rate;
+  }
 }
 
 impl AbstractClock for AudioEngine {
@@ -102,6 +116,7 @@ impl AbstractClock for AudioEngine {
 
   fn set_position(&mut self, position: Time) {
     self.clock.set_position(position);
+
     if let Err(e) = self.sink.try_seek(position.into()) {
       error!("Failed to seek audio source: {:?}", e);
     }
@@ -114,4 +129,13 @@ impl AbstractClock for AudioEngine {
   fn set_length(&mut self, value: Time) {
     self.clock.set_length(value);
   }
+
+  fn speed(&self) -> f64 {
+    return self.clock.speed();
+  }
+
+  fn set_speed(&mut self, value: f64) {
+    self.clock.set_speed(value);
+    self.playback_rate.set_rate(value);
+  }
 }
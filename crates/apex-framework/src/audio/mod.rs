@@ -5,6 +5,7 @@ pub mod audio_engine;
 pub mod audio_mixer;
 pub mod frameless_source;
 pub mod lead_in;
+pub mod rate_source;
 
 pub use audio_mixer::mixer;
 
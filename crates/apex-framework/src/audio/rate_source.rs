@@ -0,0 +1,441 @@
+use std::{
+  collections::VecDeque,
+  sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+  },
+  time::Duration,
+};
+
+use rodio::{source::SeekError, Source};
+
+/// Length of the grains which are overlapped to stretch the audio, in seconds.
+const GRAIN_DURATION: f64 = 0.04;
+
+/// How far a grain may be moved from its nominal position to line up with the previous one, in seconds.
+const GRAIN_TOLERANCE: f64 = 0.01;
+
+/// Grain positions are searched in steps this large first, then refined around the best match.
+const COARSE_STEP: usize = 8;
+
+/// Only every n-th frame is compared when lining up grains, which is plenty for finding matching waveforms.
+const COMPARE_STEP: usize = 4;
+
+/// Frames which were played already are dropped from the buffer once there are this many.
+const TRIM_FRAMES: usize = 16384;
+
+/// Frames resampled at once, the rate is only checked between batches.
+const RESAMPLE_BATCH: usize = 256;
+
+struct PlaybackRateInner {
+  rate: AtomicU64,
+  preserve_pitch: AtomicBool,
+}
+
+/// Shared playback rate of [`RateSource`]s, changes are picked up while they are playing.
+#[derive(Clone)]
+pub struct PlaybackRate(Arc<PlaybackRateInner>);
+
+impl Default for PlaybackRate {
+  fn default() -> Self {
+    return Self(Arc::new(PlaybackRateInner {
+      rate: AtomicU64::new(1.0f64.to_bits()),
+      preserve_pitch: AtomicBool::new(true),
+    }));
+  }
+}
+
+impl PlaybackRate {
+  pub fn new() -> Self {
+    return Self::default();
+  }
+
+  pub fn rate(&self) -> f64 {
+    return f64::from_bits(self.0.rate.load(Ordering::Relaxed));
+  }
+
+  pub fn set_rate(&self, value: f64) {
+    self.0.rate.store(value.to_bits(), Ordering::Relaxed);
+  }
+
+  /// Whether the audio is time-stretched, otherwise it's resampled and the pitch changes along with the rate.
+  pub fn preserves_pitch(&self) -> bool {
+    return self.0.preserve_pitch.load(Ordering::Relaxed);
+  }
+
+  pub fn set_preserve_pitch(&self, value: bool) {
+    self.0.preserve_pitch.store(value, Ordering::Relaxed);
+  }
+}
+
+pub fn rate_source<I>(input: I, rate: PlaybackRate) -> RateSource<I>
+where
+  I: Source<Item = f32>,
+{
+  return RateSource {
+    channels: input.channels().max(1) as usize,
+    input,
+    rate,
+    buffer: Vec::new(),
+    cursor: 0.0,
+    output: VecDeque::new(),
+    stretch: None,
+  };
+}
+
+/// Overlap-add state of the time-stretching, grains of the input are taken at the playback rate and laid out at the
+/// original rate, each one moved slightly to continue the waveform of the previous one (WSOLA).
+struct Stretch {
+  grain_len: usize,
+  hop: usize,
+  tolerance: usize,
+
+  window: Vec<f32>,
+
+  /// Sum of the windowed grains, the first `hop` frames are complete after every grain.
+  mixed: Vec<f32>,
+
+  /// Start of the previous grain in the buffer.
+  prev_grain: Option<usize>,
+}
+
+impl Stretch {
+  fn new(sample_rate: u32, channels: usize) -> Self {
+    let hop = ((sample_rate as f64 * GRAIN_DURATION / 2.0) as usize).max(1);
+    let grain_len = hop * 2;
+
+    // Hann windows overlapping by half of their length add up to one
+    let window = (0 .. grain_len)
+      .map(|i| (0.5 - 0.5 * (std::f64::consts::TAU * i as f64 / grain_len as f64).cos()) as f32)
+      .collect();
+
+    return Self {
+      grain_len,
+      hop,
+      tolerance: (sample_rate as f64 * GRAIN_TOLERANCE) as usize,
+      window,
+      mixed: vec![0.0; grain_len * channels],
+      prev_grain: None,
+    };
+  }
+}
+
+/// Plays its input at a [`PlaybackRate`], either time-stretched or resampled.
+///
+/// Seek positions are in the time of the input, so the source can be seeked along with a clock running at the same
+/// rate.
+pub struct RateSource<I> {
+  input: I,
+  rate: PlaybackRate,
+  channels: usize,
+
+  /// Interleaved input frames which are still needed, `cursor` is the read position in frames.
+  buffer: Vec<f32>,
+  cursor: f64,
+
+  /// Interleaved samples ready to be played.
+  output: VecDeque<f32>,
+
+  stretch: Option<Stretch>,
+}
+
+impl<I> RateSource<I>
+where
+  I: Source<Item = f32>,
+{
+  pub fn inner(&self) -> &I {
+    return &self.input;
+  }
+
+  pub fn inner_mut(&mut self) -> &mut I {
+    return &mut self.input;
+  }
+
+  fn frames(&self) -> usize {
+    return self.buffer.len() / self.channels;
+  }
+
+  /// Reads the input until the buffer holds `frames` frames or the input ends.
+  fn fill(&mut self, frames: usize) {
+    while self.frames() < frames {
+      let Some(sample) = self.input.next() else {
+        // Incomplete frames at the end are dropped
+        self.buffer.truncate(self.frames() * self.channels);
+        return;
+      };
+
+      self.buffer.push(sample);
+    }
+  }
+
+  fn trim(&mut self) {
+    let tolerance = self.stretch.as_ref().map_or(0, |x| x.tolerance);
+    let mut played = (self.cursor as usize).saturating_sub(tolerance);
+
+    if let Some(prev_grain) = self.stretch.as_ref().and_then(|x| x.prev_grain) {
+      played = played.min(prev_grain);
+    }
+
+    if played < TRIM_FRAMES {
+      return;
+    }
+
+    self.buffer.drain(.. played * self.channels);
+    self.cursor -= played as f64;
+
+    if let Some(prev_grain) = self.stretch.as_mut().and_then(|x| x.prev_grain.as_mut()) {
+      *prev_grain -= played;
+    }
+  }
+
+  /// Interpolates frames between the input frames, returns whether the input has ended.
+  fn resample(&mut self, rate: f64) -> bool {
+    for _ in 0 .. RESAMPLE_BATCH {
+      let idx = self.cursor as usize;
+      let t = (self.cursor - idx as f64) as f32;
+
+      self.fill(idx + 2);
+
+      let frames = self.frames();
+      if idx >= frames {
+        return true;
+      }
+
+      let next = (idx + 1).min(frames - 1);
+      for c in 0 .. self.channels {
+        let a = self.buffer[idx * self.channels + c];
+        let b = self.buffer[next * self.channels + c];
+        self.output.push_back(a + (b - a) * t);
+      }
+
+      self.cursor += rate;
+    }
+
+    return false;
+  }
+
+  /// Mixes in the next grain, returns whether the input has ended.
+  fn stretch(&mut self, rate: f64) -> bool {
+    let Some(stretch) = &self.stretch else {
+      return true;
+    };
+
+    let (grain_len, tolerance) = (stretch.grain_len, stretch.tolerance);
+
+    let nominal = self.cursor.round() as usize;
+    self.fill(nominal + tolerance + grain_len);
+
+    let frames = self.frames();
+    if nominal >= frames {
+      return true;
+    }
+
+    let channels = self.channels;
+    let buffer = &self.buffer;
+    let stretch = self.stretch.as_mut().unwrap();
+
+    let start = match stretch.prev_grain {
+      Some(prev_grain) => {
+        let lo = nominal.saturating_sub(tolerance);
+        let hi = (nominal + tolerance).min(frames.saturating_sub(stretch.hop)).max(lo);
+        best_grain(buffer, channels, prev_grain + stretch.hop, stretch.hop, lo, hi)
+      }
+
+      None => nominal,
+    };
+
+    for (i, weight) in stretch.window.iter().enumerate() {
+      for c in 0 .. channels {
+        let sample = buffer.get((start + i) * channels + c).copied().unwrap_or(0.0);
+        stretch.mixed[i * channels + c] += sample * weight;
+      }
+    }
+
+    let hop = stretch.hop * channels;
+    self.output.extend(&stretch.mixed[.. hop]);
+    stretch.mixed.copy_within(hop .., 0);
+    stretch.mixed[grain_len * channels - hop ..].fill(0.0);
+
+    stretch.prev_grain = Some(start);
+    self.cursor += stretch.hop as f64 * rate;
+
+    return false;
+  }
+
+  /// Produces more output, returns whether the input has ended.
+  fn refill(&mut self) -> bool {
+    let rate = self.rate.rate();
+    let stretched = rate != 1.0 && self.rate.preserves_pitch();
+
+    if stretched && self.stretch.is_none() {
+      self.stretch = Some(Stretch::new(self.input.sample_rate(), self.channels));
+    } else if !stretched && self.stretch.is_some() {
+      // Frames are passed through as they are at 1.0x
+      self.stretch = None;
+      self.cursor = self.cursor.round();
+    }
+
+    let ended = if stretched { self.stretch(rate) } else { self.resample(rate) };
+    self.trim();
+
+    return ended;
+  }
+}
+
+fn mono(buffer: &[f32], channels: usize, frame: usize) -> f32 {
+  return buffer.get(frame * channels .. (frame + 1) * channels).map_or(0.0, |x| x.iter().sum());
+}
+
+/// Finds the grain start between `lo` and `hi` which matches the `len` frames at `template` the best.
+fn best_grain(buffer: &[f32], channels: usize, template: usize, len: usize, lo: usize, hi: usize) -> usize {
+  let similarity = |start: usize| {
+    let mut dot = 0.0;
+    let mut energy = 0.0;
+
+    for j in (0 .. len).step_by(COMPARE_STEP) {
+      let a = mono(buffer, channels, template + j);
+      let b = mono(buffer, channels, start + j);
+      dot += a * b;
+      energy += b * b;
+    }
+
+    return dot / f32::sqrt(energy).max(f32::EPSILON);
+  };
+
+  let best_in = |range: std::ops::RangeInclusive<usize>, step: usize| {
+    return range
+      .step_by(step)
+      .map(|x| (x, similarity(x)))
+      .max_by(|a, b| a.1.total_cmp(&b.1))
+      .map_or(lo, |x| x.0);
+  };
+
+  let coarse = best_in(lo ..= hi, COARSE_STEP);
+  return best_in(coarse.saturating_sub(COARSE_STEP - 1).max(lo) ..= (coarse + COARSE_STEP - 1).min(hi), 1);
+}
+
+impl<I> Iterator for RateSource<I>
+where
+  I: Source<Item = f32>,
+{
+  type Item = f32;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    loop {
+      if let Some(sample) = self.output.pop_front() {
+        return Some(sample);
+      }
+
+      if self.refill() && self.output.is_empty() {
+        return None;
+      }
+    }
+  }
+}
+
+impl<I> Source for RateSource<I>
+where
+  I: Source<Item = f32>,
+{
+  fn current_frame_len(&self) -> Option<usize> {
+    return None;
+  }
+
+  fn channels(&self) -> u16 {
+    return self.input.channels();
+  }
+
+  fn sample_rate(&self) -> u32 {
+    return self.input.sample_rate();
+  }
+
+  fn total_duration(&self) -> Option<Duration> {
+    return self.input.total_duration().map(|x| x.div_f64(self.rate.rate()));
+  }
+
+  fn try_seek(&mut self, pos: Duration) -> Result<(), SeekError> {
+    self.input.try_seek(pos)?;
+
+    self.buffer.clear();
+    self.cursor = 0.0;
+    self.output.clear();
+    self.stretch = None;
+
+    return Ok(());
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::time::Duration;
+
+  use rodio::{buffer::SamplesBuffer, Source as _};
+
+  use super::{rate_source, PlaybackRate};
+
+  const SAMPLE_RATE: u32 = 44100;
+
+  fn sine(frequency: f32, seconds: f32) -> Vec<f32> {
+    let len = (SAMPLE_RATE as f32 * seconds) as usize;
+    return (0 .. len)
+      .map(|i| (std::f32::consts::TAU * frequency * i as f32 / SAMPLE_RATE as f32).sin())
+      .collect();
+  }
+
+  fn zero_crossings(samples: &[f32]) -> usize {
+    return samples.windows(2).filter(|x| (x[0] < 0.0) != (x[1] < 0.0)).count();
+  }
+
+  #[test]
+  fn passes_through_at_realtime() {
+    let data = sine(440.0, 0.5);
+    let source = rate_source(SamplesBuffer::new(1, SAMPLE_RATE, data.clone()), PlaybackRate::new());
+
+    assert_eq!(source.collect::<Vec<_>>(), data);
+  }
+
+  #[test]
+  fn resamples_without_pitch_preservation() {
+    let rate = PlaybackRate::new();
+    rate.set_rate(2.0);
+    rate.set_preserve_pitch(false);
+
+    let data = (0 .. 1000).map(|x| x as f32).collect::<Vec<_>>();
+    let output = rate_source(SamplesBuffer::new(1, SAMPLE_RATE, data), rate).collect::<Vec<_>>();
+
+    assert_eq!(output.len(), 500);
+    assert!(output.iter().enumerate().all(|(i, x)| *x == i as f32 * 2.0));
+  }
+
+  #[test]
+  fn stretches_with_pitch_preservation() {
+    let rate = PlaybackRate::new();
+    rate.set_rate(1.5);
+
+    let data = sine(440.0, 2.0);
+    let output = rate_source(SamplesBuffer::new(1, SAMPLE_RATE, data.clone()), rate).collect::<Vec<_>>();
+
+    // Shorter by the rate, up to a grain
+    let expected_len = data.len() as f64 / 1.5;
+    assert!((output.len() as f64 - expected_len).abs() < SAMPLE_RATE as f64 * 0.05);
+
+    // Same pitch, so as many zero crossings per second
+    let input_rate = zero_crossings(&data) as f64 / data.len() as f64;
+    let output_rate = zero_crossings(&output) as f64 / output.len() as f64;
+    assert!((output_rate / input_rate - 1.0).abs() < 0.05);
+  }
+
+  #[test]
+  fn seeks_in_input_time() {
+    let rate = PlaybackRate::new();
+    rate.set_rate(0.75);
+    rate.set_preserve_pitch(false);
+
+    let data = (0 .. SAMPLE_RATE * 2).map(|x| x as f32).collect::<Vec<_>>();
+    let mut source = rate_source(SamplesBuffer::new(1, SAMPLE_RATE, data), rate);
+    source.nth(1000);
+    source.try_seek(Duration::from_secs(1)).unwrap();
+
+    assert_eq!(source.next(), Some(SAMPLE_RATE as f32));
+  }
+}
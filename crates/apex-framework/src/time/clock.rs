@@ -12,6 +12,10 @@ pub trait AbstractClock {
 
   fn length(&self) -> Time;
   fn set_length(&mut self, value: Time);
+
+  /// Playback rate, `1.0` is realtime.
+  fn speed(&self) -> f64;
+  fn set_speed(&mut self, value: f64);
 }
 
 pub struct Clock {
@@ -19,6 +23,7 @@ pub struct Clock {
   last_time: Time,
 
   playing: bool,
+  speed: f64,
 
   length: Time,
 }
@@ -30,6 +35,7 @@ impl Clock {
       last_time: Time::zero(),
 
       playing: false,
+      speed: 1.0,
 
       length: Time::zero(),
     };
@@ -46,7 +52,7 @@ impl AbstractClock for Clock {
     if self.playing {
       let now = instant::Instant::now();
       let diff = now.duration_since(self.last_pause);
-      let time = self.last_time + diff.into();
+      let time = self.last_time + Time::from(diff) * self.speed;
 
       return time;
     } else {
@@ -61,7 +67,7 @@ impl AbstractClock for Clock {
   fn set_playing(&mut self, playing: bool) {
     let now = instant::Instant::now();
     let diff = now.duration_since(self.last_pause);
-    let time = self.last_time + diff.into();
+    let time = self.last_time + Time::from(diff) * self.speed;
 
     if playing {
       self.last_pause = now;
@@ -83,4 +89,15 @@ impl AbstractClock for Clock {
   fn length(&self) -> Time {
     return self.length;
   }
+
+  fn speed(&self) -> f64 {
+    return self.speed;
+  }
+
+  fn set_speed(&mut self, value: f64) {
+    // Time elapsed so far passed at the previous speed
+    self.last_time = self.position();
+    self.last_pause = Instant::now();
+    self.speed = value;
+  }
 }